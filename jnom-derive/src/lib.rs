@@ -0,0 +1,583 @@
+//! `#[derive(FromJson, ToJson)]` for the traits in `jnom_rs::typed`; enable them through the
+//! `derive` feature of `jnom-rs` rather than depending on this crate directly.
+//!
+//! Structs with named fields map to objects, newtype structs to their inner value, other
+//! tuple structs to arrays and unit structs to `null`. Enums are externally tagged: a unit
+//! variant is its name as a string, any other variant a single-member object
+//! `{"Variant": payload}`.
+//!
+//! Attributes, all under `#[json(...)]`:
+//!
+//! - container: `rename_all = "..."` (`lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`,
+//!   `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`), applied to fields of a struct and
+//!   to variants of an enum; `deny_unknown_fields`
+//! - field: `rename = "..."`; `default` or `default = "path::to::fn"` when the member may be
+//!   absent or `null`; `flatten` to read and write the field's members inline; `skip` to
+//!   leave the field out and fill it with `Default::default()`
+//! - variant: `rename = "..."`
+//!
+//! `Option` fields are optional without needing `default`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, ExprPath,
+    Fields, Generics, Ident, LitStr, Member, Token, Type,
+};
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_json(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// from_json Expand `impl FromJson`
+fn from_json(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = container_attrs(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(&data.fields, container.rename_all)?;
+                decode_named(&fields, quote!(Self), container.deny_unknown_fields)?
+            }
+            Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                no_field_attrs(&data.fields)?;
+                quote!(::jnom_rs::typed::FromJson::from_json(__value).map(Self))
+            }
+            Fields::Unnamed(_) => {
+                no_field_attrs(&data.fields)?;
+                decode_tuple(&data.fields, quote!(Self))
+            }
+            Fields::Unit => quote! {
+                ::jnom_rs::typed::__private::unit(__value)?;
+                ::core::result::Result::Ok(Self)
+            },
+        },
+        Data::Enum(data) => {
+            let mut units = Vec::new();
+            let mut tagged = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let key = variant_key(variant, container.rename_all)?;
+                let path = quote!(Self::#ident);
+                match &variant.fields {
+                    Fields::Unit => units.push(quote!(#key => ::core::result::Result::Ok(#path))),
+                    Fields::Named(_) => {
+                        let fields = named_fields(&variant.fields, None)?;
+                        let decode = decode_named(&fields, path, container.deny_unknown_fields)?;
+                        tagged.push(quote!(#key => #decode));
+                    }
+                    Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                        no_field_attrs(&variant.fields)?;
+                        tagged.push(quote! {
+                            #key => ::jnom_rs::typed::FromJson::from_json(__value).map(#path)
+                        });
+                    }
+                    Fields::Unnamed(_) => {
+                        no_field_attrs(&variant.fields)?;
+                        let decode = decode_tuple(&variant.fields, path);
+                        tagged.push(quote!(#key => { #decode }));
+                    }
+                }
+            }
+            let name = input.ident.to_string();
+            quote! {
+                match __value {
+                    ::jnom_rs::JsonValue::String(__tag) => match __tag.as_str() {
+                        #(#units,)*
+                        __other => ::core::result::Result::Err(
+                            ::jnom_rs::typed::__private::unknown_variant(__other, #name),
+                        ),
+                    },
+                    ::jnom_rs::JsonValue::Object(__obj) if __obj.len() == 1 => {
+                        let (__tag, __value) = __obj.iter().next().unwrap();
+                        let __decode = || -> ::core::result::Result<Self, ::jnom_rs::JError> {
+                            match __tag.as_str() {
+                                #(#tagged,)*
+                                __other => ::core::result::Result::Err(
+                                    ::jnom_rs::typed::__private::unknown_variant(__other, #name),
+                                ),
+                            }
+                        };
+                        __decode().map_err(|e| ::jnom_rs::typed::__private::at_key(e, __tag))
+                    }
+                    __other => ::core::result::Result::Err(::jnom_rs::typed::__private::expected(
+                        "string or single-member object",
+                        __other,
+                    )),
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "FromJson cannot be derived for unions",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let generics = bounded(&input.generics, quote!(::jnom_rs::typed::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::jnom_rs::typed::FromJson for #ident #ty_generics #where_clause {
+            fn from_json(
+                __value: &::jnom_rs::JsonValue,
+            ) -> ::core::result::Result<Self, ::jnom_rs::JError> {
+                #body
+            }
+        }
+    })
+}
+
+// to_json Expand `impl ToJson`
+fn to_json(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = container_attrs(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(&data.fields, container.rename_all)?;
+                encode_named(&fields, |field| {
+                    let member = &field.member;
+                    quote!(&self.#member)
+                })
+            }
+            Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                no_field_attrs(&data.fields)?;
+                quote!(::jnom_rs::typed::ToJson::to_json(&self.0))
+            }
+            Fields::Unnamed(unnamed) => {
+                no_field_attrs(&data.fields)?;
+                let items = (0..unnamed.unnamed.len()).map(syn::Index::from);
+                quote! {
+                    ::jnom_rs::typed::__private::array([
+                        #(::jnom_rs::typed::ToJson::to_json(&self.#items)),*
+                    ])
+                }
+            }
+            Fields::Unit => quote!(::jnom_rs::JsonValue::Null),
+        },
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let key = variant_key(variant, container.rename_all)?;
+                arms.push(match &variant.fields {
+                    Fields::Unit => quote! {
+                        Self::#ident => ::jnom_rs::typed::__private::string(#key)
+                    },
+                    Fields::Named(_) => {
+                        let fields = named_fields(&variant.fields, None)?;
+                        let bound = fields.iter().filter(|field| !field.attrs.skip);
+                        let members = bound.clone().map(|field| &field.member);
+                        let bindings = bound.map(|field| &field.binding);
+                        let encode = encode_named(&fields, |field| {
+                            let binding = &field.binding;
+                            quote!(#binding)
+                        });
+                        quote! {
+                            Self::#ident { #(#members: #bindings,)* .. } => {
+                                ::jnom_rs::typed::__private::tagged(#key, #encode)
+                            }
+                        }
+                    }
+                    Fields::Unnamed(unnamed) => {
+                        no_field_attrs(&variant.fields)?;
+                        let bindings = (0..unnamed.unnamed.len())
+                            .map(|idx| format_ident!("__{}", idx))
+                            .collect::<Vec<_>>();
+                        let payload = match bindings.as_slice() {
+                            [single] => quote!(::jnom_rs::typed::ToJson::to_json(#single)),
+                            _ => quote! {
+                                ::jnom_rs::typed::__private::array([
+                                    #(::jnom_rs::typed::ToJson::to_json(#bindings)),*
+                                ])
+                            },
+                        };
+                        quote! {
+                            Self::#ident(#(#bindings),*) => {
+                                ::jnom_rs::typed::__private::tagged(#key, #payload)
+                            }
+                        }
+                    }
+                });
+            }
+            quote! {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "ToJson cannot be derived for unions",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let generics = bounded(&input.generics, quote!(::jnom_rs::typed::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::jnom_rs::typed::ToJson for #ident #ty_generics #where_clause {
+            fn to_json(&self) -> ::jnom_rs::JsonValue {
+                #body
+            }
+        }
+    })
+}
+
+struct Field<'a> {
+    member: Member,
+    binding: Ident,
+    ty: &'a Type,
+    key: String,
+    attrs: FieldAttrs,
+}
+
+// named_fields Fields of a struct or struct variant with their attributes and JSON keys
+fn named_fields(fields: &Fields, rule: Option<Rule>) -> syn::Result<Vec<Field<'_>>> {
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.clone().expect("named field");
+            let attrs = field_attrs(&field.attrs)?;
+            let name = ident.unraw().to_string();
+            let key = match (&attrs.rename, rule) {
+                (Some(rename), _) => rename.clone(),
+                (None, Some(rule)) => rule.apply(&name),
+                (None, None) => name.clone(),
+            };
+            Ok(Field {
+                binding: format_ident!("__field_{}", name),
+                member: Member::Named(ident),
+                ty: &field.ty,
+                key,
+                attrs,
+            })
+        })
+        .collect()
+}
+
+// decode_named Read an object into the struct literal at `path`
+fn decode_named(
+    fields: &[Field],
+    path: TokenStream2,
+    deny_unknown: bool,
+) -> syn::Result<TokenStream2> {
+    if deny_unknown {
+        if let Some(field) = fields.iter().find(|field| field.attrs.flatten) {
+            return Err(Error::new_spanned(
+                &field.member,
+                "`flatten` cannot be combined with `deny_unknown_fields`",
+            ));
+        }
+    }
+    let reads = fields.iter().map(|field| {
+        let Field {
+            binding, ty, key, ..
+        } = field;
+        let attrs = &field.attrs;
+        if attrs.skip {
+            quote!()
+        } else if attrs.flatten {
+            quote! {
+                let #binding = match <#ty as ::jnom_rs::typed::FromJson>::from_json(__value) {
+                    ::core::result::Result::Ok(value) => ::core::option::Option::Some(value),
+                    ::core::result::Result::Err(e) => {
+                        __fields.error(e.message());
+                        ::core::option::Option::None
+                    }
+                };
+            }
+        } else if let Some(default) = &attrs.default {
+            let fallback = match default {
+                Some(path) => quote!(unwrap_or_else(#path)),
+                None => quote!(unwrap_or_default()),
+            };
+            quote!(let #binding = __fields.optional::<#ty>(#key).#fallback;)
+        } else if is_option(ty) {
+            quote!(let #binding = __fields.optional::<#ty>(#key).unwrap_or_default();)
+        } else {
+            quote!(let #binding = __fields.required::<#ty>(#key);)
+        }
+    });
+    let inits = fields.iter().map(|field| {
+        let Field {
+            member,
+            binding,
+            ty,
+            ..
+        } = field;
+        let attrs = &field.attrs;
+        if attrs.skip {
+            quote!(#member: ::core::default::Default::default())
+        } else if attrs.flatten || (attrs.default.is_none() && !is_option(ty)) {
+            quote!(#member: #binding.unwrap())
+        } else {
+            quote!(#member: #binding)
+        }
+    });
+    let deny = deny_unknown.then(|| quote!(__fields.deny_unknown();));
+    Ok(quote! {{
+        #[allow(unused_mut)]
+        let mut __fields = ::jnom_rs::typed::Fields::new(__value)?;
+        #(#reads)*
+        #deny
+        __fields.finish()?;
+        ::core::result::Result::Ok(#path { #(#inits),* })
+    }})
+}
+
+// decode_tuple Read an array of exactly as many elements as the tuple has fields
+fn decode_tuple(fields: &Fields, path: TokenStream2) -> TokenStream2 {
+    let len = fields.len();
+    let items = fields.iter().enumerate().map(|(idx, field)| {
+        let ty = &field.ty;
+        quote!(::jnom_rs::typed::__private::element::<#ty>(__items, #idx)?)
+    });
+    quote! {
+        let __items = ::jnom_rs::typed::__private::tuple(__value, #len)?;
+        ::core::result::Result::Ok(#path(#(#items),*))
+    }
+}
+
+// encode_named Build an object from the fields, each read through `access`
+fn encode_named(fields: &[Field], access: impl Fn(&Field) -> TokenStream2) -> TokenStream2 {
+    let inserts = fields
+        .iter()
+        .filter(|field| !field.attrs.skip)
+        .map(|field| {
+            let value = access(field);
+            let key = &field.key;
+            match field.attrs.flatten {
+                true => quote! {
+                    ::jnom_rs::typed::__private::extend(
+                        &mut __obj,
+                        ::jnom_rs::typed::ToJson::to_json(#value),
+                    );
+                },
+                false => quote! {
+                    __obj.insert(
+                        ::core::convert::Into::into(#key),
+                        ::jnom_rs::typed::ToJson::to_json(#value),
+                    );
+                },
+            }
+        });
+    quote! {{
+        #[allow(unused_mut)]
+        let mut __obj = ::jnom_rs::typed::__private::object();
+        #(#inserts)*
+        ::jnom_rs::JsonValue::Object(__obj)
+    }}
+}
+
+// bounded Generics with `bound` required of every type parameter
+fn bounded(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+// is_option Whether the type is spelled `Option<..>`
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    rename_all: Option<Rule>,
+    deny_unknown_fields: bool,
+}
+
+fn container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut out = ContainerAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                out.rename_all = Some(Rule::parse(&meta.value()?.parse()?)?);
+            } else if meta.path.is_ident("deny_unknown_fields") {
+                out.deny_unknown_fields = true;
+            } else {
+                return Err(meta.error("unknown json container attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(out)
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    // `Some(None)` for `default`, `Some(Some(path))` for `default = "path"`
+    default: Option<Option<ExprPath>>,
+    flatten: bool,
+    skip: bool,
+}
+
+fn field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut out = FieldAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                out.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("default") {
+                out.default = Some(match meta.input.peek(Token![=]) {
+                    true => Some(meta.value()?.parse::<LitStr>()?.parse()?),
+                    false => None,
+                });
+            } else if meta.path.is_ident("flatten") {
+                out.flatten = true;
+            } else if meta.path.is_ident("skip") {
+                out.skip = true;
+            } else {
+                return Err(meta.error("unknown json field attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(out)
+}
+
+// variant_key JSON name of an enum variant; only `rename` applies to variants
+fn variant_key(variant: &syn::Variant, rule: Option<Rule>) -> syn::Result<String> {
+    let attrs = field_attrs(&variant.attrs)?;
+    if attrs.default.is_some() || attrs.flatten || attrs.skip {
+        return Err(Error::new_spanned(
+            &variant.ident,
+            "variants only support the `rename` attribute",
+        ));
+    }
+    let name = variant.ident.unraw().to_string();
+    Ok(match (attrs.rename, rule) {
+        (Some(rename), _) => rename,
+        (None, Some(rule)) => rule.apply(&name),
+        (None, None) => name,
+    })
+}
+
+// no_field_attrs Reject `#[json]` on tuple fields, which have no key to rename or flatten
+fn no_field_attrs(fields: &Fields) -> syn::Result<()> {
+    match fields
+        .iter()
+        .flat_map(|field| &field.attrs)
+        .find(|attr| attr.path().is_ident("json"))
+    {
+        Some(attr) => Err(Error::new_spanned(
+            attr,
+            "json attributes are only supported on named fields",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Case convention of `rename_all`.
+#[derive(Clone, Copy)]
+enum Rule {
+    Lower,
+    Upper,
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+}
+
+impl Rule {
+    fn parse(lit: &LitStr) -> syn::Result<Rule> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Rule::Lower,
+            "UPPERCASE" => Rule::Upper,
+            "camelCase" => Rule::Camel,
+            "PascalCase" => Rule::Pascal,
+            "snake_case" => Rule::Snake,
+            "SCREAMING_SNAKE_CASE" => Rule::ScreamingSnake,
+            "kebab-case" => Rule::Kebab,
+            _ => return Err(Error::new(lit.span(), "unknown rename_all convention")),
+        })
+    }
+
+    // apply Rename a snake_case field or PascalCase variant name
+    fn apply(self, name: &str) -> String {
+        let words = words(name);
+        let lower = || words.iter().map(|w| w.to_lowercase());
+        match self {
+            Rule::Lower => name.to_lowercase(),
+            Rule::Upper => name.to_uppercase(),
+            Rule::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(idx, w)| match idx {
+                    0 => w.to_lowercase(),
+                    _ => capitalize(w),
+                })
+                .collect(),
+            Rule::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+            Rule::Snake => lower().collect::<Vec<_>>().join("_"),
+            Rule::ScreamingSnake => words
+                .iter()
+                .map(|w| w.to_uppercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            Rule::Kebab => lower().collect::<Vec<_>>().join("-"),
+        }
+    }
+}
+
+// words Split an identifier at underscores and lower-to-upper case changes
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for c in name.chars() {
+        let boundary = c == '_'
+            || (c.is_uppercase()
+                && current
+                    .chars()
+                    .last()
+                    .is_some_and(|prev| prev.is_lowercase() || prev.is_ascii_digit()));
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        if c != '_' {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,74 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{JsonValue, Map};
+
+// Nesting below which only scalars are produced, so fuzz inputs cannot blow the stack
+const MAX_DEPTH: usize = 8;
+
+// Elements or members per container
+const MAX_ITEMS: usize = 16;
+
+// Documents are built from the fuzzer's bytes: nulls, booleans, finite numbers, any
+// strings, and arrays and objects up to `MAX_DEPTH` deep. Custom literals and exact
+// numbers are never produced.
+impl<'a> Arbitrary<'a> for JsonValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_value(u, MAX_DEPTH)
+    }
+}
+
+fn arbitrary_value(u: &mut Unstructured, depth: usize) -> Result<JsonValue> {
+    let kinds = if depth == 0 { 3 } else { 5 };
+    Ok(match u.int_in_range(0..=kinds)? {
+        0 => JsonValue::Null,
+        1 => JsonValue::Bool(u.arbitrary()?),
+        2 => {
+            let n: f64 = u.arbitrary()?;
+            JsonValue::Number(if n.is_finite() { n } else { 0.0 })
+        }
+        3 => JsonValue::String(u.arbitrary()?),
+        4 => {
+            let mut arr = Vec::new();
+            while arr.len() < MAX_ITEMS && !u.is_empty() && u.arbitrary()? {
+                arr.push(arbitrary_value(u, depth - 1)?);
+            }
+            JsonValue::Array(arr)
+        }
+        _ => {
+            let mut obj = Map::default();
+            while obj.len() < MAX_ITEMS && !u.is_empty() && u.arbitrary()? {
+                let key = u.arbitrary()?;
+                obj.insert(key, arbitrary_value(u, depth - 1)?);
+            }
+            JsonValue::Object(obj)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::{parse, ser::to_string, JsonValue};
+
+    #[test]
+    fn it_arbitrary_round_trip() {
+        let mut seen_containers = 0;
+        for seed in 0..200u32 {
+            let bytes = (0..512u32)
+                .map(|i| (i.wrapping_mul(2_654_435_761) ^ seed.wrapping_mul(40_503)) as u8)
+                .collect::<Vec<_>>();
+            let value = JsonValue::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            if matches!(value, JsonValue::Array(_) | JsonValue::Object(_)) {
+                seen_containers += 1;
+            }
+            let json = to_string(&value);
+            assert_eq!(to_string(&parse(&json).unwrap()), json);
+        }
+        assert!(seen_containers > 0);
+
+        // an exhausted input still yields a value
+        let value = JsonValue::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(value, JsonValue::Null);
+    }
+}
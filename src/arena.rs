@@ -0,0 +1,341 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::hash::BuildHasherDefault;
+
+use bumpalo::{collections, Bump};
+use fnv::FnvHasher;
+use indexmap::IndexSet;
+
+use crate::{
+    error::{ErrorKind, JError},
+    token::{TokenBuffer, TokenKind},
+    value::unescape_with,
+    JsonValue,
+};
+
+/// JSON value whose nodes, element slices and strings all live in a bump arena.
+///
+/// Dropping (or resetting) the arena frees the whole tree at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaValue<'b> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(&'b str),
+    Array(&'b [ArenaValue<'b>]),
+    /// Members in source order.
+    Object(&'b [(&'b str, ArenaValue<'b>)]),
+}
+
+impl<'b> ArenaValue<'b> {
+    // get Look up an object member by key (linear in the member count)
+    pub fn get(&self, key: &str) -> Option<&ArenaValue<'b>> {
+        match self {
+            ArenaValue::Object(members) => members.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'b str> {
+        match self {
+            ArenaValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ArenaValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    // to_value Copy the tree out of the arena into an owned value
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            ArenaValue::Null => JsonValue::Null,
+            ArenaValue::Bool(b) => JsonValue::Bool(*b),
+            ArenaValue::Number(n) => JsonValue::Number(*n),
+            ArenaValue::String(s) => JsonValue::String(s.to_string()),
+            ArenaValue::Array(items) => {
+                JsonValue::Array(items.iter().map(Self::to_value).collect())
+            }
+            ArenaValue::Object(members) => JsonValue::Object(
+                members
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Deduplicates object keys allocated in one arena.
+///
+/// Record-shaped inputs repeat the same handful of keys in every element; with an
+/// interner each distinct key is stored once and every occurrence borrows that copy.
+/// One interner can be shared across documents parsed into the same arena.
+#[derive(Debug, Default)]
+pub struct KeyInterner<'b> {
+    keys: IndexSet<&'b str, BuildHasherDefault<FnvHasher>>,
+}
+
+impl<'b> KeyInterner<'b> {
+    pub fn new() -> Self {
+        KeyInterner::default()
+    }
+
+    // intern Return the arena copy of `key`, allocating it on first use
+    pub fn intern(&mut self, key: &str, bump: &'b Bump) -> &'b str {
+        match self.keys.get(key) {
+            Some(interned) => interned,
+            None => {
+                let interned = bump.alloc_str(key);
+                self.keys.insert(interned);
+                interned
+            }
+        }
+    }
+
+    // len Number of distinct keys interned so far
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+// parse_in Parse a complete JSON document into a tree allocated in `bump`
+pub fn parse_in<'b>(source: &str, bump: &'b Bump) -> Result<ArenaValue<'b>, JError> {
+    parse_in_with(source, bump, None)
+}
+
+// parse_in_interned Like `parse_in`, sharing one arena copy of every distinct object key
+pub fn parse_in_interned<'b>(
+    source: &str,
+    bump: &'b Bump,
+    interner: &mut KeyInterner<'b>,
+) -> Result<ArenaValue<'b>, JError> {
+    parse_in_with(source, bump, Some(interner))
+}
+
+fn parse_in_with<'b>(
+    source: &str,
+    bump: &'b Bump,
+    interner: Option<&mut KeyInterner<'b>>,
+) -> Result<ArenaValue<'b>, JError> {
+    let tokens = TokenBuffer::new(source)?;
+    let mut parser = ArenaParser {
+        tokens: &tokens,
+        pos: 0,
+        bump,
+        interner,
+    };
+    let value = parser.value()?;
+    match parser.tokens.text(parser.pos) {
+        None => Ok(value),
+        Some(text) => Err(JError::new(
+            ErrorKind::Syntax,
+            format!(
+                "Unexpected trailing input {text:?} at {}",
+                parser.start(parser.pos)
+            ),
+        )),
+    }
+}
+
+struct ArenaParser<'t, 's, 'b, 'i> {
+    tokens: &'t TokenBuffer<'s>,
+    pos: usize,
+    bump: &'b Bump,
+    interner: Option<&'i mut KeyInterner<'b>>,
+}
+
+impl<'t, 's, 'b> ArenaParser<'t, 's, 'b, '_> {
+    fn value(&mut self) -> Result<ArenaValue<'b>, JError> {
+        let (kind, text) = self.next()?;
+        Ok(match kind {
+            TokenKind::OpenBrace => self.object()?,
+            TokenKind::OpenBracket => self.array()?,
+            TokenKind::String => ArenaValue::String(self.string(text)?),
+            TokenKind::Number => ArenaValue::Number(text.parse().unwrap_or_default()),
+            TokenKind::True => ArenaValue::Bool(true),
+            TokenKind::False => ArenaValue::Bool(false),
+            TokenKind::Null => ArenaValue::Null,
+            _ => {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("Unexpected token {text:?} at {}", self.start(self.pos - 1)),
+                ))
+            }
+        })
+    }
+
+    fn array(&mut self) -> Result<ArenaValue<'b>, JError> {
+        let mut items = collections::Vec::new_in(self.bump);
+        if !self.eat(TokenKind::CloseBracket) {
+            loop {
+                items.push(self.value()?);
+                if self.eat(TokenKind::CloseBracket) {
+                    break;
+                }
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        Ok(ArenaValue::Array(items.into_bump_slice()))
+    }
+
+    fn object(&mut self) -> Result<ArenaValue<'b>, JError> {
+        let mut members = collections::Vec::new_in(self.bump);
+        if !self.eat(TokenKind::CloseBrace) {
+            loop {
+                let key = match self.next()? {
+                    (TokenKind::String, text) => self.key(text)?,
+                    (_, text) => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!(
+                                "Expected an object key, found {text:?} at {}",
+                                self.start(self.pos - 1)
+                            ),
+                        ))
+                    }
+                };
+                self.expect(TokenKind::Colon)?;
+                members.push((key, self.value()?));
+                if self.eat(TokenKind::CloseBrace) {
+                    break;
+                }
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        Ok(ArenaValue::Object(members.into_bump_slice()))
+    }
+
+    // string Decode a string literal (with quotes) into the arena
+    fn string(&self, literal: &str) -> Result<&'b str, JError> {
+        let raw = &literal[1..literal.len() - 1];
+        if !raw.contains('\\') {
+            return Ok(self.bump.alloc_str(raw));
+        }
+        let mut out = collections::String::with_capacity_in(raw.len(), self.bump);
+        unescape_with(raw, |c| out.push(c))?;
+        Ok(out.into_bump_str())
+    }
+
+    // key Decode an object key, going through the interner when there is one
+    fn key(&mut self, literal: &str) -> Result<&'b str, JError> {
+        let Some(interner) = self.interner.as_deref_mut() else {
+            return self.string(literal);
+        };
+        let raw = &literal[1..literal.len() - 1];
+        if !raw.contains('\\') {
+            return Ok(interner.intern(raw, self.bump));
+        }
+        let mut key = String::with_capacity(raw.len());
+        unescape_with(raw, |c| key.push(c))?;
+        Ok(interner.intern(&key, self.bump))
+    }
+
+    fn next(&mut self) -> Result<(TokenKind, &'s str), JError> {
+        let kind = self
+            .tokens
+            .kind(self.pos)
+            .ok_or_else(|| JError::new(ErrorKind::Eof, "Unexpected end of input"))?;
+        let text = self.tokens.text(self.pos).unwrap_or_default();
+        self.pos += 1;
+        Ok((kind, text))
+    }
+
+    fn start(&self, idx: usize) -> usize {
+        self.tokens.span(idx).map_or(0, |span| span.start)
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        let matched = self.tokens.kind(self.pos) == Some(kind);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), JError> {
+        match self.eat(kind) {
+            true => Ok(()),
+            false => Err(JError::new(
+                ErrorKind::Syntax,
+                format!(
+                    "JsonToken Kind {kind:?} does not match at {}",
+                    self.start(self.pos)
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bumpalo::Bump;
+
+    use crate::parse;
+
+    use super::{parse_in, parse_in_interned, ArenaValue, KeyInterner};
+
+    #[test]
+    fn it_parse_in_arena() {
+        let source =
+            r#"{"name": "John\nDoe", "scores": [100, 90], "address": {"city": "杭州"}, "x": null}"#;
+        let bump = Bump::new();
+        let value = parse_in(source, &bump).unwrap();
+
+        assert_eq!(
+            value.get("name").and_then(ArenaValue::as_str),
+            Some("John\nDoe")
+        );
+        assert_eq!(
+            value.get("address").and_then(|a| a.get("city")),
+            Some(&ArenaValue::String("杭州"))
+        );
+        assert_eq!(value.to_value(), parse(source).unwrap());
+    }
+
+    #[test]
+    fn it_parse_in_interned() {
+        let bump = Bump::new();
+        let mut interner = KeyInterner::new();
+        let first = parse_in_interned(
+            r#"[{"id": 1, "n\u0061me": "a"}, {"id": 2}]"#,
+            &bump,
+            &mut interner,
+        )
+        .unwrap();
+        let second = parse_in_interned(r#"{"id": 3, "name": "b"}"#, &bump, &mut interner).unwrap();
+
+        fn key<'b>(value: &ArenaValue<'b>, name: &str) -> &'b str {
+            match value {
+                ArenaValue::Object(members) => members.iter().find(|(k, _)| *k == name).unwrap().0,
+                _ => unreachable!(),
+            }
+        }
+        let ArenaValue::Array(records) = first else {
+            unreachable!()
+        };
+        assert_eq!(interner.len(), 2);
+        assert!(std::ptr::eq(key(&records[0], "id"), key(&records[1], "id")));
+        assert!(std::ptr::eq(key(&records[0], "id"), key(&second, "id")));
+        assert!(std::ptr::eq(key(&records[0], "name"), key(&second, "name")));
+    }
+
+    #[test]
+    fn it_parse_in_rejects_invalid_input() {
+        let bump = Bump::new();
+        assert!(parse_in("[1, 2", &bump).is_err());
+        assert!(parse_in("[1 2]", &bump).is_err());
+        assert!(parse_in(r#"{1: 2}"#, &bump).is_err());
+        assert!(parse_in("[] []", &bump).is_err());
+    }
+}
@@ -0,0 +1,527 @@
+//! Conversion of record-shaped JSON (an array of objects, or NDJSON with one object per
+//! line) into Arrow [`RecordBatch`]es for analytics ingestion.
+
+use std::{io::BufRead, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, ListArray, NullArray, RecordBatch,
+    RecordBatchOptions, StringArray, StructArray,
+};
+use arrow_buffer::{NullBuffer, OffsetBuffer};
+use arrow_schema::{ArrowError, DataType, Field, Fields, Schema, SchemaRef};
+
+use crate::{
+    error::{ErrorKind, JError},
+    parse_bytes, JsonValue, Map, Utf8Policy,
+};
+
+impl JsonValue {
+    // to_record_batch Convert an array of objects into a record batch, see [`to_record_batch`]
+    pub fn to_record_batch(&self, schema: Option<SchemaRef>) -> Result<RecordBatch, JError> {
+        let rows = self.as_array().ok_or_else(|| {
+            JError::new(
+                ErrorKind::Type,
+                format!("Expected an array of objects, found {}", self.type_name()),
+            )
+        })?;
+        to_record_batch(rows, schema)
+    }
+}
+
+/// Infer an Arrow schema covering every row, each of which must be an object.
+///
+/// Columns appear in first-seen order and are all nullable. Integral numbers map to
+/// `Int64` and other numbers to `Float64`, booleans to `Boolean`, strings to `Utf8`,
+/// arrays to `List` and objects to `Struct`. Columns holding several kinds of value are
+/// widened: `Int64` with `Float64` becomes `Float64`, a `null`-only column takes the type
+/// of any other, and every other mix becomes `Utf8`, with non-string values written as
+/// JSON text.
+pub fn infer_schema(rows: &[JsonValue]) -> Result<Schema, JError> {
+    let mut fields = Vec::new();
+    for (idx, row) in rows.iter().enumerate() {
+        merge_fields(&mut fields, record(idx, row)?);
+    }
+    Ok(Schema::new(fields))
+}
+
+/// Convert rows of objects into a record batch, with `schema` or one inferred from them.
+///
+/// Missing members and `null` become nulls. With a given schema, members it does not
+/// name are ignored and a value its column cannot hold is an [`ErrorKind::Type`] error
+/// naming the row and the field; `Utf8` columns accept any value, as JSON text.
+pub fn to_record_batch(
+    rows: &[JsonValue],
+    schema: Option<SchemaRef>,
+) -> Result<RecordBatch, JError> {
+    let schema = match schema {
+        Some(schema) => schema,
+        None => Arc::new(infer_schema(rows)?),
+    };
+    let records = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| record(idx, row))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let cells = records
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| (idx, row.get(field.name()).filter(|v| !v.is_null())))
+            .collect::<Vec<_>>();
+        columns.push(build_array(field.data_type(), field.name(), &cells)?);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    RecordBatch::try_new_with_options(schema, columns, &options).map_err(arrow_error)
+}
+
+/// Reads NDJSON (one JSON object per line) as record batches of up to `batch_size` rows.
+///
+/// Blank lines are skipped. Without a schema, the one inferred from the first batch is
+/// used for all of them. A line that is not a valid object ends the stream with an error
+/// giving its line number.
+pub struct NdjsonBatches<R> {
+    reader: R,
+    batch_size: usize,
+    schema: Option<SchemaRef>,
+    line: usize,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+// read_ndjson_batches Read NDJSON from `reader` as record batches, see [`NdjsonBatches`]
+pub fn read_ndjson_batches<R: BufRead>(
+    reader: R,
+    batch_size: usize,
+    schema: Option<SchemaRef>,
+) -> NdjsonBatches<R> {
+    NdjsonBatches {
+        reader,
+        batch_size: batch_size.max(1),
+        schema,
+        line: 0,
+        buf: Vec::new(),
+        done: false,
+    }
+}
+
+impl<R: BufRead> NdjsonBatches<R> {
+    // schema The schema of the batches, once given or inferred
+    pub fn schema(&self) -> Option<SchemaRef> {
+        self.schema.clone()
+    }
+
+    fn read_rows(&mut self) -> Result<Vec<JsonValue>, JError> {
+        let mut rows = Vec::new();
+        while rows.len() < self.batch_size {
+            self.buf.clear();
+            let read = self
+                .reader
+                .read_until(b'\n', &mut self.buf)
+                .map_err(|e| JError::new(ErrorKind::Io, format!("Failed to read NDJSON: {e}")))?;
+            if read == 0 {
+                self.done = true;
+                break;
+            }
+            self.line += 1;
+            if self.buf.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let at_line =
+                |e: JError| JError::new(e.kind(), format!("Line {}: {}", self.line, e.message()));
+            let row = parse_bytes(&self.buf, Utf8Policy::Error).map_err(at_line)?;
+            if row.as_object().is_none() {
+                return Err(at_line(JError::new(
+                    ErrorKind::Type,
+                    format!("Expected an object, found {}", row.type_name()),
+                )));
+            }
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonBatches<R> {
+    type Item = Result<RecordBatch, JError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let rows = match self.read_rows() {
+            Ok(rows) if rows.is_empty() => return None,
+            Ok(rows) => rows,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        let schema = match &self.schema {
+            Some(schema) => schema.clone(),
+            None => match infer_schema(&rows) {
+                Ok(schema) => self.schema.insert(Arc::new(schema)).clone(),
+                Err(e) => return Some(Err(e)),
+            },
+        };
+        Some(to_record_batch(&rows, Some(schema)))
+    }
+}
+
+fn record(idx: usize, row: &JsonValue) -> Result<&Map<String, JsonValue>, JError> {
+    row.as_object().ok_or_else(|| {
+        JError::new(
+            ErrorKind::Type,
+            format!(
+                "Expected an object at index {idx}, found {}",
+                row.type_name()
+            ),
+        )
+    })
+}
+
+fn infer_type(value: &JsonValue) -> DataType {
+    match value {
+        JsonValue::Null => DataType::Null,
+        JsonValue::Bool(_) => DataType::Boolean,
+        JsonValue::String(_) | JsonValue::Custom(..) => DataType::Utf8,
+        JsonValue::Array(items) => {
+            let item = items
+                .iter()
+                .map(infer_type)
+                .fold(DataType::Null, merge_types);
+            DataType::List(Arc::new(Field::new("item", item, true)))
+        }
+        JsonValue::Object(obj) => {
+            let mut fields = Vec::new();
+            merge_fields(&mut fields, obj);
+            DataType::Struct(Fields::from(fields))
+        }
+        number => match as_i64(number) {
+            Some(_) => DataType::Int64,
+            None => DataType::Float64,
+        },
+    }
+}
+
+// merge_fields Widen `fields` to hold the members of `obj`, adding new ones at the end
+fn merge_fields(fields: &mut Vec<Field>, obj: &Map<String, JsonValue>) {
+    for (key, value) in obj {
+        let ty = infer_type(value);
+        match fields.iter_mut().find(|field| field.name() == key) {
+            Some(field) => {
+                let merged = merge_types(field.data_type().clone(), ty);
+                *field = Field::new(key.as_str(), merged, true);
+            }
+            None => fields.push(Field::new(key.as_str(), ty, true)),
+        }
+    }
+}
+
+fn merge_types(a: DataType, b: DataType) -> DataType {
+    match (a, b) {
+        (DataType::Null, ty) | (ty, DataType::Null) => ty,
+        (a, b) if a == b => a,
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+            DataType::Float64
+        }
+        (DataType::List(a), DataType::List(b)) => {
+            let item = merge_types(a.data_type().clone(), b.data_type().clone());
+            DataType::List(Arc::new(Field::new("item", item, true)))
+        }
+        (DataType::Struct(a), DataType::Struct(b)) => {
+            let mut fields = a.iter().map(|f| f.as_ref().clone()).collect::<Vec<_>>();
+            for field in b.iter() {
+                match fields.iter_mut().find(|f| f.name() == field.name()) {
+                    Some(f) => {
+                        let merged = merge_types(f.data_type().clone(), field.data_type().clone());
+                        *f = Field::new(field.name(), merged, true);
+                    }
+                    None => fields.push(field.as_ref().clone()),
+                }
+            }
+            DataType::Struct(Fields::from(fields))
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+// as_i64 The number as an integer, if it is integral and in range
+fn as_i64(value: &JsonValue) -> Option<i64> {
+    #[cfg(feature = "num-bigint")]
+    if let JsonValue::BigInt(n) = value {
+        return num_traits::ToPrimitive::to_i64(n);
+    }
+    let n = value.as_f64()?;
+    (n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64).then_some(n as i64)
+}
+
+// build_array Build the column of `name` from its cells, each with the row it came from
+fn build_array(
+    ty: &DataType,
+    name: &str,
+    cells: &[(usize, Option<&JsonValue>)],
+) -> Result<ArrayRef, JError> {
+    let mismatch = |row: usize, value: &JsonValue| {
+        JError::new(
+            ErrorKind::Type,
+            format!(
+                "Row {row}, field {name}: expected {ty}, found {}",
+                value.type_name()
+            ),
+        )
+    };
+    let array: ArrayRef = match ty {
+        DataType::Null => {
+            if let Some((row, Some(value))) = cells.iter().find(|(_, v)| v.is_some()) {
+                return Err(mismatch(*row, value));
+            }
+            Arc::new(NullArray::new(cells.len()))
+        }
+        DataType::Boolean => Arc::new(
+            cells
+                .iter()
+                .map(|&(row, cell)| {
+                    cell.map(|v| v.as_bool().ok_or_else(|| mismatch(row, v)))
+                        .transpose()
+                })
+                .collect::<Result<BooleanArray, _>>()?,
+        ),
+        DataType::Int64 => Arc::new(
+            cells
+                .iter()
+                .map(|&(row, cell)| {
+                    cell.map(|v| as_i64(v).ok_or_else(|| mismatch(row, v)))
+                        .transpose()
+                })
+                .collect::<Result<Int64Array, _>>()?,
+        ),
+        DataType::Float64 => Arc::new(
+            cells
+                .iter()
+                .map(|&(row, cell)| {
+                    cell.map(|v| v.as_f64().ok_or_else(|| mismatch(row, v)))
+                        .transpose()
+                })
+                .collect::<Result<Float64Array, _>>()?,
+        ),
+        DataType::Utf8 => Arc::new(
+            cells
+                .iter()
+                .map(|(_, cell)| {
+                    cell.map(|v| match v {
+                        JsonValue::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                })
+                .collect::<StringArray>(),
+        ),
+        DataType::List(item) => {
+            let mut lengths = Vec::with_capacity(cells.len());
+            let mut items = Vec::new();
+            for &(row, cell) in cells {
+                let elements = match cell {
+                    None => &[][..],
+                    Some(JsonValue::Array(elements)) => elements.as_slice(),
+                    Some(value) => return Err(mismatch(row, value)),
+                };
+                lengths.push(elements.len());
+                items.extend(
+                    elements
+                        .iter()
+                        .map(|v| (row, Some(v).filter(|v| !v.is_null()))),
+                );
+            }
+            let values = build_array(item.data_type(), name, &items)?;
+            let list = ListArray::try_new(
+                item.clone(),
+                OffsetBuffer::from_lengths(lengths),
+                values,
+                nulls(cells),
+            )
+            .map_err(arrow_error)?;
+            Arc::new(list)
+        }
+        DataType::Struct(fields) => {
+            let mut members = Vec::with_capacity(cells.len());
+            for &(row, cell) in cells {
+                members.push(match cell {
+                    None => None,
+                    Some(JsonValue::Object(obj)) => Some(obj),
+                    Some(value) => return Err(mismatch(row, value)),
+                });
+            }
+            let mut children = Vec::with_capacity(fields.len());
+            for field in fields.iter() {
+                let column = cells
+                    .iter()
+                    .zip(&members)
+                    .map(|(&(row, _), obj)| {
+                        let value = obj.and_then(|obj| obj.get(field.name()));
+                        (row, value.filter(|v| !v.is_null()))
+                    })
+                    .collect::<Vec<_>>();
+                let path = format!("{name}.{}", field.name());
+                children.push(build_array(field.data_type(), &path, &column)?);
+            }
+            let array = StructArray::try_new_with_length(
+                fields.clone(),
+                children,
+                nulls(cells),
+                cells.len(),
+            )
+            .map_err(arrow_error)?;
+            Arc::new(array)
+        }
+        other => {
+            return Err(JError::new(
+                ErrorKind::Unsupported,
+                format!("Arrow type {other} is not supported"),
+            ))
+        }
+    };
+    Ok(array)
+}
+
+fn nulls(cells: &[(usize, Option<&JsonValue>)]) -> Option<NullBuffer> {
+    let valid = cells.iter().map(|(_, v)| v.is_some()).collect::<Vec<_>>();
+    valid.contains(&false).then(|| NullBuffer::from(valid))
+}
+
+fn arrow_error(e: ArrowError) -> JError {
+    JError::new(ErrorKind::Other, format!("Arrow: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        cast::AsArray,
+        types::{Float64Type, Int64Type},
+        Array,
+    };
+    use arrow_schema::{DataType, Field, Schema};
+
+    use crate::{parse, ErrorKind};
+
+    use super::{infer_schema, read_ndjson_batches};
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_record_batch() {
+        let rows = parse(
+            r#"[
+                {"id": 1, "name": "ann", "score": 1.5, "tags": ["a", "b"], "addr": {"city": "Oslo"}},
+                {"id": 2, "score": 2, "tags": [], "addr": {"city": "Rome", "zip": "00100"}, "extra": true},
+                {"id": 3, "name": null, "score": null, "tags": null, "addr": null, "extra": "yes"}
+            ]"#,
+        )
+        .unwrap();
+        let schema = infer_schema(rows.as_array().unwrap()).unwrap();
+        let names = schema
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["id", "name", "score", "tags", "addr", "extra"]);
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(2).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(5).data_type(), &DataType::Utf8);
+
+        let batch = rows.to_record_batch(None).unwrap();
+        assert_eq!((batch.num_rows(), batch.num_columns()), (3, 6));
+        let ids = batch.column(0).as_primitive::<Int64Type>();
+        assert_eq!(ids.values().to_vec(), [1, 2, 3]);
+        let names = batch.column(1).as_string::<i32>();
+        assert_eq!((names.value(0), names.null_count()), ("ann", 2));
+        let scores = batch.column(2).as_primitive::<Float64Type>();
+        assert_eq!((scores.value(1), scores.is_null(2)), (2.0, true));
+
+        let tags = batch.column(3).as_list::<i32>();
+        assert_eq!(tags.value_offsets(), [0, 2, 2, 2]);
+        assert!(tags.is_valid(1) && tags.is_null(2));
+        assert_eq!(tags.values().as_string::<i32>().value(1), "b");
+
+        let addr = batch.column(4).as_struct();
+        assert_eq!(addr.column_names(), ["city", "zip"]);
+        assert_eq!(addr.column(0).as_string::<i32>().value(1), "Rome");
+        assert!(addr.column(1).is_null(0) && addr.is_null(2));
+
+        let extra = batch.column(5).as_string::<i32>();
+        assert_eq!((extra.value(1), extra.value(2)), ("true", "yes"));
+        assert!(extra.is_null(0));
+    }
+
+    #[test]
+    fn it_record_batch_with_schema() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+        let rows = parse(r#"[{"id": 1, "label": [1], "skip": 0}, {"id": 2}]"#).unwrap();
+        let batch = rows.to_record_batch(Some(schema.clone())).unwrap();
+        assert_eq!(batch.schema(), schema);
+        assert_eq!(batch.column(1).as_string::<i32>().value(0), "[1]");
+
+        let err = parse(r#"[{"id": 1}, {"id": 1.5}]"#)
+            .unwrap()
+            .to_record_batch(Some(schema.clone()))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Type);
+        assert!(err.message().contains("Row 1, field id"));
+        assert!(parse(r#"[{"id": null}]"#)
+            .unwrap()
+            .to_record_batch(Some(schema))
+            .is_err());
+
+        let unsupported = Arc::new(Schema::new(vec![Field::new("d", DataType::Date32, true)]));
+        let err = parse(r#"[{"d": 1}]"#)
+            .unwrap()
+            .to_record_batch(Some(unsupported))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        assert!(parse(r#"{"id": 1}"#)
+            .unwrap()
+            .to_record_batch(None)
+            .is_err());
+        assert!(parse(r#"[{"id": 1}, 2]"#)
+            .unwrap()
+            .to_record_batch(None)
+            .is_err());
+        assert_eq!(
+            parse("[]")
+                .unwrap()
+                .to_record_batch(None)
+                .unwrap()
+                .num_rows(),
+            0
+        );
+        let empty = parse("[{}, {}]").unwrap().to_record_batch(None).unwrap();
+        assert_eq!((empty.num_rows(), empty.num_columns()), (2, 0));
+    }
+
+    #[test]
+    fn it_ndjson_batches() {
+        let source =
+            "{\"a\": 1, \"b\": [1]}\n\n{\"a\": 2}\n{\"a\": 3, \"b\": null}\n{\"a\": 4.5}\n";
+        let mut batches = read_ndjson_batches(source.as_bytes(), 2, None);
+        let first = batches.next().unwrap().unwrap();
+        assert_eq!(first.num_rows(), 2);
+        assert_eq!(first.schema().field(0).data_type(), &DataType::Int64);
+        assert_eq!(batches.schema(), Some(first.schema()));
+        let err = batches.next().unwrap().unwrap_err();
+        assert!(err.message().contains("Row 1, field a"));
+        assert!(batches.next().is_none());
+
+        let mut bad = read_ndjson_batches("{\"a\": 1}\n[1]\n".as_bytes(), 10, None);
+        let err = bad.next().unwrap().unwrap_err();
+        assert!(err.message().starts_with("Line 2:"));
+        assert!(bad.next().is_none());
+    }
+}
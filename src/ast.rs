@@ -0,0 +1,283 @@
+//! Syntax tree that keeps the byte span of every value and key, the bridge between an
+//! editor's cursor positions and the parse result.
+//!
+//! ```
+//! use jnom_rs::ast::parse_ast;
+//!
+//! let source = r#"{"user": {"tags": ["a", "b"]}}"#;
+//! let ast = parse_ast(source).unwrap();
+//! let found = ast.node_at(source.find("\"b\"").unwrap()).unwrap();
+//! assert_eq!(found.pointer, "/user/tags/1");
+//! assert_eq!(&source[found.node.span.clone()], "\"b\"");
+//! ```
+
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    combinators::{comma_list0, context, kind_of, no_match, IResult},
+    error::{ErrorKind, JError},
+    parse_string,
+    pointer::escape_token,
+    token::{JsonTokenKind, TokenInput, TokenKind, TokenStream},
+    value::unescape_cow,
+    JsonExpr,
+};
+
+/// A value and the bytes of the source it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    pub span: Range<usize>,
+    pub kind: NodeKind<'a>,
+}
+
+/// What a [`Node`] holds. Strings and keys are kept as written, escapes included.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(&'a str),
+    Custom(&'static str, &'a str),
+    Array(Vec<Node<'a>>),
+    /// Members in source order, duplicates included.
+    Object(Vec<Member<'a>>),
+}
+
+/// An object member: its key, where the key is written, and its value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member<'a> {
+    pub key: &'a str,
+    pub key_span: Range<usize>,
+    pub value: Node<'a>,
+}
+
+impl Member<'_> {
+    // key_str The key with its escapes decoded
+    pub fn key_str(&self) -> Cow<'_, str> {
+        // The parser already checked the escapes
+        unescape_cow(self.key).unwrap_or(Cow::Borrowed(self.key))
+    }
+}
+
+/// The result of a lookup by byte offset, see [`Node::node_at`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location<'n, 'a> {
+    /// The innermost value whose span covers the offset.
+    pub node: &'n Node<'a>,
+    /// JSON pointer from the root to `node`.
+    pub pointer: String,
+    /// The member whose key covers the offset, in which case `node` is its value.
+    pub key: Option<&'n Member<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// Find the innermost value covering byte `offset`, with its JSON pointer.
+    ///
+    /// Spans run from the first byte of a value up to, not including, its end, so an
+    /// offset on whitespace or punctuation between members finds their container. An
+    /// offset on a key finds the member's value, with [`Location::key`] set.
+    pub fn node_at(&self, offset: usize) -> Option<Location<'_, 'a>> {
+        if !self.span.contains(&offset) {
+            return None;
+        }
+        let mut location = Location {
+            node: self,
+            pointer: String::new(),
+            key: None,
+        };
+        loop {
+            let next = match &location.node.kind {
+                NodeKind::Array(items) => items
+                    .iter()
+                    .enumerate()
+                    .find(|(_, item)| item.span.contains(&offset))
+                    .map(|(idx, item)| (format!("{idx}"), item, None)),
+                NodeKind::Object(members) => members
+                    .iter()
+                    .find(|m| m.key_span.contains(&offset) || m.value.span.contains(&offset))
+                    .map(|m| {
+                        let key = m.key_span.contains(&offset).then_some(m);
+                        (escape_token(&m.key_str()), &m.value, key)
+                    }),
+                _ => None,
+            };
+            let Some((token, node, key)) = next else {
+                return Some(location);
+            };
+            location.pointer.push('/');
+            location.pointer.push_str(&token);
+            location.node = node;
+            location.key = key;
+            if key.is_some() {
+                return Some(location);
+            }
+        }
+    }
+}
+
+// parse_ast Parse a complete JSON document into a span-annotated tree
+pub fn parse_ast(source: &str) -> Result<Node<'_>, JError> {
+    match node(TokenStream::new(source)) {
+        Ok((rest, node)) => match (rest.peek(), rest.lex_error()) {
+            (Some(token), _) => Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Unexpected trailing input {token:?}"),
+            )
+            .with_span(token.span.clone())),
+            (None, Some(e)) => Err(e),
+            (None, None) => Ok(node),
+        },
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e),
+        Err(nom::Err::Incomplete(_)) => Err(JError::new(ErrorKind::Eof, "Unexpected end of input")),
+    }
+    .map_err(|e| e.locate_document(source))
+}
+
+fn node<'a, I: TokenInput<'a>>(i: I) -> IResult<I, Node<'a>> {
+    let (start, kind) = match i.peek() {
+        Some(token) => (token.span.clone(), token.kind.clone()),
+        None => return Err(no_match(&i, "Expected a value")),
+    };
+    let (i, kind, end) = match kind {
+        JsonTokenKind::OpenBrace => {
+            let (i, _) = kind_of(TokenKind::OpenBrace)(i)?;
+            let (i, members) = comma_list0(context("object member", member))(i)?;
+            let (i, end) = close(i, TokenKind::CloseBrace)?;
+            (i, NodeKind::Object(members), end)
+        }
+        JsonTokenKind::OpenBracket => {
+            let (i, _) = kind_of(TokenKind::OpenBracket)(i)?;
+            let (i, items) = comma_list0(context("array element", node))(i)?;
+            let (i, end) = close(i, TokenKind::CloseBracket)?;
+            (i, NodeKind::Array(items), end)
+        }
+        JsonTokenKind::String => match parse_string(i)? {
+            (i, JsonExpr::String(s)) => (i, NodeKind::String(s), start.end),
+            _ => unreachable!("a string token parses as a string"),
+        },
+        JsonTokenKind::Number(n) => (i.advance(), NodeKind::Number(n), start.end),
+        JsonTokenKind::True => (i.advance(), NodeKind::Bool(true), start.end),
+        JsonTokenKind::False => (i.advance(), NodeKind::Bool(false), start.end),
+        JsonTokenKind::Null => (i.advance(), NodeKind::Null, start.end),
+        JsonTokenKind::Custom(tag) => {
+            let at = i.peek().map_or("", |token| token.text());
+            (i.advance(), NodeKind::Custom(tag, at), start.end)
+        }
+        _ => return Err(no_match(&i, "Expected a value")),
+    };
+    Ok((
+        i,
+        Node {
+            span: start.start..end,
+            kind,
+        },
+    ))
+}
+
+fn member<'a, I: TokenInput<'a>>(i: I) -> IResult<I, Member<'a>> {
+    let key_span = match i.peek() {
+        Some(token) => token.span.clone(),
+        None => return Err(no_match(&i, "Expected an object key")),
+    };
+    let (i, key) = match parse_string(i)? {
+        (i, JsonExpr::String(key)) => (i, key),
+        _ => unreachable!("a string token parses as a string"),
+    };
+    let (i, _) = kind_of(TokenKind::Colon)(i)?;
+    let (i, value) = node(i)?;
+    Ok((
+        i,
+        Member {
+            key,
+            key_span,
+            value,
+        },
+    ))
+}
+
+// close Consume the closing bracket of a container, returning where it ends
+fn close<'a, I: TokenInput<'a>>(i: I, kind: TokenKind) -> IResult<I, usize> {
+    let end = i.peek().map_or(0, |token| token.span.end);
+    let (i, _) = kind_of(kind)(i)?;
+    Ok((i, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{token_at_offset, JsonTokenKind, TokenBuffer};
+
+    use super::{parse_ast, NodeKind};
+
+    #[test]
+    fn it_parse_ast_spans() {
+        let source = r#" {"a~/b": [1, true, {"c": null}], "s": "x\"y", "a~/b": 2} "#;
+        let ast = parse_ast(source).unwrap();
+        assert_eq!(ast.span, 1..source.len() - 1);
+        let NodeKind::Object(members) = &ast.kind else {
+            panic!("expected an object");
+        };
+        assert_eq!(members.len(), 3);
+        assert_eq!(&source[members[0].key_span.clone()], r#""a~/b""#);
+        assert_eq!(
+            &source[members[0].value.span.clone()],
+            r#"[1, true, {"c": null}]"#
+        );
+        assert_eq!(members[1].value.kind, NodeKind::String(r#"x\"y"#));
+        assert_eq!(members[2].key_str(), "a~/b");
+
+        assert!(parse_ast("[1, ]").is_err());
+        assert!(parse_ast("[1] 2").is_err());
+        assert!(parse_ast(r#"{"a" 1}"#).is_err());
+        assert!(parse_ast(r#"["\x"]"#).is_err());
+        assert!(parse_ast("[@]").is_err());
+    }
+
+    #[test]
+    fn it_node_at() {
+        let source = r#"{"a~/b": [1, true, {"c": null}], "s": "x"}"#;
+        let ast = parse_ast(source).unwrap();
+        let at = |needle: &str| ast.node_at(source.find(needle).unwrap()).unwrap();
+
+        let found = at("true");
+        assert_eq!(found.pointer, "/a~0~1b/1");
+        assert_eq!(found.node.kind, NodeKind::Bool(true));
+        assert!(found.key.is_none());
+
+        let found = at("null");
+        assert_eq!(found.pointer, "/a~0~1b/2/c");
+        assert_eq!(&source[found.node.span.clone()], "null");
+
+        // a key finds its member's value
+        let found = at("\"c\"");
+        assert_eq!(found.pointer, "/a~0~1b/2/c");
+        assert_eq!(found.key.map(|m| m.key), Some("c"));
+
+        // punctuation and whitespace find the container
+        let found = at(", true");
+        assert_eq!(found.pointer, "/a~0~1b");
+        assert_eq!(ast.node_at(0).unwrap().pointer, "");
+        assert!(ast.node_at(source.len()).is_none());
+    }
+
+    #[test]
+    fn it_token_at_offset() {
+        let source = r#"{"a": [10, @x]}"#;
+        let token = token_at_offset(source, 8).unwrap();
+        assert_eq!((token.text(), token.span.clone()), ("10", 7..9));
+        assert_eq!(token_at_offset(source, 7).unwrap().text(), "10");
+        assert!(token_at_offset(source, 9).unwrap().kind == JsonTokenKind::Comma);
+        assert!(token_at_offset(source, 10).is_none());
+        assert_eq!(
+            token_at_offset(source, 11).unwrap().kind,
+            JsonTokenKind::Error
+        );
+        assert!(token_at_offset(source, source.len()).is_none());
+
+        let buffer = TokenBuffer::new(r#"{"a": [10]}"#).unwrap();
+        assert_eq!(buffer.token_at(8), Some(4));
+        assert_eq!(buffer.token_at(5), None);
+        assert_eq!(buffer.token_at(0), Some(0));
+        assert_eq!(buffer.token_at(11), None);
+    }
+}
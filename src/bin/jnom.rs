@@ -0,0 +1,266 @@
+use std::{
+    io::{Read, Write},
+    process::ExitCode,
+};
+
+use jnom_rs::{
+    diff::{diff, report, to_patch, ReportOptions},
+    lines::LineIndex,
+    parse,
+    ser::{to_string_with, try_to_string_with, write_colored, NonFinite, SerializeOptions, Style},
+    ErrorKind, JError, JsonValue,
+};
+
+const USAGE: &str = "\
+Usage: jnom <command> [options] [FILE]
+
+Commands:
+  validate [FILE...]                   check that every input is well-formed JSON
+  pretty [--style NAME] [--indent N] [--sort-keys] [--color] [FILE]
+                                       print the document indented; styles are
+                                       compact, pretty, prettier and aligned
+  minify [FILE]                        print the document without whitespace
+  get <POINTER> [FILE]                 print the value at a JSON pointer
+  diff [--report] [--unified] [--color] <FILE> <FILE>
+                                       print a JSON Patch from the first to the second
+                                       document, or a readable report of the changes
+
+FILE defaults to standard input; `-` reads standard input explicitly.
+
+Exit status: 0 on success, 1 when the document is invalid, the pointer is
+missing or the documents differ, 2 on usage or I/O errors.";
+
+/// Failure of a command, mapped onto the process exit status.
+enum Failure {
+    /// The command ran but the answer is negative (invalid input, no match, difference).
+    Negative(String),
+    Usage(String),
+    Io(String),
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("validate") => validate(&args[1..]),
+        Some("pretty") => pretty(&args[1..]),
+        Some("minify") => minify(&args[1..]),
+        Some("get") => get(&args[1..]),
+        Some("diff") => diff_files(&args[1..]),
+        Some("-h" | "--help" | "help") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(other) => Err(Failure::Usage(format!("unknown command {other:?}"))),
+        None => Err(Failure::Usage("missing command".to_string())),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Negative(msg)) => {
+            if !msg.is_empty() {
+                eprintln!("{msg}");
+            }
+            ExitCode::from(1)
+        }
+        Err(Failure::Usage(msg)) => {
+            eprintln!("jnom: {msg}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(Failure::Io(msg)) => {
+            eprintln!("jnom: {msg}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn validate(args: &[String]) -> Result<(), Failure> {
+    let files = if args.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.to_vec()
+    };
+    let mut invalid = 0;
+    for file in &files {
+        let source = read_input(file)?;
+        if let Err(errors) = jnom_rs::validate(&source) {
+            for e in &errors {
+                eprintln!("{}", describe(file, &source, e));
+            }
+            invalid += 1;
+        }
+    }
+    match invalid {
+        0 => Ok(()),
+        _ => Err(Failure::Negative(String::new())),
+    }
+}
+
+fn pretty(args: &[String]) -> Result<(), Failure> {
+    let mut options = SerializeOptions::pretty();
+    let mut colored = false;
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--style" => {
+                let style = args
+                    .next()
+                    .and_then(|name| Style::from_name(name))
+                    .ok_or_else(|| {
+                        Failure::Usage(
+                            "--style expects compact, pretty, prettier or aligned".to_string(),
+                        )
+                    })?;
+                options = SerializeOptions::style(style);
+            }
+            "--indent" => {
+                let width = args
+                    .next()
+                    .and_then(|w| w.parse().ok())
+                    .ok_or_else(|| Failure::Usage("--indent expects a number".to_string()))?;
+                options = options.indent(width);
+            }
+            "--sort-keys" => options = options.sort_keys(),
+            "--color" => colored = true,
+            _ => file = Some(single_file(file, arg)?),
+        }
+    }
+    print(file.as_deref().unwrap_or("-"), &options, colored)
+}
+
+fn minify(args: &[String]) -> Result<(), Failure> {
+    let file = match args {
+        [] => "-",
+        [file] => file.as_str(),
+        _ => return Err(Failure::Usage("minify takes at most one file".to_string())),
+    };
+    print(file, &SerializeOptions::compact(), false)
+}
+
+fn get(args: &[String]) -> Result<(), Failure> {
+    let (pointer, file) = match args {
+        [pointer] => (pointer, "-"),
+        [pointer, file] => (pointer, file.as_str()),
+        _ => {
+            return Err(Failure::Usage(
+                "get expects a pointer and at most one file".to_string(),
+            ))
+        }
+    };
+    let value = load(file)?;
+    match value.pointer(pointer) {
+        Some(found) => emit(&to_string_with(found, &SerializeOptions::pretty())),
+        None => Err(Failure::Negative(format!("jnom: no value at {pointer:?}"))),
+    }
+}
+
+fn diff_files(args: &[String]) -> Result<(), Failure> {
+    let mut options = ReportOptions::default();
+    let mut readable = false;
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--report" => readable = true,
+            "--unified" => (readable, options.unified) = (true, true),
+            "--color" => (readable, options.colors) = (true, true),
+            _ if arg.starts_with("--") => {
+                return Err(Failure::Usage(format!("unknown option {arg:?}")))
+            }
+            _ => files.push(arg),
+        }
+    }
+    let [from, to] = files[..] else {
+        return Err(Failure::Usage("diff expects two files".to_string()));
+    };
+    let (from, to) = (load(from)?, load(to)?);
+    let ops = diff(&from, &to);
+    if ops.is_empty() {
+        return Ok(());
+    }
+    match readable {
+        true => emit(&report(&from, &to, &options))?,
+        false => emit(&to_string_with(
+            &to_patch(&ops),
+            &SerializeOptions::pretty(),
+        ))?,
+    }
+    Err(Failure::Negative(String::new()))
+}
+
+fn single_file(current: Option<String>, arg: &str) -> Result<String, Failure> {
+    match current {
+        None if !arg.starts_with("--") => Ok(arg.to_string()),
+        None => Err(Failure::Usage(format!("unknown option {arg:?}"))),
+        Some(_) => Err(Failure::Usage("expected a single file".to_string())),
+    }
+}
+
+fn load(file: &str) -> Result<JsonValue, Failure> {
+    let source = read_input(file)?;
+    parse(&source).map_err(|e| Failure::Negative(describe(file, &source, &e)))
+}
+
+// describe Error message with the offending source line when the error has a span
+fn describe(file: &str, source: &str, e: &JError) -> String {
+    let message = e.message();
+    match e.span() {
+        Some(span) if span.end <= source.len() => format!(
+            "{}: {}",
+            display_name(file),
+            LineIndex::new(source).render(span, &message)
+        ),
+        _ => format!("{}: {message}", display_name(file)),
+    }
+}
+
+// print Lay the document out again from its value, so every style normalizes numbers,
+// escapes and duplicate keys alike
+//
+// A number beyond the f64 range fails the command rather than being printed as null.
+fn print(file: &str, options: &SerializeOptions, colored: bool) -> Result<(), Failure> {
+    let value = load(file)?;
+    let options = options.clone().non_finite(NonFinite::Error);
+    let out = match colored {
+        true => {
+            let mut out = String::new();
+            write_colored(&mut out, &value, &options)
+                .map(|_| out)
+                .map_err(|_| {
+                    JError::new(
+                        ErrorKind::Unsupported,
+                        "Cannot serialize a non-finite number",
+                    )
+                })
+        }
+        false => try_to_string_with(&value, &options),
+    };
+    out.map_err(|e| Failure::Negative(format!("{}: {}", display_name(file), e.message())))
+        .and_then(|out| emit(&out))
+}
+
+fn read_input(file: &str) -> Result<String, Failure> {
+    let mut source = String::new();
+    let result = if file == "-" {
+        std::io::stdin().read_to_string(&mut source)
+    } else {
+        std::fs::File::open(file).and_then(|mut f| f.read_to_string(&mut source))
+    };
+    result
+        .map(|_| source)
+        .map_err(|e| Failure::Io(format!("{}: {e}", display_name(file))))
+}
+
+fn display_name(file: &str) -> &str {
+    if file == "-" {
+        "<stdin>"
+    } else {
+        file
+    }
+}
+
+fn emit(output: &str) -> Result<(), Failure> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{output}")
+        .and_then(|_| stdout.flush())
+        .map_err(|e| Failure::Io(format!("<stdout>: {e}")))
+}
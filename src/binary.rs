@@ -0,0 +1,55 @@
+//! Base64-encoded binary blobs inside string values, behind the `base64` feature.
+
+use alloc::vec::Vec;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{error::JError, pointer::type_mismatch, JsonValue};
+
+impl JsonValue {
+    // as_base64_bytes Decode a string value as standard, padded base64
+    pub fn as_base64_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            JsonValue::String(s) => STANDARD.decode(s).ok(),
+            _ => None,
+        }
+    }
+
+    // get_base64_at Look up and decode a base64 string by JSON pointer
+    pub fn get_base64_at(&self, pointer: &str) -> Result<Vec<u8>, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_base64_bytes()
+            .ok_or_else(|| type_mismatch("base64 string", pointer, found))
+    }
+
+    // from_bytes_base64 Wrap binary data as a standard, padded base64 string value
+    pub fn from_bytes_base64(bytes: &[u8]) -> JsonValue {
+        JsonValue::String(STANDARD.encode(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonValue};
+
+    #[test]
+    fn it_base64_bytes() {
+        let doc = parse(r#"{"blob": "AAEC/w==", "bad": "AAEC/w", "n": 1}"#).unwrap();
+
+        assert_eq!(doc.get_base64_at("/blob").unwrap(), [0, 1, 2, 255]);
+        assert_eq!(
+            doc.get_base64_at("/bad").unwrap_err().message(),
+            "expected base64 string at /bad, found string"
+        );
+        assert!(doc.get("n").unwrap().as_base64_bytes().is_none());
+        assert_eq!(
+            JsonValue::String(String::new()).as_base64_bytes(),
+            Some(Vec::new())
+        );
+
+        let value = JsonValue::from_bytes_base64(&[0, 1, 2, 255]);
+        assert_eq!(value, JsonValue::String("AAEC/w==".to_string()));
+        assert_eq!(value.as_base64_bytes().unwrap(), [0, 1, 2, 255]);
+    }
+}
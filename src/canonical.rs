@@ -0,0 +1,193 @@
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::Write,
+    hash::{BuildHasherDefault, Hash, Hasher},
+};
+
+use fnv::FnvHasher;
+
+use crate::{
+    ser::{write_escaped, write_scientific},
+    JsonValue,
+};
+
+// to_canonical_string Serialize the value in the RFC 8785 (JCS) canonical form
+//
+// Members are sorted by the UTF-16 code units of their keys, there is no whitespace, and
+// numbers are written the way ECMAScript's `Number.prototype.toString` does, so equal
+// documents produce identical bytes whatever their original formatting.
+pub fn to_canonical_string(value: &JsonValue) -> String {
+    let mut out = String::new();
+    write_canonical(&mut out, value).expect("writing to a String cannot fail");
+    out
+}
+
+pub fn write_canonical<W: Write>(w: &mut W, value: &JsonValue) -> core::fmt::Result {
+    match value {
+        JsonValue::Null => w.write_str("null"),
+        JsonValue::Bool(b) => write!(w, "{b}"),
+        JsonValue::Number(n) => write_es_number(w, *n),
+        // RFC 8785 numbers are IEEE doubles, so big integers are rounded like any other
+        #[cfg(feature = "num-bigint")]
+        JsonValue::BigInt(_) => write_es_number(w, value.as_f64().unwrap_or(f64::NAN)),
+        #[cfg(feature = "rust_decimal")]
+        JsonValue::Decimal(_) => write_es_number(w, value.as_f64().unwrap_or(f64::NAN)),
+        JsonValue::String(s) | JsonValue::Custom(_, s) => write_escaped(w, s),
+        JsonValue::Array(arr) => {
+            w.write_char('[')?;
+            for (idx, item) in arr.iter().enumerate() {
+                if idx > 0 {
+                    w.write_char(',')?;
+                }
+                write_canonical(w, item)?;
+            }
+            w.write_char(']')
+        }
+        JsonValue::Object(obj) => {
+            let mut members = obj.iter().collect::<Vec<_>>();
+            members.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            w.write_char('{')?;
+            for (idx, (key, item)) in members.into_iter().enumerate() {
+                if idx > 0 {
+                    w.write_char(',')?;
+                }
+                write_escaped(w, key)?;
+                w.write_char(':')?;
+                write_canonical(w, item)?;
+            }
+            w.write_char('}')
+        }
+    }
+}
+
+// write_es_number Write a number like ECMAScript's `Number.prototype.toString`
+//
+// Non-finite numbers have no JSON form and are written as `null`, as the serializer does.
+pub(crate) fn write_es_number<W: Write>(w: &mut W, n: f64) -> core::fmt::Result {
+    match n.is_finite() {
+        true => write_scientific(w, n, -6, 21),
+        false => w.write_str("null"),
+    }
+}
+
+impl JsonValue {
+    // canonical_digest Stable 64-bit FNV-1a digest of the canonical form
+    pub fn canonical_digest(&self) -> u64 {
+        self.canonical_digest_with::<FnvHasher>()
+    }
+
+    // canonical_digest_with Digest of the canonical form computed with the hasher `H`
+    pub fn canonical_digest_with<H: Hasher + Default>(&self) -> u64 {
+        let mut hasher = HasherWriter(H::default());
+        write_canonical(&mut hasher, self).expect("hashing cannot fail");
+        hasher.0.finish()
+    }
+}
+
+/// Adapter feeding formatted output straight into a hasher.
+struct HasherWriter<H>(H);
+
+impl<H: Hasher> Write for HasherWriter<H> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Structural hash consistent with `==`.
+///
+/// Object members are hashed independently and combined without regard to order, since
+/// objects compare equal regardless of member order; `0.0` and `-0.0` hash alike.
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            JsonValue::Null => {}
+            JsonValue::Bool(b) => b.hash(state),
+            JsonValue::Number(n) => {
+                let n = if *n == 0.0 { 0.0 } else { *n };
+                n.to_bits().hash(state)
+            }
+            JsonValue::String(s) => s.hash(state),
+            JsonValue::Custom(tag, text) => (tag, text).hash(state),
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(n) => n.hash(state),
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(n) => n.hash(state),
+            JsonValue::Array(arr) => arr.hash(state),
+            JsonValue::Object(obj) => {
+                let members = obj.iter().fold(0u64, |acc, member| {
+                    let build = BuildHasherDefault::<FnvHasher>::default();
+                    acc.wrapping_add(core::hash::BuildHasher::hash_one(&build, member))
+                });
+                obj.len().hash(state);
+                members.hash(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use crate::parse;
+
+    use super::{to_canonical_string, write_es_number};
+
+    fn es(n: f64) -> String {
+        let mut out = String::new();
+        write_es_number(&mut out, n).unwrap();
+        out
+    }
+
+    #[test]
+    fn it_es_number() {
+        assert_eq!(es(0.0), "0");
+        assert_eq!(es(-0.0), "0");
+        assert_eq!(es(1.0), "1");
+        assert_eq!(es(-1.5), "-1.5");
+        assert_eq!(es(123456789.0), "123456789");
+        assert_eq!(es(1e21), "1e+21");
+        assert_eq!(es(1e20), "100000000000000000000");
+        assert_eq!(es(0.000001), "0.000001");
+        assert_eq!(es(1e-7), "1e-7");
+        assert_eq!(es(1.5e-7), "1.5e-7");
+        assert_eq!(es(4.35), "4.35");
+        assert_eq!(es(9007199254740993.0), "9007199254740992");
+        assert_eq!(es(f64::NAN), "null");
+    }
+
+    #[test]
+    fn it_canonical_string() {
+        let value =
+            parse(r#"{ "b": [1.0, 2e3, "é\n"], "a": {"z": null, "y": true}, "😀": 0, "￿": 1 }"#)
+                .unwrap();
+
+        assert_eq!(
+            to_canonical_string(&value),
+            "{\"a\":{\"y\":true,\"z\":null},\"b\":[1,2000,\"é\\n\"],\"😀\":0,\"\u{ffff}\":1}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_hash_ignores_formatting_and_member_order() {
+        use std::{collections::hash_map::DefaultHasher, hash::BuildHasher};
+
+        let a = parse(r#"{"x": [1, -0.0], "y": {"p": 1, "q": 2}}"#).unwrap();
+        let b = parse("{\n  \"y\": {\"q\": 2.0, \"p\": 1},\n  \"x\": [1, 0]\n}").unwrap();
+        let c = parse(r#"{"x": [1, 0], "y": {"p": 2, "q": 1}}"#).unwrap();
+        let hash = |v| std::hash::BuildHasherDefault::<DefaultHasher>::default().hash_one(v);
+
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&c));
+        assert_eq!(a.canonical_digest(), b.canonical_digest());
+        assert_ne!(a.canonical_digest(), c.canonical_digest());
+        assert_eq!(
+            a.canonical_digest_with::<DefaultHasher>(),
+            b.canonical_digest_with::<DefaultHasher>()
+        );
+    }
+}
@@ -0,0 +1,625 @@
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+
+use crate::{
+    error::{ErrorKind, JError},
+    event::{JsonEvent, ValueBuilder},
+    value::valid_number,
+    JsonValue, NumberMode,
+};
+
+const MAX_DEPTH: usize = 512;
+
+// to_cbor Encode the value as CBOR (RFC 8949)
+pub fn to_cbor(value: &JsonValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_events(value.events(), &mut out);
+    out
+}
+
+// encode_events Encode an event sequence as CBOR, using indefinite lengths where the count is unknown
+pub fn encode_events<'a>(events: impl IntoIterator<Item = JsonEvent<'a>>, out: &mut Vec<u8>) {
+    // whether each open container was started with an indefinite length
+    let mut indefinite = Vec::new();
+    let mut chunked = false;
+    for event in events {
+        match event {
+            JsonEvent::StartObject(len) | JsonEvent::StartArray(len) => {
+                let major = if matches!(event, JsonEvent::StartObject(_)) {
+                    5
+                } else {
+                    4
+                };
+                match len {
+                    Some(len) => write_head(out, major, len as u64),
+                    None => out.push(major << 5 | 31),
+                }
+                indefinite.push(len.is_none());
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                if indefinite.pop() == Some(true) {
+                    out.push(0xff);
+                }
+            }
+            JsonEvent::Key(s) | JsonEvent::String(s) => {
+                write_head(out, 3, s.len() as u64);
+                out.extend_from_slice(s.as_bytes());
+            }
+            // the chunks of a string become the pieces of an indefinite-length text string
+            JsonEvent::StringChunk(s) => {
+                if !chunked {
+                    out.push(3 << 5 | 31);
+                    chunked = true;
+                }
+                write_head(out, 3, s.len() as u64);
+                out.extend_from_slice(s.as_bytes());
+            }
+            JsonEvent::StringEnd => {
+                out.push(0xff);
+                chunked = false;
+            }
+            JsonEvent::Number(n) => write_number(out, n),
+            JsonEvent::RawNumber(literal) => write_raw_number(out, &literal),
+            JsonEvent::Bool(false) => out.push(0xf4),
+            JsonEvent::Bool(true) => out.push(0xf5),
+            JsonEvent::Null => out.push(0xf6),
+        }
+    }
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    match arg {
+        0..=23 => out.push(major | arg as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, arg as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(arg as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(arg as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&arg.to_be_bytes());
+        }
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, n: f64) {
+    // integral values in the exactly representable range are written as CBOR integers
+    let exact = n.abs() <= 9_007_199_254_740_992.0 && n == (n as i64) as f64;
+    if exact && n.is_sign_positive() {
+        return write_head(out, 0, n as u64);
+    }
+    if exact && n < 0.0 {
+        return write_head(out, 1, (-1.0 - n) as u64);
+    }
+    out.push(0xfb);
+    out.extend_from_slice(&n.to_bits().to_be_bytes());
+}
+
+// write_raw_number Write a number literal exactly: an integer, or a decimal fraction (tag 4)
+//
+// A literal that is not a JSON number falls back to its nearest float.
+fn write_raw_number(out: &mut Vec<u8>, literal: &str) {
+    let Some((negative, digits, exponent)) = split_literal(literal) else {
+        return write_number(out, literal.parse().unwrap_or(f64::NAN));
+    };
+    if exponent != 0 {
+        write_head(out, 6, 4);
+        write_head(out, 4, 2);
+        match exponent < 0 {
+            true => write_head(out, 1, (-1 - exponent) as u64),
+            false => write_head(out, 0, exponent as u64),
+        }
+    }
+    write_integer(out, negative, &digits);
+}
+
+// split_literal Split a number literal into its sign, its significant digits and a power of ten
+fn split_literal(literal: &str) -> Option<(bool, String, i64)> {
+    if !valid_number(literal.as_bytes()) {
+        return None;
+    }
+    let (negative, rest) = match literal.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, literal),
+    };
+    let (mantissa, exponent) = match rest.split_once(['e', 'E']) {
+        Some((mantissa, exp)) => (mantissa, exp.parse::<i64>().ok()?),
+        None => (rest, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let exponent = exponent.checked_sub(i64::try_from(frac.len()).ok()?)?;
+    Some((negative, format!("{int}{frac}"), exponent))
+}
+
+// write_integer Write decimal digits as a CBOR integer, or as a bignum (tag 2 or 3) past 64 bits
+fn write_integer(out: &mut Vec<u8>, negative: bool, digits: &str) {
+    let mut magnitude = decimal_to_bytes(digits);
+    // CBOR stores a negative n as -1 - n; there is no negative zero
+    let negative = negative && !magnitude.is_empty();
+    if negative {
+        decrement(&mut magnitude);
+    }
+    let major = u8::from(negative);
+    if magnitude.len() <= 8 {
+        let mut arg = [0; 8];
+        arg[8 - magnitude.len()..].copy_from_slice(&magnitude);
+        return write_head(out, major, u64::from_be_bytes(arg));
+    }
+    write_head(out, 6, 2 + major as u64);
+    write_head(out, 2, magnitude.len() as u64);
+    out.extend_from_slice(&magnitude);
+}
+
+// decimal_to_bytes Convert decimal digits to a big-endian magnitude without leading zeros
+fn decimal_to_bytes(digits: &str) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for digit in digits.bytes() {
+        let mut carry = (digit - b'0') as u32;
+        for byte in bytes.iter_mut().rev() {
+            let v = *byte as u32 * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry > 0 {
+            bytes.insert(0, carry as u8);
+        }
+    }
+    bytes
+}
+
+// bytes_to_decimal Convert a big-endian magnitude to decimal digits
+fn bytes_to_decimal(bytes: &[u8]) -> String {
+    let mut bytes = bytes.to_vec();
+    let mut digits = Vec::new();
+    loop {
+        let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+        bytes.drain(..zeros);
+        if bytes.is_empty() {
+            break;
+        }
+        let mut rem = 0;
+        for byte in bytes.iter_mut() {
+            let v = rem * 256 + *byte as u32;
+            *byte = (v / 10) as u8;
+            rem = v % 10;
+        }
+        digits.push(b'0' + rem as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("decimal digits are ASCII")
+}
+
+// increment Add one to a big-endian magnitude
+fn increment(bytes: &mut Vec<u8>) {
+    for byte in bytes.iter_mut().rev() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            return;
+        }
+    }
+    bytes.insert(0, 1);
+}
+
+// decrement Subtract one from a non-zero big-endian magnitude, dropping a leading zero
+fn decrement(bytes: &mut Vec<u8>) {
+    for byte in bytes.iter_mut().rev() {
+        let (diff, borrow) = byte.overflowing_sub(1);
+        *byte = diff;
+        if !borrow {
+            break;
+        }
+    }
+    if bytes.first() == Some(&0) {
+        bytes.remove(0);
+    }
+}
+
+// from_cbor Decode a single CBOR data item into a value
+//
+// Byte strings and non-text map keys have no JSON counterpart and are rejected;
+// bignums and decimal fractions (tags 2 to 4) keep every digit, other tags are
+// skipped and `undefined` decodes as `null`.
+pub fn from_cbor(bytes: &[u8]) -> Result<JsonValue, JError> {
+    from_cbor_with(bytes, NumberMode::default())
+}
+
+// from_cbor_with Decode like `from_cbor`, storing numbers wider than an f64 as `mode` asks
+pub fn from_cbor_with(bytes: &[u8], mode: NumberMode) -> Result<JsonValue, JError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        builder: ValueBuilder::with_number_mode(mode),
+    };
+    decoder.item(0)?;
+    if decoder.pos != bytes.len() {
+        return Err(JError::new(
+            ErrorKind::Syntax,
+            format!("Unexpected trailing CBOR data at byte {}", decoder.pos),
+        ));
+    }
+    decoder.builder.finish()
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    builder: ValueBuilder,
+}
+
+impl<'a> Decoder<'a> {
+    fn item(&mut self, depth: usize) -> Result<(), JError> {
+        if depth > MAX_DEPTH {
+            return Err(JError::new(
+                ErrorKind::Unsupported,
+                "CBOR nesting is too deep",
+            ));
+        }
+        let at = self.pos;
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        if info == 31 {
+            return match major {
+                3 => {
+                    let text = self.indefinite_text()?;
+                    self.builder.push(JsonEvent::String(Cow::Owned(text)))
+                }
+                4 => {
+                    self.builder.push(JsonEvent::StartArray(None))?;
+                    while !self.at_break()? {
+                        self.item(depth + 1)?;
+                    }
+                    self.builder.push(JsonEvent::EndArray)
+                }
+                5 => {
+                    self.builder.push(JsonEvent::StartObject(None))?;
+                    while !self.at_break()? {
+                        self.key()?;
+                        self.item(depth + 1)?;
+                    }
+                    self.builder.push(JsonEvent::EndObject)
+                }
+                _ => Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("Invalid indefinite length at byte {at}"),
+                )),
+            };
+        }
+
+        if major == 7 {
+            let event = match info {
+                20 => JsonEvent::Bool(false),
+                21 => JsonEvent::Bool(true),
+                22 | 23 => JsonEvent::Null,
+                25 => JsonEvent::Number(f16_to_f64(u16::from_be_bytes(self.array()?))),
+                26 => JsonEvent::Number(f32::from_be_bytes(self.array()?) as f64),
+                27 => JsonEvent::Number(f64::from_be_bytes(self.array()?)),
+                _ => {
+                    return Err(JError::new(
+                        ErrorKind::Unsupported,
+                        format!("Unsupported simple value at byte {at}"),
+                    ))
+                }
+            };
+            return self.builder.push(event);
+        }
+
+        let arg = self.argument(info)?;
+        match major {
+            0 => self.builder.push(JsonEvent::Number(arg as f64)),
+            1 => self.builder.push(JsonEvent::Number(-1.0 - arg as f64)),
+            2 => Err(JError::new(
+                ErrorKind::Unsupported,
+                format!("Byte strings are not supported at byte {at}"),
+            )),
+            3 => {
+                let text = self.text(arg)?;
+                self.builder.push(JsonEvent::String(Cow::Borrowed(text)))
+            }
+            4 => {
+                self.builder.push(JsonEvent::StartArray(None))?;
+                for _ in 0..arg {
+                    self.item(depth + 1)?;
+                }
+                self.builder.push(JsonEvent::EndArray)
+            }
+            5 => {
+                self.builder.push(JsonEvent::StartObject(None))?;
+                for _ in 0..arg {
+                    self.key()?;
+                    self.item(depth + 1)?;
+                }
+                self.builder.push(JsonEvent::EndObject)
+            }
+            _ => match arg {
+                2..=4 => {
+                    let literal = self.tagged_number(arg)?;
+                    self.builder.push(JsonEvent::RawNumber(Cow::Owned(literal)))
+                }
+                _ => self.item(depth + 1), // other tags: decode the wrapped item as is
+            },
+        }
+    }
+
+    // tagged_number Read a bignum (tag 2 or 3) or decimal fraction (tag 4) as a number literal
+    fn tagged_number(&mut self, tag: u64) -> Result<String, JError> {
+        if tag != 4 {
+            return self.bignum(tag == 3);
+        }
+        let at = self.pos;
+        if self.byte()? != 0x82 {
+            return Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Invalid decimal fraction at byte {at}"),
+            ));
+        }
+        let exponent = self.integer()?;
+        let mantissa = self.integer()?;
+        Ok(format!("{mantissa}e{exponent}"))
+    }
+
+    // integer Read an integer or bignum item as decimal digits
+    fn integer(&mut self) -> Result<String, JError> {
+        let at = self.pos;
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        match (major, self.argument(info)?) {
+            (0, arg) => Ok(format!("{arg}")),
+            (1, arg) => Ok(format!("{}", -1 - arg as i128)),
+            (6, tag @ (2 | 3)) => self.bignum(tag == 3),
+            _ => Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Expected an integer at byte {at}"),
+            )),
+        }
+    }
+
+    // bignum Read the byte string of a bignum, which holds -1 - n when negative
+    fn bignum(&mut self, negative: bool) -> Result<String, JError> {
+        let at = self.pos;
+        let initial = self.byte()?;
+        if initial >> 5 != 2 || initial & 0x1f == 31 {
+            return Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Invalid bignum at byte {at}"),
+            ));
+        }
+        let len = self.argument(initial & 0x1f)?;
+        let len = usize::try_from(len)
+            .map_err(|_| JError::new(ErrorKind::Unsupported, "CBOR bignum too long"))?;
+        let mut magnitude = self.take(len)?.to_vec();
+        if !negative {
+            return Ok(bytes_to_decimal(&magnitude));
+        }
+        increment(&mut magnitude);
+        Ok(format!("-{}", bytes_to_decimal(&magnitude)))
+    }
+
+    fn key(&mut self) -> Result<(), JError> {
+        let at = self.pos;
+        let initial = self.byte()?;
+        let key = match (initial >> 5, initial & 0x1f) {
+            (3, 31) => Cow::Owned(self.indefinite_text()?),
+            (3, info) => {
+                let len = self.argument(info)?;
+                Cow::Borrowed(self.text(len)?)
+            }
+            _ => {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("Map keys must be text strings at byte {at}"),
+                ))
+            }
+        };
+        self.builder.push(JsonEvent::Key(key))
+    }
+
+    fn indefinite_text(&mut self) -> Result<String, JError> {
+        let mut text = String::new();
+        while !self.at_break()? {
+            let at = self.pos;
+            let initial = self.byte()?;
+            if initial >> 5 != 3 || initial & 0x1f == 31 {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("Invalid text string chunk at byte {at}"),
+                ));
+            }
+            let len = self.argument(initial & 0x1f)?;
+            text.push_str(self.text(len)?);
+        }
+        Ok(text)
+    }
+
+    fn argument(&mut self, info: u8) -> Result<u64, JError> {
+        Ok(match info {
+            0..=23 => info as u64,
+            24 => self.byte()? as u64,
+            25 => u16::from_be_bytes(self.array()?) as u64,
+            26 => u32::from_be_bytes(self.array()?) as u64,
+            27 => u64::from_be_bytes(self.array()?),
+            _ => {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("Invalid additional info {info}"),
+                ))
+            }
+        })
+    }
+
+    fn at_break(&mut self) -> Result<bool, JError> {
+        match self.bytes.get(self.pos) {
+            Some(0xff) => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(JError::new(ErrorKind::Eof, "Unexpected end of CBOR data")),
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, JError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], JError> {
+        Ok(self.take(N)?.try_into().expect("take returns N bytes"))
+    }
+
+    fn text(&mut self, len: u64) -> Result<&'a str, JError> {
+        let at = self.pos;
+        let len = usize::try_from(len)
+            .map_err(|_| JError::new(ErrorKind::Unsupported, "CBOR string too long"))?;
+        core::str::from_utf8(self.take(len)?).map_err(|_| {
+            JError::new(
+                ErrorKind::Encoding,
+                format!("Invalid UTF-8 in text string at byte {at}"),
+            )
+        })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], JError> {
+        match self.bytes.get(self.pos..self.pos.saturating_add(len)) {
+            Some(slice) => {
+                self.pos += len;
+                Ok(slice)
+            }
+            None => Err(JError::new(ErrorKind::Eof, "Unexpected end of CBOR data")),
+        }
+    }
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let exp = (half >> 10) & 0x1f;
+    let mant = (half & 0x3ff) as f64;
+    let value = match exp {
+        0 => mant / 16_777_216.0, // mant * 2^-24
+        31 if mant == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        // (1024 + mant) * 2^(exp - 25), with the power of two built from its bits
+        _ => (mant + 1024.0) * f64::from_bits(((exp as u64) + 1023 - 25) << 52),
+    };
+    if half & 0x8000 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{event::JsonEvent, parse};
+
+    use super::{encode_events, from_cbor, from_cbor_with, to_cbor};
+
+    #[test]
+    fn it_cbor_round_trip() {
+        let value =
+            parse(r#"{"a": 1, "b": [-10, 1.5, 1000000], "c": "杭州", "d": null, "e": false}"#)
+                .unwrap();
+        let bytes = to_cbor(&value);
+
+        assert_eq!(&bytes[..4], &[0xa5, 0x61, b'a', 0x01]);
+        assert_eq!(from_cbor(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn it_cbor_encode_indefinite_events() {
+        let events = vec![
+            JsonEvent::StartArray(None),
+            JsonEvent::Number(1.0),
+            JsonEvent::StartObject(Some(0)),
+            JsonEvent::EndObject,
+            JsonEvent::StringChunk("ab".into()),
+            JsonEvent::StringChunk("c".into()),
+            JsonEvent::StringEnd,
+            JsonEvent::EndArray,
+        ];
+        let mut bytes = Vec::new();
+        encode_events(events, &mut bytes);
+
+        assert_eq!(
+            bytes,
+            vec![0x9f, 0x01, 0xa0, 0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff, 0xff]
+        );
+        assert_eq!(
+            from_cbor(&bytes).unwrap(),
+            parse(r#"[1, {}, "abc"]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_cbor_decode_rfc_examples() {
+        // [1, [2, 3], {_ "a": 1}] with indefinite containers, a tag and a half float
+        let bytes = [
+            0x9f, 0x01, 0x82, 0x02, 0x03, 0xbf, 0x61, 0x61, 0xc1, 0xf9, 0x3e, 0x00, 0xff, 0xff,
+        ];
+
+        assert_eq!(
+            from_cbor(&bytes).unwrap(),
+            parse(r#"[1, [2, 3], {"a": 1.5}]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_cbor_rejects_invalid_input() {
+        assert!(from_cbor(&[0x82, 0x01]).is_err());
+        assert!(from_cbor(&[0x41, 0x00]).is_err());
+        assert!(from_cbor(&[0xa1, 0x01, 0x02]).is_err());
+        assert!(from_cbor(&[0x01, 0x02]).is_err());
+        assert!(from_cbor(&[0xc2, 0x01]).is_err());
+        assert!(from_cbor(&[0xc4, 0x83, 0x01, 0x02, 0x03]).is_err());
+    }
+
+    #[test]
+    fn it_cbor_decode_bignums() {
+        // 2^64, -2^64 - 1 and 27315e-2 as a decimal fraction
+        let bytes = [
+            0x83, 0xc2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0xc3, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0,
+            0, 0xc4, 0x82, 0x21, 0x19, 0x6a, 0xb3,
+        ];
+
+        let value = from_cbor(&bytes).unwrap();
+        let items = value.as_array().unwrap();
+        let big = parse("[18446744073709551616, -18446744073709551617]").unwrap();
+
+        assert_eq!(items[..2], big.as_array().unwrap()[..]);
+        assert_eq!(items[2].as_f64(), Some(273.15));
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn it_cbor_big_integer_round_trip() {
+        let value = parse("[123456789012345678901234567890, -98765432109876543210]").unwrap();
+        let bytes = to_cbor(&value);
+
+        assert_eq!(&bytes[..4], &[0x82, 0xc2, 0x4d, 0x01]);
+        assert_eq!(from_cbor(&bytes).unwrap(), value);
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn it_cbor_decimal_round_trip() {
+        use crate::{parse_with, NumberMode, ParseOptions};
+
+        let options = ParseOptions::new().number_mode(NumberMode::Decimal);
+        let value = parse_with("[0.10, -2.5e-3]", &options).unwrap();
+
+        let bytes = to_cbor(&value);
+        assert_eq!(from_cbor_with(&bytes, NumberMode::Decimal).unwrap(), value);
+        assert_eq!(from_cbor(&bytes).unwrap(), parse("[0.1, -0.0025]").unwrap());
+
+        // 1e-40 is too small for a decimal and falls back to a float
+        let tiny = from_cbor_with(&[0xc4, 0x82, 0x38, 0x27, 0x01], NumberMode::Decimal).unwrap();
+        assert_eq!(tiny.as_decimal(), None);
+        assert_eq!(tiny.as_f64(), Some(1e-40));
+    }
+}
@@ -0,0 +1,310 @@
+use std::{collections::HashSet, fmt::Write};
+
+use indexmap::IndexMap;
+
+use crate::JsonValue;
+
+/// Shape of a value, merged across every sample seen at the same position.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// No sample seen yet (e.g. elements of an empty array).
+    Unknown,
+    Null,
+    Bool,
+    Integer,
+    Float,
+    String,
+    Array(Box<Shape>),
+    /// Members with a flag telling whether some sample lacked them.
+    Object(IndexMap<String, (Shape, bool)>),
+    Nullable(Box<Shape>),
+    /// Samples disagree; falls back to `serde_json::Value`.
+    Any,
+}
+
+impl Shape {
+    fn of(value: &JsonValue) -> Shape {
+        match value {
+            JsonValue::Null => Shape::Null,
+            JsonValue::Bool(_) => Shape::Bool,
+            JsonValue::Number(n) if n.fract() == 0.0 => Shape::Integer,
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(_) => Shape::Integer,
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(_) => Shape::Float,
+            JsonValue::Number(_) => Shape::Float,
+            JsonValue::String(_) | JsonValue::Custom(..) => Shape::String,
+            JsonValue::Array(arr) => Shape::Array(Box::new(
+                arr.iter().map(Shape::of).fold(Shape::Unknown, Shape::merge),
+            )),
+            JsonValue::Object(obj) => Shape::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), (Shape::of(v), false)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn merge(self, other: Shape) -> Shape {
+        match (self, other) {
+            (Shape::Unknown, s) | (s, Shape::Unknown) => s,
+            (Shape::Null, Shape::Null) => Shape::Null,
+            (Shape::Null, s) | (s, Shape::Null) => s.nullable(),
+            (Shape::Nullable(a), b) | (b, Shape::Nullable(a)) => a.merge(b).nullable(),
+            (Shape::Integer, Shape::Float) | (Shape::Float, Shape::Integer) => Shape::Float,
+            (Shape::Array(a), Shape::Array(b)) => Shape::Array(Box::new(a.merge(*b))),
+            (Shape::Object(mut a), Shape::Object(mut b)) => {
+                for (key, (shape, missing)) in a.iter_mut() {
+                    match b.shift_remove(key) {
+                        Some((other, other_missing)) => {
+                            *shape = std::mem::replace(shape, Shape::Unknown).merge(other);
+                            *missing |= other_missing;
+                        }
+                        None => *missing = true,
+                    }
+                }
+                for (key, (shape, _)) in b {
+                    a.insert(key, (shape, true));
+                }
+                Shape::Object(a)
+            }
+            (a, b) if a == b => a,
+            _ => Shape::Any,
+        }
+    }
+
+    fn nullable(self) -> Shape {
+        match self {
+            Shape::Nullable(_) | Shape::Any => self,
+            s => Shape::Nullable(Box::new(s)),
+        }
+    }
+}
+
+// generate Generate Rust struct definitions (with serde attributes) matching the shape of the sample
+pub fn generate(sample: &JsonValue, root_name: &str) -> String {
+    let mut gen = Generator::default();
+    let root_name = gen.type_name(root_name);
+    let shape = Shape::of(sample);
+    match &shape {
+        Shape::Object(members) => gen.emit_struct(&root_name, members),
+        _ => {
+            let ty = gen.rust_type(&shape, &root_name);
+            gen.structs
+                .insert(0, format!("pub type {root_name} = {ty};\n"));
+        }
+    }
+
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+    for def in gen.structs {
+        out.push('\n');
+        out.push_str(&def);
+    }
+    out
+}
+
+#[derive(Default)]
+struct Generator {
+    structs: Vec<String>,
+    used_names: HashSet<String>,
+}
+
+impl Generator {
+    fn emit_struct(&mut self, name: &str, members: &IndexMap<String, (Shape, bool)>) {
+        // reserve the slot so the parent struct precedes its children
+        let slot = self.structs.len();
+        self.structs.push(String::new());
+
+        let mut def = String::new();
+        let mut fields = HashSet::new();
+        def.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+        writeln!(def, "pub struct {name} {{").unwrap();
+        for (key, (shape, missing)) in members {
+            let field = unique(field_name(key), &mut fields);
+            let mut ty = self.rust_type(shape, &format!("{name}_{key}"));
+            if *missing && !matches!(shape, Shape::Null | Shape::Nullable(_)) {
+                ty = format!("Option<{ty}>");
+            }
+            if field.trim_start_matches("r#") != key {
+                writeln!(def, "    #[serde(rename = {key:?})]").unwrap();
+            }
+            writeln!(def, "    pub {field}: {ty},").unwrap();
+        }
+        def.push_str("}\n");
+        self.structs[slot] = def;
+    }
+
+    // rust_type Render the shape as a type, naming nested structs after the hint
+    fn rust_type(&mut self, shape: &Shape, hint: &str) -> String {
+        match shape {
+            Shape::Null => "Option<serde_json::Value>".to_string(),
+            Shape::Unknown | Shape::Any => "serde_json::Value".to_string(),
+            Shape::Bool => "bool".to_string(),
+            Shape::Integer => "i64".to_string(),
+            Shape::Float => "f64".to_string(),
+            Shape::String => "String".to_string(),
+            Shape::Array(item) => {
+                format!("Vec<{}>", self.rust_type(item, &format!("{hint}Item")))
+            }
+            Shape::Object(members) => {
+                let name = self.type_name(hint);
+                self.emit_struct(&name, members);
+                name
+            }
+            Shape::Nullable(inner) => format!("Option<{}>", self.rust_type(inner, hint)),
+        }
+    }
+
+    // type_name Turn a hint into a PascalCase type name not claimed by any other struct
+    fn type_name(&mut self, hint: &str) -> String {
+        let mut name = String::new();
+        for word in words(hint) {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                name.extend(first.to_uppercase());
+                name.push_str(&chars.as_str().to_lowercase());
+            }
+        }
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            name.insert_str(0, "Type");
+        }
+        unique(name, &mut self.used_names)
+    }
+}
+
+fn field_name(key: &str) -> String {
+    let mut name = words(key)
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.is_empty() {
+        name.push_str("field");
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    match name.as_str() {
+        "self" | "super" | "crate" | "_" => format!("{name}_"),
+        n if KEYWORDS.contains(&n) => format!("r#{name}"),
+        _ => name,
+    }
+}
+
+// words Split an identifier-ish string on separators and camelCase boundaries
+fn words(s: &str) -> impl Iterator<Item = String> + '_ {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if !c.is_alphanumeric() {
+            words.push(std::mem::take(&mut current));
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    words.push(current);
+    words.into_iter().filter(|w| !w.is_empty())
+}
+
+fn unique(name: String, used: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{name}{n}");
+        n += 1;
+    }
+    candidate
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use", "where",
+    "while", "yield",
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::generate;
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_generate_nested_structs() {
+        let sample = parse(
+            r#"{
+                "name": "John Doe",
+                "age": 30,
+                "isStudent": false,
+                "scores": [100, 90.5],
+                "address": {"city": "Springfield", "zip-code": "62701"},
+                "type": null
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            generate(&sample, "person"),
+            r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub age: i64,
+    #[serde(rename = "isStudent")]
+    pub is_student: bool,
+    pub scores: Vec<f64>,
+    pub address: PersonAddress,
+    pub r#type: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonAddress {
+    pub city: String,
+    #[serde(rename = "zip-code")]
+    pub zip_code: String,
+}
+"#
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_generate_optional_fields_from_arrays() {
+        let sample = parse(
+            r#"[
+                {"id": 1, "tags": ["a"], "note": null},
+                {"id": 2, "tags": [], "note": "x", "extra": true}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            generate(&sample, "Record"),
+            r#"use serde::{Deserialize, Serialize};
+
+pub type Record = Vec<RecordItem>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordItem {
+    pub id: i64,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+    pub extra: Option<bool>,
+}
+"#
+        );
+    }
+}
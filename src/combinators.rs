@@ -0,0 +1,271 @@
+//! Token-level parser combinators for grammars built on this crate's lexer.
+//!
+//! Every parser here is generic over [`TokenInput`], so it runs on a token slice
+//! ([`Input`]) as well as on a lazily lexed [`TokenStream`](crate::TokenStream), and
+//! composes with the `nom` combinators since errors are [`JError`]s. Mismatches are
+//! recoverable `nom::Err::Error`s; a token the lexer rejected surfaces as a
+//! `nom::Err::Failure` so alternatives do not paper over it.
+//!
+//! ```
+//! use jnom_rs::combinators::{kind_of, list0, IResult};
+//! use jnom_rs::{TokenInput, TokenKind, TokenStream};
+//!
+//! // `[[x, y], ...]` read as a list of points
+//! fn point<'a, I: TokenInput<'a>>(i: I) -> IResult<I, (f64, f64)> {
+//!     let (i, xy) = list0(TokenKind::OpenBracket, kind_of(TokenKind::Number), TokenKind::CloseBracket)(i)?;
+//!     match xy[..] {
+//!         [x, y] => Ok((i, (x.parse().unwrap(), y.parse().unwrap()))),
+//!         _ => Err(jnom_rs::combinators::no_match(&i, "expected two coordinates")),
+//!     }
+//! }
+//!
+//! let (rest, points) =
+//!     list0(TokenKind::OpenBracket, point, TokenKind::CloseBracket)(TokenStream::new("[[1, 2], [3, 4.5]]"))
+//!         .unwrap();
+//! assert!(rest.peek().is_none());
+//! assert_eq!(points, [(1.0, 2.0), (3.0, 4.5)]);
+//! ```
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use nom::{multi::separated_list0, sequence::delimited};
+
+use crate::{
+    error::{ErrorKind, Expected, JError},
+    token::{JsonToken, JsonTokenKind, TokenKind},
+};
+pub use crate::{token::TokenInput, Input};
+
+/// Result of a parser over any [`TokenInput`], failing with a [`JError`].
+///
+/// [`crate::IResult`] is the same type fixed to a token slice.
+pub type IResult<I, O> = nom::IResult<I, O, JError>;
+
+// match_token Consume the next token if it equals `kind`, payload included, returning its text
+pub fn match_token<'a, I: TokenInput<'a>>(
+    kind: JsonTokenKind,
+) -> impl Fn(I) -> IResult<I, &'a str> {
+    let expected = Expected::Kind(TokenKind::from(&kind));
+    // Rejected input never matches, not even a request for `Error` tokens
+    move |i| match i
+        .peek()
+        .filter(|token| token.kind == kind && token_kind(token).is_some())
+    {
+        Some(token) => Ok((i.advance(), token.text())),
+        None => Err(mismatch(&i, expected)),
+    }
+}
+
+// match_text Consume the next token if its source text is exactly `text`
+pub fn match_text<'a, I: TokenInput<'a>>(text: &'static str) -> impl Fn(I) -> IResult<I, &'a str> {
+    move |i| match i.peek().filter(|token| token.text() == text) {
+        Some(token) => Ok((i.advance(), token.text())),
+        None => Err(mismatch(&i, Expected::Text(text))),
+    }
+}
+
+// kind_of Consume the next token if it is of `kind`, whatever its payload, returning its text
+pub fn kind_of<'a, I: TokenInput<'a>>(kind: TokenKind) -> impl Fn(I) -> IResult<I, &'a str> {
+    move |i| match i.peek().filter(|token| token_kind(token) == Some(kind)) {
+        Some(token) => Ok((i.advance(), token.text())),
+        None => Err(mismatch(&i, Expected::Kind(kind))),
+    }
+}
+
+// any_token Consume the next token whatever it is; fails only at the end of input
+pub fn any_token<'a, I: TokenInput<'a>>(i: I) -> IResult<I, JsonToken<'a>> {
+    match i.peek() {
+        Some(token) => {
+            let token = token.clone();
+            Ok((i.advance(), token))
+        }
+        None => Err(no_match(&i, "Expected a token")),
+    }
+}
+
+// peek_kind The kind of the next token without consuming it, `None` at the end of input
+pub fn peek_kind<'a, I: TokenInput<'a>>(i: I) -> IResult<I, Option<TokenKind>> {
+    match (i.peek(), i.lex_error()) {
+        (None, Some(e)) => Err(nom::Err::Failure(e)),
+        (token, _) => {
+            let kind = token.and_then(token_kind);
+            Ok((i, kind))
+        }
+    }
+}
+
+// between Run `parser` between an `open` and a `close` token
+pub fn between<'a, I: TokenInput<'a>, O>(
+    open: TokenKind,
+    mut parser: impl FnMut(I) -> IResult<I, O>,
+    close: TokenKind,
+) -> impl FnMut(I) -> IResult<I, O> {
+    move |i| delimited(kind_of(open), &mut parser, kind_of(close))(i)
+}
+
+// comma_list0 Zero or more `item`s separated by commas, without a trailing comma
+pub fn comma_list0<'a, I: TokenInput<'a>, O>(
+    mut item: impl FnMut(I) -> IResult<I, O>,
+) -> impl FnMut(I) -> IResult<I, Vec<O>> {
+    move |i| separated_list0(kind_of(TokenKind::Comma), &mut item)(i)
+}
+
+// list0 A comma separated list of `item`s enclosed in `open` and `close`, like a JSON array
+pub fn list0<'a, I: TokenInput<'a>, O>(
+    open: TokenKind,
+    item: impl FnMut(I) -> IResult<I, O>,
+    close: TokenKind,
+) -> impl FnMut(I) -> IResult<I, Vec<O>> {
+    between(open, comma_list0(item), close)
+}
+
+// no_match Report `msg`, or the lexer error if the input stopped at an invalid token
+pub fn no_match<'a, I: TokenInput<'a>>(
+    i: &I,
+    msg: impl Into<Cow<'static, str>>,
+) -> nom::Err<JError> {
+    match i.lex_error() {
+        Some(e) => nom::Err::Failure(e),
+        None => nom::Err::Error(JError::new(ErrorKind::Syntax, msg)),
+    }
+}
+
+// mismatch Like `no_match`, but the message is only rendered if the error is displayed
+pub(crate) fn mismatch<'a, I: TokenInput<'a>>(i: &I, expected: Expected) -> nom::Err<JError> {
+    match i.lex_error() {
+        Some(e) => nom::Err::Failure(e),
+        None => nom::Err::Error(JError::mismatch(
+            expected,
+            i.peek().map(|token| token.span.clone()),
+        )),
+    }
+}
+
+/// Label the rule `parser` implements, so its errors report where in the grammar they arose.
+///
+/// Errors leaving the rule, recoverable or not, get `label` appended to
+/// [`JError::context`]. With the `trace` feature every call is also logged to stderr on
+/// entry and exit, indented by nesting depth, which helps when debugging custom grammars.
+pub fn context<'a, I: TokenInput<'a>, O>(
+    label: &'static str,
+    mut parser: impl FnMut(I) -> IResult<I, O>,
+) -> impl FnMut(I) -> IResult<I, O> {
+    move |i| {
+        #[cfg(feature = "trace")]
+        let depth = trace::enter(label, &i);
+        let result = parser(i);
+        #[cfg(feature = "trace")]
+        trace::exit(label, depth, &result);
+        result.map_err(|e| e.map(|e| e.in_context(label)))
+    }
+}
+
+// token_kind The payload-free kind, `None` for tokens the lexer rejected
+fn token_kind(token: &JsonToken) -> Option<TokenKind> {
+    match token.kind {
+        JsonTokenKind::Whitespace | JsonTokenKind::Error => None,
+        ref kind => Some(TokenKind::from(kind)),
+    }
+}
+
+#[cfg(feature = "trace")]
+mod trace {
+    use core::cell::Cell;
+
+    use crate::{error::JError, token::TokenInput};
+
+    std::thread_local! {
+        static DEPTH: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub fn enter<'a, I: TokenInput<'a>>(label: &str, i: &I) -> usize {
+        let depth = DEPTH.with(|d| d.replace(d.get() + 1));
+        match i.peek() {
+            Some(token) => eprintln!(
+                "{:indent$}> {label} at {:?} {}..{}",
+                "",
+                token.text(),
+                token.span.start,
+                token.span.end,
+                indent = depth * 2
+            ),
+            None => eprintln!(
+                "{:indent$}> {label} at end of input",
+                "",
+                indent = depth * 2
+            ),
+        }
+        depth
+    }
+
+    pub fn exit<I, O>(label: &str, depth: usize, result: &nom::IResult<I, O, JError>) {
+        DEPTH.with(|d| d.set(depth));
+        let outcome = match result {
+            Ok(_) => "ok".into(),
+            Err(nom::Err::Error(e)) => alloc::format!("error: {}", e.message()),
+            Err(nom::Err::Failure(e)) => alloc::format!("failure: {}", e.message()),
+            Err(nom::Err::Incomplete(_)) => "incomplete".into(),
+        };
+        eprintln!("{:indent$}< {label} {outcome}", "", indent = depth * 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{any_token, between, comma_list0, kind_of, list0, match_text, peek_kind};
+    use crate::{tokenize, tokenize_recovering, JsonTokenKind, TokenInput, TokenKind, TokenStream};
+
+    #[test]
+    fn it_token_combinators() {
+        let tokens = tokenize(r#"{"a": [1, "x"]}"#);
+        let input = tokens.as_slice();
+
+        assert_eq!(peek_kind(input).unwrap().1, Some(TokenKind::OpenBrace));
+        assert_eq!(peek_kind(&input[..0]).unwrap().1, None);
+        let (rest, token) = any_token(input).unwrap();
+        assert_eq!(token.kind, JsonTokenKind::OpenBrace);
+        assert_eq!(kind_of(TokenKind::String)(rest).unwrap().1, r#""a""#);
+        assert!(kind_of(TokenKind::Number)(rest).is_err());
+        assert!(any_token(&input[..0]).is_err());
+
+        let (rest, items) =
+            list0(TokenKind::OpenBracket, any_token, TokenKind::CloseBracket)(&input[3..]).unwrap();
+        assert_eq!(
+            items.iter().map(|t| t.text()).collect::<Vec<_>>(),
+            ["1", r#""x""#]
+        );
+        assert_eq!(rest.peek().map(|t| t.text()), Some("}"));
+
+        let (_, empty) = comma_list0(kind_of(TokenKind::Null))(&input[1..]).unwrap();
+        assert!(empty.is_empty());
+        let mut object = between(
+            TokenKind::OpenBrace,
+            match_text(r#""a""#),
+            TokenKind::CloseBrace,
+        );
+        assert!(object(input).is_err());
+    }
+
+    #[test]
+    fn it_rejected_tokens() {
+        let stream = TokenStream::new("[1, @]");
+        let after = stream.advance().advance().advance();
+        assert!(matches!(
+            peek_kind(after.clone()),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            kind_of(TokenKind::Number)(after),
+            Err(nom::Err::Failure(_))
+        ));
+
+        let tokens = tokenize_recovering("[@]");
+        assert_eq!(peek_kind(&tokens[1..]).unwrap().1, None);
+        assert_eq!(
+            any_token(&tokens[1..]).unwrap().1.kind,
+            JsonTokenKind::Error
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{JsonValue, Map};
+
+/// How far apart two numbers may be for [`JsonValue::approx_eq`] to treat them as equal.
+///
+/// Numbers match when they are within `absolute` of each other, or within `relative`
+/// times the larger magnitude. The default accepts differences up to `1e-9` either way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            absolute: 1e-9,
+            relative: 1e-9,
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn exact() -> Self {
+        Tolerance {
+            absolute: 0.0,
+            relative: 0.0,
+        }
+    }
+
+    pub fn absolute(absolute: f64) -> Self {
+        Tolerance {
+            absolute,
+            relative: 0.0,
+        }
+    }
+
+    pub fn relative(relative: f64) -> Self {
+        Tolerance {
+            absolute: 0.0,
+            relative,
+        }
+    }
+
+    // accepts Whether `a` and `b` are equal within the tolerance
+    pub fn accepts(&self, a: f64, b: f64) -> bool {
+        if a == b || (a.is_nan() && b.is_nan()) {
+            return true;
+        }
+        let diff = if a > b { a - b } else { b - a };
+        let magnitude = magnitude(a).max(magnitude(b));
+        diff <= self.absolute || diff <= self.relative * magnitude
+    }
+}
+
+fn magnitude(n: f64) -> f64 {
+    if n < 0.0 {
+        -n
+    } else {
+        n
+    }
+}
+
+/// What [`JsonValue::semantic_eq`] may disregard when comparing documents.
+///
+/// Object member order never matters. By default everything else must match exactly;
+/// each builder method relaxes one aspect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompareOptions {
+    pub ignore_array_order: bool,
+    pub null_equals_missing: bool,
+    pub tolerance: Tolerance,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        CompareOptions {
+            ignore_array_order: false,
+            null_equals_missing: false,
+            tolerance: Tolerance::exact(),
+        }
+    }
+}
+
+impl CompareOptions {
+    pub fn new() -> Self {
+        CompareOptions::default()
+    }
+
+    // ignore_array_order Compare arrays as multisets
+    pub fn ignore_array_order(mut self) -> Self {
+        self.ignore_array_order = true;
+        self
+    }
+
+    // null_equals_missing Treat a member set to `null` like an absent member
+    pub fn null_equals_missing(mut self) -> Self {
+        self.null_equals_missing = true;
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+impl JsonValue {
+    /// Exact structural equality.
+    ///
+    /// Same as `==` (object members match regardless of order, arrays element by element)
+    /// except that `NaN` equals `NaN`, so every value is `deep_eq` to itself.
+    pub fn deep_eq(&self, other: &JsonValue) -> bool {
+        self.approx_eq(other, Tolerance::exact())
+    }
+
+    /// Structural equality that lets numbers differ within `tolerance`.
+    ///
+    /// Use this for assertions on parsed documents, where `0.1 + 0.2` and `0.3` should
+    /// compare equal. All other values, including object keys, must match exactly.
+    pub fn approx_eq(&self, other: &JsonValue, tolerance: Tolerance) -> bool {
+        match (self, other) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => tolerance.accepts(*a, *b),
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, tolerance))
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.approx_eq(b, tolerance)))
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Equality under the relaxations chosen in `options`.
+    ///
+    /// With `ignore_array_order`, every element must be matched by a distinct element of
+    /// the other array; matching is greedy, which is exact unless a loose tolerance makes
+    /// one element equal to several others.
+    pub fn semantic_eq(&self, other: &JsonValue, options: &CompareOptions) -> bool {
+        match (self, other) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => options.tolerance.accepts(*a, *b),
+            (JsonValue::Array(a), JsonValue::Array(b)) if options.ignore_array_order => {
+                let mut unmatched = b.iter().collect::<Vec<_>>();
+                a.len() == b.len()
+                    && a.iter().all(|a| {
+                        match unmatched.iter().position(|b| a.semantic_eq(b, options)) {
+                            Some(idx) => {
+                                unmatched.swap_remove(idx);
+                                true
+                            }
+                            None => false,
+                        }
+                    })
+            }
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.semantic_eq(b, options))
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) if options.null_equals_missing => {
+                let covers = |a: &Map<String, JsonValue>, b: &Map<String, JsonValue>| {
+                    a.iter().all(|(key, a)| match b.get(key) {
+                        Some(b) => a.semantic_eq(b, options),
+                        None => a.is_null(),
+                    })
+                };
+                covers(a, b) && b.iter().all(|(key, b)| a.contains_key(key) || b.is_null())
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.semantic_eq(b, options)))
+            }
+            _ => self == other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{parse, JsonValue};
+
+    use super::{CompareOptions, Tolerance};
+
+    #[test]
+    fn it_approx_eq() {
+        let a = parse(r#"{"x": 1.0, "ys": [0.30000000000000004, 2], "s": "a"}"#).unwrap();
+        let b = parse(r#"{"ys": [0.3, 2], "s": "a", "x": 0.9999999999}"#).unwrap();
+
+        assert!(a != b);
+        assert!(a.approx_eq(&b, Tolerance::default()));
+        assert!(!a.approx_eq(&b, Tolerance::exact()));
+        assert!(!a.approx_eq(
+            &parse(r#"{"x": 1, "ys": [0.3, 2], "s": "b"}"#).unwrap(),
+            Tolerance::default()
+        ));
+        assert!(Tolerance::relative(0.01).accepts(1000.0, 1005.0));
+        assert!(!Tolerance::absolute(0.01).accepts(1000.0, 1005.0));
+    }
+
+    #[test]
+    fn it_deep_eq() {
+        let nan = JsonValue::Array(vec![JsonValue::Number(f64::NAN)]);
+
+        assert!(nan != nan);
+        assert!(nan.deep_eq(&nan));
+        assert!(parse("[1, {\"a\": null}]")
+            .unwrap()
+            .deep_eq(&parse("[1.0, {\"a\": null}]").unwrap()));
+        assert!(!parse("[1, 2]").unwrap().deep_eq(&parse("[2, 1]").unwrap()));
+    }
+
+    #[test]
+    fn it_semantic_eq() {
+        let a =
+            parse(r#"{"ids": [3, 1, 2], "tags": [{"n": "x"}, {"n": "y"}], "note": null}"#).unwrap();
+        let b = parse(r#"{"tags": [{"n": "y"}, {"n": "x"}], "ids": [1, 2, 3]}"#).unwrap();
+        let strict = CompareOptions::new();
+        let relaxed = CompareOptions::new()
+            .ignore_array_order()
+            .null_equals_missing();
+
+        assert!(!a.semantic_eq(&b, &strict));
+        assert!(!a.semantic_eq(&b, &CompareOptions::new().ignore_array_order()));
+        assert!(a.semantic_eq(&b, &relaxed));
+        assert!(b.semantic_eq(&a, &relaxed));
+
+        let dupes = parse("[1, 1, 2]").unwrap();
+        assert!(!dupes.semantic_eq(&parse("[1, 2, 2]").unwrap(), &relaxed));
+        assert!(!a.semantic_eq(
+            &parse(r#"{"ids": [3, 1, 2], "tags": [], "note": 0}"#).unwrap(),
+            &relaxed
+        ));
+        assert!(parse("[1.0000000001]").unwrap().semantic_eq(
+            &parse("[1]").unwrap(),
+            &relaxed.tolerance(Tolerance::default())
+        ));
+    }
+}
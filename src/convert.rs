@@ -0,0 +1,353 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    error::{ErrorKind, JError},
+    JsonValue, Map,
+};
+
+fn mismatch(expected: &str, found: &JsonValue) -> JError {
+    JError::new(
+        ErrorKind::Type,
+        format!("expected {expected}, found {}", found.type_name()),
+    )
+}
+
+impl TryFrom<&JsonValue> for String {
+    type Error = JError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value
+            .as_str()
+            .map(ToString::to_string)
+            .ok_or_else(|| mismatch("string", value))
+    }
+}
+
+impl TryFrom<JsonValue> for String {
+    type Error = JError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::String(s) => Ok(s),
+            other => Err(mismatch("string", &other)),
+        }
+    }
+}
+
+impl TryFrom<&JsonValue> for bool {
+    type Error = JError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or_else(|| mismatch("boolean", value))
+    }
+}
+
+impl TryFrom<&JsonValue> for f64 {
+    type Error = JError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or_else(|| mismatch("number", value))
+    }
+}
+
+impl TryFrom<&JsonValue> for f32 {
+    type Error = JError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let n = f64::try_from(value)?;
+        match n as f32 {
+            m if m.is_finite() || !n.is_finite() => Ok(m),
+            _ => Err(JError::new(
+                ErrorKind::Type,
+                format!("number {n} is out of range for f32"),
+            )),
+        }
+    }
+}
+
+// Integers must be integral and fit the target type exactly
+macro_rules! try_from_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<&JsonValue> for $ty {
+                type Error = JError;
+
+                fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+                    let n = value
+                        .as_f64()
+                        .ok_or_else(|| mismatch(stringify!($ty), value))?;
+                    let int = n as $ty;
+                    if int as f64 == n && n < <$ty>::MAX as f64 + 1.0 {
+                        Ok(int)
+                    } else {
+                        Err(JError::new(ErrorKind::Type, format!(
+                            "expected {}, found number {n}",
+                            stringify!($ty)
+                        )))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+try_from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+// Scalars have nothing to move out of an owned value, so they convert through a reference
+macro_rules! try_from_owned {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<JsonValue> for $ty {
+                type Error = JError;
+
+                fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+                    <$ty>::try_from(&value)
+                }
+            }
+        )*
+    };
+}
+
+try_from_owned!(bool, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<'a, T: TryFrom<&'a JsonValue, Error = JError>> TryFrom<&'a JsonValue> for Vec<T> {
+    type Error = JError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        let arr = value.as_array().ok_or_else(|| mismatch("array", value))?;
+        arr.iter()
+            .enumerate()
+            .map(|(idx, item)| T::try_from(item).map_err(|e| at_index(idx, e)))
+            .collect()
+    }
+}
+
+impl<T: TryFrom<JsonValue, Error = JError>> TryFrom<JsonValue> for Vec<T> {
+    type Error = JError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Array(arr) => arr
+                .into_iter()
+                .enumerate()
+                .map(|(idx, item)| T::try_from(item).map_err(|e| at_index(idx, e)))
+                .collect(),
+            other => Err(mismatch("array", &other)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: TryFrom<&'a JsonValue, Error = JError>> TryFrom<&'a JsonValue>
+    for std::collections::HashMap<String, T>
+{
+    type Error = JError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        let obj = value.as_object().ok_or_else(|| mismatch("object", value))?;
+        obj.iter()
+            .map(|(key, item)| Ok((key.clone(), T::try_from(item).map_err(|e| at_key(key, e))?)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: TryFrom<JsonValue, Error = JError>> TryFrom<JsonValue>
+    for std::collections::HashMap<String, T>
+{
+    type Error = JError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Object(obj) => obj
+                .into_iter()
+                .map(|(key, item)| {
+                    let item = T::try_from(item).map_err(|e| at_key(&key, e))?;
+                    Ok((key, item))
+                })
+                .collect(),
+            other => Err(mismatch("object", &other)),
+        }
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+// Numbers are stored as f64, so integers beyond 2^53 lose precision
+macro_rules! from_number {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for JsonValue {
+                fn from(n: $ty) -> Self {
+                    JsonValue::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+from_number!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(items: Vec<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+impl<T: Into<JsonValue> + Clone> From<&[T]> for JsonValue {
+    fn from(items: &[T]) -> Self {
+        items.iter().cloned().collect()
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+impl From<Map<String, JsonValue>> for JsonValue {
+    fn from(obj: Map<String, JsonValue>) -> Self {
+        JsonValue::Object(obj)
+    }
+}
+
+impl<T: Into<JsonValue>> FromIterator<T> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JsonValue::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>> FromIterator<(K, V)> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        JsonValue::Object(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+fn at_index(idx: usize, e: JError) -> JError {
+    JError::new(e.kind(), format!("{} at index {idx}", e.message()))
+}
+
+fn at_key(key: &str, e: JError) -> JError {
+    JError::new(e.kind(), format!("{} at key {key:?}", e.message()))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec,
+    };
+
+    use crate::{parse, JsonValue};
+
+    #[test]
+    fn it_try_from_scalars() {
+        let value =
+            parse(r#"{"name": "John", "age": 30, "ratio": 0.5, "ok": true, "big": 300}"#).unwrap();
+        let get = |key| &value.as_object().unwrap()[key];
+
+        assert_eq!(String::try_from(get("name")).unwrap(), "John");
+        assert_eq!(u8::try_from(get("age")).unwrap(), 30);
+        assert_eq!(i64::try_from(get("age").clone()).unwrap(), 30);
+        assert_eq!(f32::try_from(get("ratio")).unwrap(), 0.5);
+        assert!(bool::try_from(get("ok")).unwrap());
+
+        assert_eq!(
+            u8::try_from(get("big")).unwrap_err().message(),
+            "expected u8, found number 300"
+        );
+        assert_eq!(
+            i32::try_from(get("ratio")).unwrap_err().message(),
+            "expected i32, found number 0.5"
+        );
+        assert_eq!(
+            u32::try_from(&JsonValue::Number(-1.0))
+                .unwrap_err()
+                .message(),
+            "expected u32, found number -1"
+        );
+        assert_eq!(
+            String::try_from(get("ok")).unwrap_err().message(),
+            "expected string, found boolean"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_try_from_collections() {
+        use alloc::vec::Vec;
+        use std::collections::HashMap;
+
+        use crate::JError;
+
+        let value = parse(r#"{"scores": [100, 90], "tags": {"a": ["x"], "b": []}}"#).unwrap();
+        let obj = value.as_object().unwrap();
+
+        assert_eq!(Vec::<u32>::try_from(&obj["scores"]).unwrap(), vec![100, 90]);
+        let tags: HashMap<String, Vec<String>> = HashMap::try_from(obj["tags"].clone()).unwrap();
+        assert_eq!(tags["a"], vec!["x".to_string()]);
+        assert!(tags["b"].is_empty());
+
+        let err: JError = Vec::<String>::try_from(&obj["scores"]).unwrap_err();
+        assert_eq!(err.message(), "expected string, found number at index 0");
+        let err = HashMap::<String, Vec<u8>>::try_from(&obj["tags"]).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "expected u8, found string at index 0 at key \"a\""
+        );
+    }
+
+    #[test]
+    fn it_from_rust_types() {
+        let obj = [
+            ("name", JsonValue::from("John")),
+            ("age", 30.into()),
+            ("ratio", 0.5.into()),
+            ("ok", true.into()),
+            ("scores", vec![100, 90].into()),
+            ("nick", None::<String>.into()),
+            ("tags", ["a", "b"].as_slice().into()),
+        ]
+        .into_iter()
+        .collect::<JsonValue>();
+
+        assert_eq!(
+            obj,
+            parse(
+                r#"{"name": "John", "age": 30, "ratio": 0.5, "ok": true,
+                    "scores": [100, 90], "nick": null, "tags": ["a", "b"]}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            (1..=3).collect::<JsonValue>(),
+            JsonValue::from(vec![1u8, 2, 3])
+        );
+        assert_eq!(JsonValue::from(Some("x".to_string())), JsonValue::from("x"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::Write;
+
+use indexmap::IndexSet;
+
+use crate::{
+    error::{ErrorKind, JError},
+    JsonValue,
+};
+
+impl JsonValue {
+    // to_csv Write an array of objects as CSV, see [`to_csv`]
+    pub fn to_csv<W: Write>(&self, writer: W) -> Result<(), JError> {
+        to_csv(self, writer)
+    }
+}
+
+// to_csv Write an array of objects as CSV rows
+//
+// The header is the union of the member names of all rows in first-seen order.
+// Nested objects are flattened one level into `parent.child` columns; deeper
+// values and arrays are written as compact JSON. Missing members and `null`
+// become empty cells.
+pub fn to_csv<W: Write>(value: &JsonValue, mut writer: W) -> Result<(), JError> {
+    let rows = value
+        .as_array()
+        .ok_or_else(|| {
+            JError::new(
+                ErrorKind::Type,
+                format!("Expected an array of objects, found {}", value.type_name()),
+            )
+        })?
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            row.as_object().ok_or_else(|| {
+                JError::new(
+                    ErrorKind::Type,
+                    format!(
+                        "Expected an object at index {idx}, found {}",
+                        row.type_name()
+                    ),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut header = IndexSet::new();
+    for row in &rows {
+        for (key, cell) in row.iter() {
+            match cell {
+                JsonValue::Object(nested) => {
+                    header.extend(nested.keys().map(|k| format!("{key}.{k}")));
+                }
+                _ => {
+                    header.insert(key.clone());
+                }
+            }
+        }
+    }
+
+    let io_err =
+        |e: std::io::Error| JError::new(ErrorKind::Io, format!("Failed to write CSV: {e}"));
+    write_record(&mut writer, header.iter().map(String::as_str)).map_err(io_err)?;
+
+    let mut cells = Vec::with_capacity(header.len());
+    for row in rows {
+        cells.clear();
+        for column in &header {
+            let cell = match row.get(column) {
+                Some(cell) => Some(cell),
+                None => column.split_once('.').and_then(|(parent, child)| {
+                    row.get(parent)
+                        .and_then(JsonValue::as_object)
+                        .and_then(|nested| nested.get(child))
+                }),
+            };
+            cells.push(match cell {
+                None | Some(JsonValue::Null) => String::new(),
+                Some(JsonValue::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+            });
+        }
+        write_record(&mut writer, cells.iter().map(String::as_str)).map_err(io_err)?;
+    }
+    writer.flush().map_err(io_err)
+}
+
+fn write_record<'a, W: Write>(
+    writer: &mut W,
+    fields: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (idx, field) in fields.enumerate() {
+        if idx > 0 {
+            writer.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_to_csv() {
+        let value = parse(
+            r#"[
+                {"name": "John Doe", "age": 30, "address": {"city": "Springfield", "state": "IL"}},
+                {"name": "Jane \"JJ\", Jr", "scores": [1, 2], "address": {"city": "Chicago"}, "age": null}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        value.to_csv(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "name,age,address.city,address.state,scores\r\n\
+             John Doe,30,Springfield,IL,\r\n\
+             \"Jane \"\"JJ\"\", Jr\",,Chicago,,\"[1,2]\"\r\n"
+        );
+    }
+
+    #[test]
+    fn it_to_csv_rejects_non_records() {
+        assert!(parse(r#"{"a": 1}"#).unwrap().to_csv(Vec::new()).is_err());
+        assert!(parse(r#"[{"a": 1}, 2]"#)
+            .unwrap()
+            .to_csv(Vec::new())
+            .is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! RFC 3339 timestamps inside string values, behind the `chrono` feature.
+
+use alloc::{format, string::String, vec::Vec};
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::{
+    error::JError,
+    pointer::{escape_token, type_mismatch},
+    JsonValue, ParseOptions,
+};
+
+/// Timestamps found in a document, as JSON pointers paired with the parsed values.
+pub type Timestamps = Vec<(String, DateTime<FixedOffset>)>;
+
+impl JsonValue {
+    // as_datetime Parse a string value, or a custom literal, as an RFC 3339 timestamp
+    pub fn as_datetime(&self) -> Option<DateTime<FixedOffset>> {
+        match self {
+            JsonValue::String(s) | JsonValue::Custom(_, s) => DateTime::parse_from_rfc3339(s).ok(),
+            _ => None,
+        }
+    }
+
+    // get_datetime_at Look up an RFC 3339 timestamp by JSON pointer
+    pub fn get_datetime_at(&self, pointer: &str) -> Result<DateTime<FixedOffset>, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_datetime()
+            .ok_or_else(|| type_mismatch("RFC 3339 timestamp", pointer, found))
+    }
+}
+
+// find_timestamps Every string in `value` that parses as an RFC 3339 timestamp, in iteration order
+pub fn find_timestamps(value: &JsonValue) -> Timestamps {
+    let mut found = Vec::new();
+    collect(value, &mut String::new(), &mut found);
+    found
+}
+
+// parse_with_timestamps Parse a document and mark which of its strings are timestamps
+pub fn parse_with_timestamps(
+    source: &str,
+    options: &ParseOptions,
+) -> Result<(JsonValue, Timestamps), JError> {
+    let value = crate::parse_with(source, options)?;
+    let timestamps = find_timestamps(&value);
+    Ok((value, timestamps))
+}
+
+fn collect(value: &JsonValue, path: &mut String, found: &mut Timestamps) {
+    let len = path.len();
+    match value {
+        JsonValue::Array(arr) => {
+            for (idx, item) in arr.iter().enumerate() {
+                path.push_str(&format!("/{idx}"));
+                collect(item, path, found);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Object(obj) => {
+            for (key, item) in obj {
+                path.push('/');
+                path.push_str(&escape_token(key));
+                collect(item, path, found);
+                path.truncate(len);
+            }
+        }
+        other => {
+            if let Some(at) = other.as_datetime() {
+                found.push((path.clone(), at));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, ParseOptions};
+
+    use super::{find_timestamps, parse_with_timestamps};
+
+    #[test]
+    fn it_datetimes() {
+        let doc = parse(
+            r#"{"created": "2024-01-31T10:00:00Z", "events": [{"at": "2024-02-01T08:30:00.5+02:00"}, {"at": "tomorrow"}], "n": 3}"#,
+        )
+        .unwrap();
+
+        let created = doc.get_datetime_at("/created").unwrap();
+        assert_eq!(created.to_rfc3339(), "2024-01-31T10:00:00+00:00");
+        assert_eq!(
+            doc.get_datetime_at("/events/0/at")
+                .unwrap()
+                .timestamp_millis(),
+            1_706_769_000_500
+        );
+        assert_eq!(
+            doc.get_datetime_at("/events/1/at").unwrap_err().message(),
+            "expected RFC 3339 timestamp at /events/1/at, found string"
+        );
+        assert!(doc.get_datetime_at("/n").is_err());
+        assert!(doc.get("n").unwrap().as_datetime().is_none());
+
+        let paths = find_timestamps(&doc)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["/created", "/events/0/at"]);
+
+        let (value, timestamps) =
+            parse_with_timestamps(r#"["2024-01-31T10:00:00Z"]"#, &ParseOptions::new()).unwrap();
+        assert_eq!(
+            timestamps,
+            [(
+                "/0".to_string(),
+                value.pointer("/0").and_then(|v| v.as_datetime()).unwrap()
+            )]
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{
+    pointer::{display_path, escape_token},
+    ser::{to_string, to_string_pretty},
+    JsonValue, Map,
+};
+
+/// One operation of an RFC 6902 JSON Patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    Add { path: String, value: JsonValue },
+    Remove { path: String },
+    Replace { path: String, value: JsonValue },
+}
+
+impl PatchOp {
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOp::Add { path, .. }
+            | PatchOp::Remove { path }
+            | PatchOp::Replace { path, .. } => path,
+        }
+    }
+
+    // to_value Render the operation as a JSON Patch object
+    pub fn to_value(&self) -> JsonValue {
+        let (op, value) = match self {
+            PatchOp::Add { value, .. } => ("add", Some(value)),
+            PatchOp::Remove { .. } => ("remove", None),
+            PatchOp::Replace { value, .. } => ("replace", Some(value)),
+        };
+        let mut obj = Map::default();
+        obj.insert("op".to_string(), JsonValue::String(op.to_string()));
+        obj.insert(
+            "path".to_string(),
+            JsonValue::String(self.path().to_string()),
+        );
+        if let Some(value) = value {
+            obj.insert("value".to_string(), value.clone());
+        }
+        JsonValue::Object(obj)
+    }
+}
+
+// diff Compute a JSON Patch turning `from` into `to`
+//
+// Object members are matched by key, so member order is ignored. Array
+// elements are matched by position; surplus elements are removed from the end
+// first so every emitted index is valid when the patch is applied in order.
+pub fn diff(from: &JsonValue, to: &JsonValue) -> Vec<PatchOp> {
+    changes(from, to).into_iter().map(PatchOp::from).collect()
+}
+
+// to_patch Render a list of operations as a JSON Patch document
+pub fn to_patch(ops: &[PatchOp]) -> JsonValue {
+    JsonValue::Array(ops.iter().map(PatchOp::to_value).collect())
+}
+
+/// One difference between two documents, with the values on both sides.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    Added {
+        path: String,
+        value: &'a JsonValue,
+    },
+    Removed {
+        path: String,
+        value: &'a JsonValue,
+    },
+    Changed {
+        path: String,
+        from: &'a JsonValue,
+        to: &'a JsonValue,
+    },
+}
+
+impl Change<'_> {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl From<Change<'_>> for PatchOp {
+    fn from(change: Change<'_>) -> Self {
+        match change {
+            Change::Added { path, value } => PatchOp::Add {
+                path,
+                value: value.clone(),
+            },
+            Change::Removed { path, .. } => PatchOp::Remove { path },
+            Change::Changed { path, to, .. } => PatchOp::Replace {
+                path,
+                value: to.clone(),
+            },
+        }
+    }
+}
+
+// changes List what differs between `from` and `to`, in the order `diff` emits its operations
+pub fn changes<'a>(from: &'a JsonValue, to: &'a JsonValue) -> Vec<Change<'a>> {
+    let mut out = Vec::new();
+    changes_at(from, to, &mut String::new(), &mut out);
+    out
+}
+
+fn changes_at<'a>(
+    from: &'a JsonValue,
+    to: &'a JsonValue,
+    path: &mut String,
+    out: &mut Vec<Change<'a>>,
+) {
+    match (from, to) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            for (key, old) in a {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(key));
+                match b.get(key) {
+                    Some(new) => changes_at(old, new, path, out),
+                    None => out.push(Change::Removed {
+                        path: path.clone(),
+                        value: old,
+                    }),
+                }
+                path.truncate(len);
+            }
+            for (key, new) in b.iter().filter(|(key, _)| !a.contains_key(*key)) {
+                out.push(Change::Added {
+                    path: format!("{path}/{}", escape_token(key)),
+                    value: new,
+                });
+            }
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            for (idx, (old, new)) in a.iter().zip(b).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{idx}"));
+                changes_at(old, new, path, out);
+                path.truncate(len);
+            }
+            for (idx, old) in a.iter().enumerate().skip(b.len()).rev() {
+                out.push(Change::Removed {
+                    path: format!("{path}/{idx}"),
+                    value: old,
+                });
+            }
+            for (idx, new) in b.iter().enumerate().skip(a.len()) {
+                out.push(Change::Added {
+                    path: format!("{path}/{idx}"),
+                    value: new,
+                });
+            }
+        }
+        _ if from == to => {}
+        _ => out.push(Change::Changed {
+            path: path.clone(),
+            from,
+            to,
+        }),
+    }
+}
+
+/// Layout of a [`report`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportOptions {
+    /// One block per change, the old value on `-` lines and the new one on `+` lines,
+    /// pretty-printed, instead of one line per change.
+    pub unified: bool,
+    /// Color additions green, removals red and changes yellow with ANSI escapes.
+    pub colors: bool,
+}
+
+/// Describe what changed between two documents, for people rather than tools.
+///
+/// Member order is ignored, so two responses that only order their keys differently
+/// compare equal. Each change is listed under its JSON pointer, by default on one line:
+///
+/// ```text
+/// ~ /age: 30 -> 31
+/// - /tags/1: "b"
+/// + /city: "Paris"
+/// 3 changes: 1 added, 1 removed, 1 changed
+/// ```
+///
+/// The report is empty when the documents are equal.
+pub fn report(from: &JsonValue, to: &JsonValue, options: &ReportOptions) -> String {
+    let changes = changes(from, to);
+    let mut out = String::new();
+    if changes.is_empty() {
+        return out;
+    }
+    let paint = |out: &mut String, color: &str, text: &str| match options.colors {
+        true => {
+            let _ = write!(out, "\x1b[{color}m{text}\x1b[0m");
+        }
+        false => out.push_str(text),
+    };
+    for change in &changes {
+        let path = display_path(change.path());
+        if options.unified {
+            let _ = writeln!(out, "@@ {path} @@");
+            let (old, new) = match change {
+                Change::Added { value, .. } => (None, Some(value)),
+                Change::Removed { value, .. } => (Some(value), None),
+                Change::Changed { from, to, .. } => (Some(from), Some(to)),
+            };
+            for (sign, color, value) in [("-", RED, old), ("+", GREEN, new)] {
+                for line in value
+                    .map(|v| to_string_pretty(v))
+                    .iter()
+                    .flat_map(|v| v.lines())
+                {
+                    paint(&mut out, color, &format!("{sign}{line}"));
+                    out.push('\n');
+                }
+            }
+            continue;
+        }
+        let line = match change {
+            Change::Added { value, .. } => (GREEN, format!("+ {path}: {}", to_string(value))),
+            Change::Removed { value, .. } => (RED, format!("- {path}: {}", to_string(value))),
+            Change::Changed { from, to, .. } => (
+                YELLOW,
+                format!("~ {path}: {} -> {}", to_string(from), to_string(to)),
+            ),
+        };
+        paint(&mut out, line.0, &line.1);
+        out.push('\n');
+    }
+    let count = |pick: fn(&Change) -> bool| changes.iter().filter(|c| pick(c)).count();
+    let _ = write!(
+        out,
+        "{} change{}: {} added, {} removed, {} changed",
+        changes.len(),
+        if changes.len() == 1 { "" } else { "s" },
+        count(|c| matches!(c, Change::Added { .. })),
+        count(|c| matches!(c, Change::Removed { .. })),
+        count(|c| matches!(c, Change::Changed { .. })),
+    );
+    out
+}
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+const YELLOW: &str = "33";
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::{changes, diff, report, to_patch, Change, ReportOptions};
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_diff_objects_and_arrays() {
+        let from = parse(r#"{"name": "John", "age": 30, "scores": [1, 2, 3], "a/b": {}}"#).unwrap();
+        let to = parse(r#"{"age": 31, "name": "John", "scores": [1, 5], "city": "X"}"#).unwrap();
+
+        assert_eq!(
+            to_patch(&diff(&from, &to)),
+            parse(
+                r#"[
+                    {"op": "replace", "path": "/age", "value": 31},
+                    {"op": "replace", "path": "/scores/1", "value": 5},
+                    {"op": "remove", "path": "/scores/2"},
+                    {"op": "remove", "path": "/a~1b"},
+                    {"op": "add", "path": "/city", "value": "X"}
+                ]"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_diff_equal_and_root_values() {
+        let value = parse(r#"{"a": [1, {"b": null}]}"#).unwrap();
+        assert!(diff(&value, &value).is_empty());
+
+        let ops = diff(&parse("[1]").unwrap(), &parse("[1, 2, 3]").unwrap());
+        assert_eq!(
+            to_patch(&ops),
+            parse(r#"[{"op": "add", "path": "/1", "value": 2}, {"op": "add", "path": "/2", "value": 3}]"#)
+                .unwrap()
+        );
+
+        let ops = diff(&parse("1").unwrap(), &parse(r#""x""#).unwrap());
+        assert_eq!(
+            to_patch(&ops),
+            parse(r#"[{"op": "replace", "path": "", "value": "x"}]"#).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_diff_report() {
+        let from =
+            parse(r#"{"name": "ann", "age": 30, "tags": ["a", "b"], "meta": {"v": 1}}"#).unwrap();
+        let to = parse(
+            r#"{"meta": {"v": 1}, "age": 31, "name": "ann", "tags": ["a"], "city": "Paris"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            report(&from, &to, &ReportOptions::default()),
+            r#"~ /age: 30 -> 31
+- /tags/1: "b"
++ /city: "Paris"
+3 changes: 1 added, 1 removed, 1 changed"#
+        );
+
+        let unified = ReportOptions {
+            unified: true,
+            ..ReportOptions::default()
+        };
+        let to =
+            parse(r#"{"name": "ann", "age": 30, "tags": ["a", "b"], "meta": {"v": 2, "w": [1]}}"#)
+                .unwrap();
+        assert_eq!(
+            report(&from, &to, &unified),
+            r#"@@ /meta/v @@
+-1
++2
+@@ /meta/w @@
++[
++  1
++]
+2 changes: 1 added, 0 removed, 1 changed"#
+        );
+
+        let colored = ReportOptions {
+            colors: true,
+            ..ReportOptions::default()
+        };
+        assert_eq!(
+            report(&parse("1").unwrap(), &parse("2").unwrap(), &colored),
+            "\u{1b}[33m~ /: 1 -> 2\u{1b}[0m\n1 change: 0 added, 0 removed, 1 changed"
+        );
+        assert_eq!(report(&from, &from, &colored), "");
+
+        let (long, short) = (parse("[1, 2, 3]").unwrap(), parse("[1]").unwrap());
+        let ops = changes(&long, &short);
+        assert!(matches!(
+            &ops[..],
+            [Change::Removed { .. }, Change::Removed { .. }]
+        ));
+        assert_eq!(ops[0].path(), "/2");
+    }
+}
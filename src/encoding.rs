@@ -0,0 +1,112 @@
+use alloc::{borrow::Cow, format, string::String};
+
+use crate::error::{ErrorKind, JError};
+
+/// The UTF-8 byte order mark, skipped wherever lexing starts.
+pub const UTF8_BOM: &str = "\u{feff}";
+
+/// What to do with bytes that are not valid UTF-8 (or UTF-16, when decoding it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Utf8Policy {
+    /// Fail, reporting the offset of the first invalid byte.
+    #[default]
+    Error,
+    /// Drop invalid sequences.
+    Strip,
+    /// Replace each invalid sequence with U+FFFD.
+    Replace,
+}
+
+/// Text encoding of a byte payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Detect the encoding from a byte order mark, or else from where the zero bytes fall
+    /// around the first character, which is ASCII in any JSON text (RFC 4627, section 3).
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        match bytes {
+            [0xef, 0xbb, 0xbf, ..] => Encoding::Utf8,
+            [0xff, 0xfe, ..] => Encoding::Utf16Le,
+            [0xfe, 0xff, ..] => Encoding::Utf16Be,
+            [0, b, ..] if *b != 0 => Encoding::Utf16Be,
+            [b, 0, ..] if *b != 0 => Encoding::Utf16Le,
+            _ => Encoding::Utf8,
+        }
+    }
+}
+
+// decode Turn bytes in any supported encoding into text, detecting the encoding first
+pub fn decode(bytes: &[u8], policy: Utf8Policy) -> Result<Cow<'_, str>, JError> {
+    match Encoding::detect(bytes) {
+        Encoding::Utf8 => decode_utf8(bytes, policy),
+        encoding => decode_utf16(bytes, encoding == Encoding::Utf16Be, policy).map(Cow::Owned),
+    }
+}
+
+/// Transcode UTF-16 to a string, keeping a byte order mark as U+FEFF for the lexer to skip.
+///
+/// Unpaired surrogates and a dangling odd byte are invalid input, handled by `policy`
+/// like invalid UTF-8.
+pub fn decode_utf16(bytes: &[u8], big_endian: bool, policy: Utf8Policy) -> Result<String, JError> {
+    let units = bytes.chunks_exact(2).map(|pair| match big_endian {
+        true => u16::from_be_bytes([pair[0], pair[1]]),
+        false => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut offset = 0;
+    for c in char::decode_utf16(units) {
+        let width = c.as_ref().map_or(2, |c| c.len_utf16() * 2);
+        match (c, policy) {
+            (Ok(c), _) => text.push(c),
+            (Err(_), Utf8Policy::Error) => {
+                return Err(JError::new(
+                    ErrorKind::Encoding,
+                    format!("Invalid UTF-16 at byte {offset}"),
+                ))
+            }
+            (Err(_), Utf8Policy::Strip) => {}
+            (Err(_), Utf8Policy::Replace) => text.push(char::REPLACEMENT_CHARACTER),
+        }
+        offset += width;
+    }
+    match (bytes.len() % 2, policy) {
+        (0, _) | (_, Utf8Policy::Strip) => Ok(text),
+        (_, Utf8Policy::Error) => Err(JError::new(
+            ErrorKind::Encoding,
+            format!("Invalid UTF-16 at byte {}", bytes.len() - 1),
+        )),
+        (_, Utf8Policy::Replace) => {
+            text.push(char::REPLACEMENT_CHARACTER);
+            Ok(text)
+        }
+    }
+}
+
+// decode_utf8 Turn bytes into text under `policy`, borrowing them when they are valid
+pub fn decode_utf8(bytes: &[u8], policy: Utf8Policy) -> Result<Cow<'_, str>, JError> {
+    let err = match core::str::from_utf8(bytes) {
+        Ok(text) => return Ok(Cow::Borrowed(text)),
+        Err(err) => err,
+    };
+    let mut text = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        match policy {
+            _ if chunk.invalid().is_empty() => {}
+            Utf8Policy::Error => {
+                return Err(JError::new(
+                    ErrorKind::Encoding,
+                    format!("Invalid UTF-8 at byte {}", err.valid_up_to()),
+                ))
+            }
+            Utf8Policy::Strip => {}
+            Utf8Policy::Replace => text.push(char::REPLACEMENT_CHARACTER),
+        }
+    }
+    Ok(Cow::Owned(text))
+}
@@ -1,20 +1,421 @@
-use crate::{IResult, Input};
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, ops::Range};
 
-#[derive(Debug)]
-pub struct JError(pub String);
+use crate::{
+    lines::{ColumnUnit, LineCol, LineIndex},
+    pointer::escape_token,
+    token::{tokenize_recovering, JsonTokenKind, TokenInput, TokenKind},
+    value::unescape_cow,
+    IResult, JsonValue, Map,
+};
+
+/// What went wrong, for callers that need to react to errors rather than print them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input is not valid JSON, or not valid in the format being read
+    Syntax,
+    /// Source text that the lexer does not accept as a token
+    Lex,
+    /// The input ended in the middle of a value
+    Eof,
+    /// An invalid escape sequence inside a string
+    Escape,
+    /// Bytes that are not valid in the detected text encoding
+    Encoding,
+    /// A value of the wrong type or range for the requested conversion
+    Type,
+    /// A value, nesting depth or size this crate cannot handle
+    Unsupported,
+    /// Reading from or writing to the underlying stream failed
+    Io,
+    /// Anything not covered above
+    Other,
+}
+
+/// Stable identifier of an error, for tools that match on errors rather than read them.
+///
+/// The codes never change meaning; new ones are only ever added at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `E0001` A token the grammar does not allow where it appears
+    UnexpectedToken,
+    /// `E0002` A string without its closing quote
+    UnterminatedString,
+    /// `E0003` Source text that is not a token
+    InvalidToken,
+    /// `E0004` A raw control character inside a string
+    ControlCharacter,
+    /// `E0005` A hexadecimal or non-finite number the options do not allow
+    InvalidNumber,
+    /// `E0006` An invalid escape sequence inside a string
+    InvalidEscape,
+    /// `E0007` The input ended in the middle of a value
+    UnexpectedEof,
+    /// `E0008` Bytes that are not valid in the detected text encoding
+    InvalidEncoding,
+    /// `E0009` A value of the wrong type or range for the requested conversion
+    TypeMismatch,
+    /// `E0010` A value, nesting depth or size this crate cannot handle
+    Unsupported,
+    /// `E0011` Reading from or writing to the underlying stream failed
+    Io,
+    /// `E0012` Anything not covered above
+    Other,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedToken => "E0001",
+            ErrorCode::UnterminatedString => "E0002",
+            ErrorCode::InvalidToken => "E0003",
+            ErrorCode::ControlCharacter => "E0004",
+            ErrorCode::InvalidNumber => "E0005",
+            ErrorCode::InvalidEscape => "E0006",
+            ErrorCode::UnexpectedEof => "E0007",
+            ErrorCode::InvalidEncoding => "E0008",
+            ErrorCode::TypeMismatch => "E0009",
+            ErrorCode::Unsupported => "E0010",
+            ErrorCode::Io => "E0011",
+            ErrorCode::Other => "E0012",
+        }
+    }
+}
+
+// The code an error of each kind gets unless its origin knows better
+impl From<ErrorKind> for ErrorCode {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::Syntax => ErrorCode::UnexpectedToken,
+            ErrorKind::Lex => ErrorCode::InvalidToken,
+            ErrorKind::Eof => ErrorCode::UnexpectedEof,
+            ErrorKind::Escape => ErrorCode::InvalidEscape,
+            ErrorKind::Encoding => ErrorCode::InvalidEncoding,
+            ErrorKind::Type => ErrorCode::TypeMismatch,
+            ErrorKind::Unsupported => ErrorCode::Unsupported,
+            ErrorKind::Io => ErrorCode::Io,
+            ErrorKind::Other => ErrorCode::Other,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JError {
+    kind: ErrorKind,
+    code: ErrorCode,
+    message: Message,
+    // Grammar rules the error passed through, innermost first
+    context: Vec<&'static str>,
+    // Bytes of the source the error is about
+    span: Option<Range<usize>>,
+    // Boxed as only errors leaving a parse entry point carry it
+    location: Option<Box<Location>>,
+}
+
+// Location Where a span sits in its source, resolved by `JError::locate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Location {
+    line_col: LineCol,
+    path: String,
+}
+
+// Message Error text, kept unrendered for token mismatches since `alt` and
+// `separated_list0` create and drop those on almost every token
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Message {
+    Text(Cow<'static, str>),
+    Mismatch {
+        expected: Expected,
+        at: Option<usize>,
+    },
+}
+
+/// What a token-level parser was looking for when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Expected {
+    Kind(TokenKind),
+    Text(&'static str),
+    // Any token that starts a value
+    Value,
+}
 
 impl JError {
-    pub fn from<O>(msg: &str) -> IResult<O> {
-        Err(nom::Err::Error(JError(msg.to_string())))
+    pub fn new(kind: ErrorKind, message: impl Into<Cow<'static, str>>) -> Self {
+        JError {
+            kind,
+            code: ErrorCode::from(kind),
+            message: Message::Text(message.into()),
+            context: Vec::new(),
+            span: None,
+            location: None,
+        }
+    }
+
+    // mismatch A recoverable syntax error whose message is only built when displayed
+    pub(crate) fn mismatch(expected: Expected, span: Option<Range<usize>>) -> Self {
+        JError {
+            kind: ErrorKind::Syntax,
+            code: match span {
+                Some(_) => ErrorCode::UnexpectedToken,
+                None => ErrorCode::UnexpectedEof,
+            },
+            message: Message::Mismatch {
+                expected,
+                at: span.as_ref().map(|span| span.start),
+            },
+            context: Vec::new(),
+            span,
+            location: None,
+        }
+    }
+
+    pub fn from<O>(msg: &str) -> IResult<'_, O> {
+        Err(nom::Err::Error(JError::new(
+            ErrorKind::Syntax,
+            msg.to_string(),
+        )))
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    // message The error text without the context chain, rendered on demand for token mismatches
+    pub fn message(&self) -> Cow<'_, str> {
+        match &self.message {
+            Message::Text(text) => Cow::Borrowed(text),
+            Message::Mismatch { .. } => Cow::Owned(self.message.to_string()),
+        }
+    }
+
+    // context The labels of the `context` rules the error bubbled out of, innermost first
+    pub fn context(&self) -> &[&'static str] {
+        &self.context
+    }
+
+    // in_context Record that the error left the grammar rule `label`
+    pub fn in_context(mut self, label: &'static str) -> Self {
+        self.context.push(label);
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    // with_code Replace the code derived from the kind with a more precise one
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    // span The bytes of the source the error is about, if known
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    // line_col Zero-based position of the span start, once resolved by `locate`
+    pub fn line_col(&self) -> Option<LineCol> {
+        self.location.as_ref().map(|location| location.line_col)
+    }
+
+    // path JSON pointer to the value the error was found in, once resolved by `locate`
+    pub fn path(&self) -> Option<&str> {
+        self.location
+            .as_ref()
+            .map(|location| location.path.as_str())
+    }
+
+    /// Resolve the span against the source it was found in, filling in
+    /// [`line_col`](Self::line_col) and [`path`](Self::path).
+    ///
+    /// The parse functions do this before returning; errors without a span are left
+    /// as they are. The path is that of the innermost value open at the span, so a bad
+    /// member value reports the member and a bad key reports its object.
+    pub fn locate(mut self, source: &str) -> Self {
+        if let Some(span) = &self.span {
+            self.location = Some(Box::new(Location {
+                line_col: LineIndex::new(source).line_col(span.start, ColumnUnit::Utf8),
+                path: path_at(source, span.start),
+            }));
+        }
+        self
+    }
+
+    // locate_document Like `locate`, for an error from reading all of `source`, where a
+    // missing span can only mean the input ended early
+    pub(crate) fn locate_document(self, source: &str) -> Self {
+        match self.span {
+            Some(_) => self,
+            None => self.with_span(source.len()..source.len()),
+        }
+        .locate(source)
+    }
+
+    /// The error as a JSON object, for CI annotations and editor frontends.
+    ///
+    /// ```json
+    /// {"code": "E0003", "message": "Invalid token \"@\" at 6..7", "context": ["array element"],
+    ///  "span": {"start": 6, "end": 7}, "line": 0, "col": 6, "path": "/a/0"}
+    /// ```
+    ///
+    /// `message` leaves out the context chain, which is listed in `context`. Unknown
+    /// positions are `null`; `line` and `col` are zero-based, the column in UTF-8 bytes
+    /// as the span is.
+    pub fn to_json(&self) -> JsonValue {
+        let mut obj = Map::default();
+        obj.insert("code".into(), JsonValue::String(self.code.as_str().into()));
+        obj.insert("message".into(), JsonValue::String(self.message().into()));
+        obj.insert(
+            "context".into(),
+            JsonValue::Array(
+                self.context
+                    .iter()
+                    .map(|label| JsonValue::String((*label).into()))
+                    .collect(),
+            ),
+        );
+        let span = match &self.span {
+            Some(span) => {
+                let mut bounds = Map::default();
+                bounds.insert("start".into(), JsonValue::Number(span.start as f64));
+                bounds.insert("end".into(), JsonValue::Number(span.end as f64));
+                JsonValue::Object(bounds)
+            }
+            None => JsonValue::Null,
+        };
+        obj.insert("span".into(), span);
+        let line_col = self.line_col();
+        let number = |n: Option<usize>| n.map_or(JsonValue::Null, |n| JsonValue::Number(n as f64));
+        obj.insert("line".into(), number(line_col.map(|pos| pos.line)));
+        obj.insert("col".into(), number(line_col.map(|pos| pos.col)));
+        obj.insert(
+            "path".into(),
+            self.path()
+                .map_or(JsonValue::Null, |path| JsonValue::String(path.into())),
+        );
+        JsonValue::Object(obj)
+    }
+}
+
+// path_at JSON pointer to the innermost value open at byte `offset`, from the tokens before it
+fn path_at(source: &str, offset: usize) -> String {
+    enum Frame {
+        // The current key, and whether the next string is a key
+        Object(Option<String>, bool),
+        // The current index
+        Array(usize),
+    }
+    let mut stack: Vec<Frame> = vec![];
+    for token in tokenize_recovering(source) {
+        if token.span.start >= offset {
+            break;
+        }
+        match (&token.kind, stack.last_mut()) {
+            (JsonTokenKind::OpenBrace, _) => stack.push(Frame::Object(None, true)),
+            (JsonTokenKind::OpenBracket, _) => stack.push(Frame::Array(0)),
+            (JsonTokenKind::CloseBrace | JsonTokenKind::CloseBracket, _) => {
+                stack.pop();
+            }
+            (JsonTokenKind::Comma, Some(Frame::Object(key, expect_key))) => {
+                *key = None;
+                *expect_key = true;
+            }
+            (JsonTokenKind::Comma, Some(Frame::Array(idx))) => *idx += 1,
+            (JsonTokenKind::String, Some(Frame::Object(key, expect_key))) if *expect_key => {
+                let text = token.text();
+                let raw = &text[1..text.len().max(2) - 1];
+                *key = Some(unescape_cow(raw).unwrap_or(Cow::Borrowed(raw)).into_owned());
+                *expect_key = false;
+            }
+            _ => {}
+        }
+    }
+    let mut path = String::new();
+    for frame in &stack {
+        match frame {
+            Frame::Object(Some(key), _) => {
+                path.push('/');
+                path.push_str(&escape_token(key));
+            }
+            Frame::Object(None, _) => break,
+            Frame::Array(idx) => path.push_str(&format!("/{idx}")),
+        }
+    }
+    path
+}
+
+impl fmt::Display for JError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        for (idx, label) in self.context.iter().enumerate() {
+            let sep = if idx == 0 { " (in " } else { ", in " };
+            write!(f, "{sep}{label}")?;
+        }
+        match self.context.is_empty() {
+            true => Ok(()),
+            false => f.write_str(")"),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Text(text) => f.write_str(text),
+            Message::Mismatch { expected, at } => {
+                match expected {
+                    Expected::Kind(kind) => write!(f, "JsonToken Kind {kind} does not match")?,
+                    Expected::Text(text) => write!(f, "Json Text {text} does not match")?,
+                    Expected::Value => f.write_str("Expected a value")?,
+                }
+                match at {
+                    Some(at) => write!(f, " at {at}"),
+                    None => f.write_str(" at the end of input"),
+                }
+            }
+        }
     }
 }
 
-impl nom::error::ParseError<Input<'_>> for JError {
-    fn from_error_kind(input: Input, kind: nom::error::ErrorKind) -> Self {
-        JError(format!("Error: {:?} at {:?}", kind, input))
+#[cfg(feature = "std")]
+impl std::error::Error for JError {}
+
+impl<'a, I: TokenInput<'a>> nom::error::ParseError<I> for JError {
+    fn from_error_kind(input: I, kind: nom::error::ErrorKind) -> Self {
+        JError::new(
+            ErrorKind::Syntax,
+            format!("Error: {:?} at {:?}", kind, input),
+        )
     }
 
-    fn append(_: Input, _: nom::error::ErrorKind, other: Self) -> Self {
+    fn append(_: I, _: nom::error::ErrorKind, other: Self) -> Self {
         other
     }
 }
+
+impl<I> nom::error::ContextError<I> for JError {
+    fn add_context(_: I, ctx: &'static str, other: Self) -> Self {
+        other.in_context(ctx)
+    }
+}
@@ -0,0 +1,326 @@
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+
+use crate::{
+    error::{ErrorKind, JError},
+    value::{self, map_with_capacity},
+    JsonValue, Map, NumberMode,
+};
+
+/// Flat, SAX-style view of a JSON document.
+///
+/// Container starts carry the member count when it is known up front
+/// (e.g. when walking an in-memory value) and `None` when it is not.
+///
+/// A long string value may instead arrive as a run of `StringChunk`s closed by a
+/// `StringEnd`; the chunks carry decoded text and concatenate to the whole string.
+/// Keys are never split.
+///
+/// Numbers wider than an `f64` (`BigInt` and `Decimal` values) arrive as a
+/// `RawNumber` holding their literal text, so no digits are lost on the way.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonEvent<'a> {
+    StartObject(Option<usize>),
+    Key(Cow<'a, str>),
+    EndObject,
+    StartArray(Option<usize>),
+    EndArray,
+    String(Cow<'a, str>),
+    StringChunk(Cow<'a, str>),
+    StringEnd,
+    Number(f64),
+    RawNumber(Cow<'a, str>),
+    Bool(bool),
+    Null,
+}
+
+impl JsonValue {
+    // events Walk the value as a sequence of events, borrowing its strings
+    pub fn events(&self) -> Events<'_> {
+        Events {
+            stack: Vec::new(),
+            pending: Some(self),
+        }
+    }
+
+    // from_events Build a value from a complete event sequence
+    pub fn from_events<'a>(
+        events: impl IntoIterator<Item = JsonEvent<'a>>,
+    ) -> Result<JsonValue, JError> {
+        JsonValue::from_events_with(events, NumberMode::default())
+    }
+
+    // from_events_with Build a value from events, storing raw numbers as `mode` asks
+    pub fn from_events_with<'a>(
+        events: impl IntoIterator<Item = JsonEvent<'a>>,
+        mode: NumberMode,
+    ) -> Result<JsonValue, JError> {
+        let mut builder = ValueBuilder::with_number_mode(mode);
+        for event in events {
+            builder.push(event)?;
+        }
+        builder.finish()
+    }
+}
+
+pub struct Events<'a> {
+    stack: Vec<Frame<'a>>,
+    pending: Option<&'a JsonValue>,
+}
+
+enum Frame<'a> {
+    Array(core::slice::Iter<'a, JsonValue>),
+    Object(<&'a Map<String, JsonValue> as IntoIterator>::IntoIter),
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = JsonEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(match value {
+                JsonValue::Null => JsonEvent::Null,
+                JsonValue::Bool(b) => JsonEvent::Bool(*b),
+                JsonValue::Number(n) => JsonEvent::Number(*n),
+                #[cfg(feature = "num-bigint")]
+                JsonValue::BigInt(n) => JsonEvent::RawNumber(Cow::Owned(format!("{n}"))),
+                #[cfg(feature = "rust_decimal")]
+                JsonValue::Decimal(n) => JsonEvent::RawNumber(Cow::Owned(format!("{n}"))),
+                JsonValue::String(s) | JsonValue::Custom(_, s) => {
+                    JsonEvent::String(Cow::Borrowed(s))
+                }
+                JsonValue::Array(arr) => {
+                    self.stack.push(Frame::Array(arr.iter()));
+                    JsonEvent::StartArray(Some(arr.len()))
+                }
+                JsonValue::Object(obj) => {
+                    self.stack.push(Frame::Object(obj.iter()));
+                    JsonEvent::StartObject(Some(obj.len()))
+                }
+            });
+        }
+
+        match self.stack.last_mut()? {
+            Frame::Array(items) => match items.next() {
+                Some(item) => {
+                    self.pending = Some(item);
+                    self.next()
+                }
+                None => {
+                    self.stack.pop();
+                    Some(JsonEvent::EndArray)
+                }
+            },
+            Frame::Object(members) => match members.next() {
+                Some((key, item)) => {
+                    self.pending = Some(item);
+                    Some(JsonEvent::Key(Cow::Borrowed(key)))
+                }
+                None => {
+                    self.stack.pop();
+                    Some(JsonEvent::EndObject)
+                }
+            },
+        }
+    }
+}
+
+/// Assembles a [`JsonValue`] from events pushed one at a time.
+#[derive(Debug, Default)]
+pub struct ValueBuilder {
+    stack: Vec<Partial>,
+    root: Option<JsonValue>,
+    // Text of a chunked string still waiting for its `StringEnd`
+    chunked: Option<String>,
+    number_mode: NumberMode,
+}
+
+#[derive(Debug)]
+enum Partial {
+    Array(Vec<JsonValue>),
+    Object(Map<String, JsonValue>, Option<String>),
+}
+
+impl ValueBuilder {
+    pub fn new() -> Self {
+        ValueBuilder::default()
+    }
+
+    // with_number_mode Store `RawNumber` events as `mode` asks, e.g. as exact decimals
+    pub fn with_number_mode(mode: NumberMode) -> Self {
+        ValueBuilder {
+            number_mode: mode,
+            ..ValueBuilder::default()
+        }
+    }
+
+    pub fn push(&mut self, event: JsonEvent) -> Result<(), JError> {
+        if self.chunked.is_some()
+            && !matches!(event, JsonEvent::StringChunk(_) | JsonEvent::StringEnd)
+        {
+            return Err(JError::new(
+                ErrorKind::Syntax,
+                "Unterminated chunked string",
+            ));
+        }
+        let value = match event {
+            JsonEvent::StartObject(len) => {
+                let map = map_with_capacity(len.unwrap_or(0));
+                self.stack.push(Partial::Object(map, None));
+                return Ok(());
+            }
+            JsonEvent::StartArray(len) => {
+                let vec = Vec::with_capacity(len.unwrap_or(0));
+                self.stack.push(Partial::Array(vec));
+                return Ok(());
+            }
+            JsonEvent::Key(key) => {
+                return match self.stack.last_mut() {
+                    Some(Partial::Object(_, slot @ None)) => {
+                        *slot = Some(key.into_owned());
+                        Ok(())
+                    }
+                    _ => Err(JError::new(
+                        ErrorKind::Syntax,
+                        format!("Unexpected key {key:?}"),
+                    )),
+                }
+            }
+            JsonEvent::EndObject => match self.stack.pop() {
+                Some(Partial::Object(map, None)) => JsonValue::Object(map),
+                _ => return Err(JError::new(ErrorKind::Syntax, "Unexpected end of object")),
+            },
+            JsonEvent::EndArray => match self.stack.pop() {
+                Some(Partial::Array(vec)) => JsonValue::Array(vec),
+                _ => return Err(JError::new(ErrorKind::Syntax, "Unexpected end of array")),
+            },
+            JsonEvent::String(s) => JsonValue::String(s.into_owned()),
+            JsonEvent::StringChunk(s) => {
+                self.chunked.get_or_insert_with(String::new).push_str(&s);
+                return Ok(());
+            }
+            JsonEvent::StringEnd => match self.chunked.take() {
+                Some(s) => JsonValue::String(s),
+                None => return Err(JError::new(ErrorKind::Syntax, "Unexpected end of string")),
+            },
+            JsonEvent::Number(n) => JsonValue::Number(n),
+            JsonEvent::RawNumber(literal) => value::raw_number(&literal, self.number_mode)?,
+            JsonEvent::Bool(b) => JsonValue::Bool(b),
+            JsonEvent::Null => JsonValue::Null,
+        };
+        self.complete(value)
+    }
+
+    fn complete(&mut self, value: JsonValue) -> Result<(), JError> {
+        match self.stack.last_mut() {
+            Some(Partial::Array(vec)) => vec.push(value),
+            Some(Partial::Object(map, slot)) => match slot.take() {
+                Some(key) => {
+                    map.insert(key, value);
+                }
+                None => {
+                    return Err(JError::new(
+                        ErrorKind::Syntax,
+                        "Expected a key before the value",
+                    ))
+                }
+            },
+            None if self.root.is_none() => self.root = Some(value),
+            None => {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    "Unexpected value after the document",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    // finish Return the built value once every container has been closed
+    pub fn finish(self) -> Result<JsonValue, JError> {
+        match (self.stack.is_empty(), self.root) {
+            (true, Some(value)) => Ok(value),
+            _ => Err(JError::new(ErrorKind::Eof, "Incomplete event sequence")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, vec, vec::Vec};
+
+    use crate::{parse, JsonValue};
+
+    use super::JsonEvent;
+
+    #[test]
+    fn it_value_events() {
+        let value = parse(r#"{"a": [1, true], "b": {}}"#).unwrap();
+
+        assert_eq!(
+            value.events().collect::<Vec<_>>(),
+            vec![
+                JsonEvent::StartObject(Some(2)),
+                JsonEvent::Key(Cow::Borrowed("a")),
+                JsonEvent::StartArray(Some(2)),
+                JsonEvent::Number(1.0),
+                JsonEvent::Bool(true),
+                JsonEvent::EndArray,
+                JsonEvent::Key(Cow::Borrowed("b")),
+                JsonEvent::StartObject(Some(0)),
+                JsonEvent::EndObject,
+                JsonEvent::EndObject,
+            ]
+        );
+        assert_eq!(JsonValue::from_events(value.events()).unwrap(), value);
+    }
+
+    #[test]
+    fn it_from_events_rejects_malformed_sequences() {
+        let unclosed = vec![JsonEvent::StartArray(None), JsonEvent::Null];
+        assert!(JsonValue::from_events(unclosed).is_err());
+
+        let keyless = vec![JsonEvent::StartObject(None), JsonEvent::Null];
+        assert!(JsonValue::from_events(keyless).is_err());
+
+        let two_roots = vec![JsonEvent::Null, JsonEvent::Null];
+        assert!(JsonValue::from_events(two_roots).is_err());
+
+        let interrupted = vec![
+            JsonEvent::StartArray(None),
+            JsonEvent::StringChunk(Cow::Borrowed("ab")),
+            JsonEvent::EndArray,
+        ];
+        assert!(JsonValue::from_events(interrupted).is_err());
+        assert!(JsonValue::from_events(vec![JsonEvent::StringEnd]).is_err());
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn it_big_integer_events() {
+        let value = parse("[123456789012345678901234567890]").unwrap();
+
+        assert_eq!(
+            value.events().nth(1),
+            Some(JsonEvent::RawNumber(Cow::Borrowed(
+                "123456789012345678901234567890"
+            )))
+        );
+        assert_eq!(JsonValue::from_events(value.events()).unwrap(), value);
+        assert!(JsonValue::from_events(vec![JsonEvent::RawNumber(Cow::Borrowed("1x"))]).is_err());
+    }
+
+    #[test]
+    fn it_chunked_string_events() {
+        let events = vec![
+            JsonEvent::StartArray(None),
+            JsonEvent::StringChunk(Cow::Borrowed("ab")),
+            JsonEvent::StringChunk(Cow::Borrowed("c\n")),
+            JsonEvent::StringEnd,
+            JsonEvent::EndArray,
+        ];
+        assert_eq!(
+            JsonValue::from_events(events).unwrap(),
+            parse(r#"["abc\n"]"#).unwrap()
+        );
+    }
+}
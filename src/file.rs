@@ -0,0 +1,164 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    error::{ErrorKind, JError},
+    ser::{SerializeOptions, Serializer},
+    JsonValue, Utf8Policy,
+};
+
+// Tells apart the temporary files of concurrent writes from one process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl JsonValue {
+    /// Read and parse the file at `path`.
+    ///
+    /// UTF-8 (with or without BOM) and UTF-16 are accepted, as in [`crate::parse_bytes`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<JsonValue, JError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| io_error("read", path, e))?;
+        crate::parse_bytes(&bytes, Utf8Policy::Error)
+    }
+
+    // to_file Atomically replace the file at `path` with the value as compact JSON
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), JError> {
+        self.to_file_with(path, SerializeOptions::compact())
+    }
+
+    // to_file_pretty Atomically replace the file at `path` with the value indented by two spaces
+    pub fn to_file_pretty(&self, path: impl AsRef<Path>) -> Result<(), JError> {
+        self.to_file_with(path, SerializeOptions::pretty())
+    }
+
+    /// Atomically replace the file at `path` with the serialized value.
+    ///
+    /// The JSON is written to a temporary file next to the target, flushed to disk and
+    /// renamed over it, so readers (and a crash halfway through) see either the old
+    /// contents or the new ones, never a truncated file. An existing file keeps its
+    /// permissions.
+    pub fn to_file_with(
+        &self,
+        path: impl AsRef<Path>,
+        options: SerializeOptions,
+    ) -> Result<(), JError> {
+        let path = path.as_ref();
+        let (temp, file) = create_temp(path)?;
+        let written = write_synced(self, file, options, path)
+            .and_then(|()| fs::rename(&temp, path).map_err(|e| io_error("replace", path, e)));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp);
+            return written;
+        }
+        sync_parent(path);
+        Ok(())
+    }
+}
+
+// create_temp Create a fresh file in the target's directory, with the target's permissions
+fn create_temp(path: &Path) -> Result<(PathBuf, File), JError> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io_error("write", path, io::ErrorKind::InvalidInput.into()))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let permissions = fs::metadata(path).ok().map(|meta| meta.permissions());
+    loop {
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp = dir.join(temp_name);
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => {
+                if let Some(permissions) = &permissions {
+                    let _ = file.set_permissions(permissions.clone());
+                }
+                return Ok((temp, file));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(io_error("write", path, e)),
+        }
+    }
+}
+
+fn write_synced(
+    value: &JsonValue,
+    file: File,
+    options: SerializeOptions,
+    path: &Path,
+) -> Result<(), JError> {
+    let mut ser = Serializer::with_options(file, options);
+    ser.write_value(value)?;
+    ser.into_inner()?
+        .sync_all()
+        .map_err(|e| io_error("write", path, e))
+}
+
+// sync_parent Persist the rename itself; best effort, as not every platform can open a directory
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+fn io_error(action: &str, path: &Path, e: io::Error) -> JError {
+    JError::new(
+        ErrorKind::Io,
+        format!("Failed to {action} {}: {e}", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, ErrorKind, JsonValue};
+
+    #[test]
+    fn it_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("jnom-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let value = parse(r#"{"name": "jnom", "tags": ["a", "b"], "n": 1.5}"#).unwrap();
+
+        value.to_file(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            crate::ser::to_string(&value)
+        );
+        assert_eq!(JsonValue::from_file(&path).unwrap(), value);
+
+        value.to_file_pretty(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            crate::ser::to_string_pretty(&value)
+        );
+
+        // a failed write leaves the old contents and no temporary file behind
+        let nan = JsonValue::Array(vec![JsonValue::Number(f64::NAN)]);
+        let options =
+            crate::ser::SerializeOptions::compact().non_finite(crate::ser::NonFinite::Error);
+        assert!(nan.to_file_with(&path, options).is_err());
+        assert_eq!(JsonValue::from_file(&path).unwrap(), value);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let missing = JsonValue::from_file(dir.join("missing.json")).unwrap_err();
+        assert_eq!(missing.kind(), ErrorKind::Io);
+        assert!(missing.message().contains("missing.json"));
+        assert!(value.to_file(dir.join("no/such/dir.json")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,622 @@
+//! Random documents conforming to a [`Schema`], for load testing and fuzzing downstream
+//! services.
+//!
+//! Generation is deterministic for a given seed. Every document is checked against the
+//! schema before it is returned, so a schema that cannot be satisfied (or one the
+//! generator cannot find a value for) is reported as an error instead.
+
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+use crate::{
+    error::{ErrorKind, JError},
+    pointer::escape_token,
+    schema::{node_is_valid, Node, Rules, Schema, Type},
+    JsonValue, Map,
+};
+
+// Attempts at a value for one location before giving up on it
+const ATTEMPTS: usize = 8;
+
+// Largest integer a double holds exactly, the boundary many JSON consumers care about
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+const PLAIN_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+// Characters that tend to trip up escaping, length counting and rendering
+const EDGE_CHARS: &[char] = &[
+    'é', 'ß', 'Ω', '中', '文', '😀', '🦀', '\u{301}', '\u{200b}', '\u{202e}', '\u{0}', '\t', '\n',
+    '"', '\\', '/', ' ',
+];
+
+/// Size and shape knobs for a [`Generator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerateOptions {
+    max_depth: usize,
+    max_items: usize,
+    max_string_len: usize,
+    edge_bias: f64,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            max_depth: 4,
+            max_items: 4,
+            max_string_len: 16,
+            edge_bias: 0.2,
+        }
+    }
+}
+
+impl GenerateOptions {
+    // max_depth Nesting beyond which containers get only what the schema requires
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    // max_items Array elements and optional extra members added beyond the required ones
+    pub fn max_items(mut self, items: usize) -> Self {
+        self.max_items = items;
+        self
+    }
+
+    // max_string_len Longest string generated where the schema sets no `maxLength`
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    /// Probability, from 0 to 1, of picking an edge case over a typical value.
+    ///
+    /// Edge cases are boundary numbers (the schema's bounds, zero, ±2^53 - 1), empty or
+    /// minimal strings, arrays and objects, and strings of non-ASCII, zero-width,
+    /// control and escaped characters.
+    pub fn edge_bias(mut self, bias: f64) -> Self {
+        self.edge_bias = bias.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Produces random documents conforming to a schema, see the [module docs](self).
+///
+/// The generator is an endless iterator of documents.
+#[derive(Debug, Clone)]
+pub struct Generator<'s> {
+    schema: &'s Schema,
+    options: GenerateOptions,
+    rng: Rng,
+}
+
+impl<'s> Generator<'s> {
+    pub fn new(schema: &'s Schema, seed: u64) -> Self {
+        Generator {
+            schema,
+            options: GenerateOptions::default(),
+            rng: Rng(seed),
+        }
+    }
+
+    pub fn options(mut self, options: GenerateOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    // generate The next document
+    pub fn generate(&mut self) -> Result<JsonValue, JError> {
+        self.node(&self.schema.root, 0, &mut String::new())
+    }
+
+    fn node(&mut self, node: &Node, depth: usize, path: &mut String) -> Result<JsonValue, JError> {
+        // `true` allows anything, as a schema without keywords does
+        let any = Rules::default();
+        let rules = match node {
+            Node::Bool(true) => &any,
+            Node::Bool(false) => return Err(unsatisfiable(path, "no value is allowed")),
+            Node::Rules(rules) => rules,
+        };
+        if let Some(constant) = &rules.constant {
+            return match node_is_valid(node, constant) {
+                true => Ok(constant.clone()),
+                false => Err(unsatisfiable(path, "`const` conflicts with other keywords")),
+            };
+        }
+        if let Some(values) = &rules.enumeration {
+            let allowed = values
+                .iter()
+                .filter(|v| node_is_valid(node, v))
+                .collect::<Vec<_>>();
+            if allowed.is_empty() {
+                return Err(unsatisfiable(
+                    path,
+                    "no `enum` value fits the other keywords",
+                ));
+            }
+            return Ok(allowed[self.rng.below(allowed.len())].clone());
+        }
+
+        let mut types = candidate_types(rules);
+        if depth >= self.options.max_depth && types.iter().any(|ty| !is_container(*ty)) {
+            types.retain(|ty| !is_container(*ty));
+        }
+        let mut error = None;
+        for _ in 0..ATTEMPTS {
+            let ty = types[self.rng.below(types.len())];
+            match self.typed(rules, ty, depth, path) {
+                Ok(value) if node_is_valid(node, &value) => return Ok(value),
+                Ok(_) => {}
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap_or_else(|| unsatisfiable(path, "no conforming value was found")))
+    }
+
+    fn typed(
+        &mut self,
+        rules: &Rules,
+        ty: Type,
+        depth: usize,
+        path: &mut String,
+    ) -> Result<JsonValue, JError> {
+        Ok(match ty {
+            Type::Null => JsonValue::Null,
+            Type::Boolean => JsonValue::Bool(self.rng.chance(0.5)),
+            Type::Integer => JsonValue::Number(self.integer(rules, path)?),
+            Type::Number => JsonValue::Number(self.number(rules, path)?),
+            Type::String => JsonValue::String(self.string(rules, path)?),
+            Type::Array => self.array(rules, depth, path)?,
+            Type::Object => self.object(rules, depth, path)?,
+        })
+    }
+
+    fn integer(&mut self, rules: &Rules, path: &str) -> Result<f64, JError> {
+        let (lo, lo_exclusive) = lower_bound(rules);
+        let (hi, hi_exclusive) = upper_bound(rules);
+        let lo = match lo_exclusive {
+            true => lo.floor() + 1.0,
+            false => lo.ceil(),
+        };
+        let hi = match hi_exclusive {
+            true => hi.ceil() - 1.0,
+            false => hi.floor(),
+        };
+        if lo > hi {
+            return Err(unsatisfiable(path, "no integer lies within the bounds"));
+        }
+        if self.edge() {
+            let edges = [lo, hi, 0.0, 1.0, -1.0, MAX_SAFE_INTEGER, -MAX_SAFE_INTEGER];
+            if let Some(n) = self.pick_within(&edges, lo, hi) {
+                return Ok(n);
+            }
+        }
+        let (lo, hi) = window(lo, hi);
+        Ok((lo + (self.rng.unit() * (hi - lo + 1.0)).floor()).min(hi))
+    }
+
+    fn number(&mut self, rules: &Rules, path: &str) -> Result<f64, JError> {
+        let (lo, lo_exclusive) = lower_bound(rules);
+        let (hi, hi_exclusive) = upper_bound(rules);
+        if lo > hi || (lo == hi && (lo_exclusive || hi_exclusive)) {
+            return Err(unsatisfiable(path, "no number lies within the bounds"));
+        }
+        let within = |n: f64| {
+            (n > lo || (n == lo && !lo_exclusive)) && (n < hi || (n == hi && !hi_exclusive))
+        };
+        if self.edge() {
+            let edges = [lo, hi, 0.0, f64::EPSILON, -1.5, 1e300, -1e300]
+                .into_iter()
+                .filter(|n| n.is_finite() && within(*n))
+                .collect::<Vec<_>>();
+            if !edges.is_empty() {
+                return Ok(edges[self.rng.below(edges.len())]);
+            }
+        }
+        let (lo, hi) = window(lo, hi);
+        let n = lo + self.rng.unit() * (hi - lo);
+        // Round to a readable value where that keeps it in bounds
+        let rounded = (n * 100.0).round() / 100.0;
+        Ok(match (within(rounded), within(n)) {
+            (true, _) => rounded,
+            (false, true) => n,
+            (false, false) => lo + (hi - lo) / 2.0,
+        })
+    }
+
+    fn string(&mut self, rules: &Rules, path: &str) -> Result<String, JError> {
+        let min = rules.min_length.unwrap_or(0);
+        let max = rules
+            .max_length
+            .unwrap_or_else(|| min.max(self.options.max_string_len));
+        if min > max {
+            return Err(unsatisfiable(
+                path,
+                "`minLength` is greater than `maxLength`",
+            ));
+        }
+        if let Some(pattern) = &rules.pattern {
+            let hir = regex_syntax::parse(pattern.as_str()).map_err(|e| {
+                JError::new(
+                    ErrorKind::Other,
+                    format!("Invalid pattern at {path:?}: {e}"),
+                )
+            })?;
+            let mut out = String::new();
+            self.matching(&hir, &mut out);
+            return Ok(out);
+        }
+
+        let len = match self.edge() {
+            true if self.rng.chance(0.5) => min,
+            _ => {
+                min + self
+                    .rng
+                    .below(max.min(min + self.options.max_string_len) - min + 1)
+            }
+        };
+        let unusual = self.edge();
+        Ok((0..len)
+            .map(|_| match unusual {
+                true => EDGE_CHARS[self.rng.below(EDGE_CHARS.len())],
+                false => PLAIN_CHARS[self.rng.below(PLAIN_CHARS.len())] as char,
+            })
+            .collect())
+    }
+
+    // matching Append a random string matching the regular expression
+    fn matching(&mut self, hir: &Hir, out: &mut String) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(literal) => out.push_str(&String::from_utf8_lossy(&literal.0)),
+            HirKind::Class(Class::Unicode(class)) => {
+                if let Some(range) = pick(&mut self.rng, class.ranges()) {
+                    let (start, end) = (u32::from(range.start()), u32::from(range.end()));
+                    let code = start + self.rng.below((end - start) as usize + 1) as u32;
+                    out.push(char::from_u32(code).unwrap_or(range.start()));
+                }
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                if let Some(range) = pick(&mut self.rng, class.ranges()) {
+                    let span = (range.end() - range.start()) as usize + 1;
+                    out.push(char::from(range.start() + self.rng.below(span) as u8));
+                }
+            }
+            HirKind::Repetition(repetition) => {
+                let min = repetition.min as usize;
+                let extra = match repetition.max {
+                    Some(max) => (max as usize - min).min(self.options.max_items),
+                    None => self.options.max_items,
+                };
+                for _ in 0..min + self.rng.below(extra + 1) {
+                    self.matching(&repetition.sub, out);
+                }
+            }
+            HirKind::Capture(capture) => self.matching(&capture.sub, out),
+            HirKind::Concat(parts) => {
+                for part in parts {
+                    self.matching(part, out);
+                }
+            }
+            HirKind::Alternation(branches) => {
+                if let Some(branch) = pick(&mut self.rng, branches) {
+                    self.matching(branch, out);
+                }
+            }
+        }
+    }
+
+    fn array(
+        &mut self,
+        rules: &Rules,
+        depth: usize,
+        path: &mut String,
+    ) -> Result<JsonValue, JError> {
+        let min = rules.min_items.unwrap_or(0);
+        let max = rules.max_items.unwrap_or(usize::MAX);
+        if min > max {
+            return Err(unsatisfiable(path, "`minItems` is greater than `maxItems`"));
+        }
+        let extra = match depth >= self.options.max_depth || self.edge() {
+            true => 0,
+            false => self.rng.below((max - min).min(self.options.max_items) + 1),
+        };
+        let items = rules.items.as_ref().unwrap_or(&Node::Bool(true));
+        let mut arr = Vec::with_capacity(min + extra);
+        for idx in 0..min + extra {
+            let len = path.len();
+            path.push_str(&format!("/{idx}"));
+            arr.push(self.node(items, depth + 1, path)?);
+            path.truncate(len);
+        }
+        Ok(JsonValue::Array(arr))
+    }
+
+    fn object(
+        &mut self,
+        rules: &Rules,
+        depth: usize,
+        path: &mut String,
+    ) -> Result<JsonValue, JError> {
+        let minimal = depth >= self.options.max_depth || self.edge();
+        let additional = rules
+            .additional_properties
+            .as_ref()
+            .filter(|node| !matches!(node, Node::Bool(false)));
+        let mut names = rules.required.clone();
+        if !minimal {
+            for name in rules.properties.keys() {
+                if !names.contains(name) && self.rng.chance(0.6) {
+                    names.push(name.clone());
+                }
+            }
+            if additional.is_some() {
+                for _ in 0..self.rng.below(self.options.max_items.min(2) + 1) {
+                    let len = 1 + self.rng.below(8);
+                    let name = (0..len)
+                        .map(|_| PLAIN_CHARS[self.rng.below(26)] as char)
+                        .collect::<String>();
+                    if !names.contains(&name) && !rules.properties.contains_key(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+
+        let mut obj = Map::default();
+        for name in names {
+            let sub = rules
+                .properties
+                .get(&name)
+                .or(additional)
+                .unwrap_or(&Node::Bool(true));
+            let len = path.len();
+            path.push('/');
+            path.push_str(&escape_token(&name));
+            let value = self.node(sub, depth + 1, path)?;
+            path.truncate(len);
+            obj.insert(name, value);
+        }
+        Ok(JsonValue::Object(obj))
+    }
+
+    fn edge(&mut self) -> bool {
+        self.rng.chance(self.options.edge_bias)
+    }
+
+    fn pick_within(&mut self, candidates: &[f64], lo: f64, hi: f64) -> Option<f64> {
+        let within = candidates
+            .iter()
+            .filter(|n| n.is_finite() && **n >= lo && **n <= hi)
+            .collect::<Vec<_>>();
+        pick(&mut self.rng, &within).map(|n| **n)
+    }
+}
+
+impl Iterator for Generator<'_> {
+    type Item = Result<JsonValue, JError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generate())
+    }
+}
+
+impl Schema {
+    // generate One random document conforming to the schema, see [`Generator`]
+    pub fn generate(&self, seed: u64) -> Result<JsonValue, JError> {
+        Generator::new(self, seed).generate()
+    }
+}
+
+// candidate_types The types a value may take, guessed from the keywords when `type` is absent
+fn candidate_types(rules: &Rules) -> Vec<Type> {
+    if let Some(types) = &rules.types {
+        return types.clone();
+    }
+    let mut types = Vec::new();
+    if !rules.properties.is_empty()
+        || !rules.required.is_empty()
+        || rules.additional_properties.is_some()
+    {
+        types.push(Type::Object);
+    }
+    if rules.items.is_some() || rules.min_items.is_some() || rules.max_items.is_some() {
+        types.push(Type::Array);
+    }
+    if rules.min_length.is_some() || rules.max_length.is_some() || rules.pattern.is_some() {
+        types.push(Type::String);
+    }
+    if rules.minimum.is_some()
+        || rules.maximum.is_some()
+        || rules.exclusive_minimum.is_some()
+        || rules.exclusive_maximum.is_some()
+    {
+        types.push(Type::Number);
+    }
+    if types.is_empty() {
+        types = vec![
+            Type::Null,
+            Type::Boolean,
+            Type::Integer,
+            Type::Number,
+            Type::String,
+            Type::Array,
+            Type::Object,
+        ];
+    }
+    types
+}
+
+fn is_container(ty: Type) -> bool {
+    matches!(ty, Type::Array | Type::Object)
+}
+
+// lower_bound The tightest lower bound and whether it is exclusive
+fn lower_bound(rules: &Rules) -> (f64, bool) {
+    let inclusive = rules.minimum.unwrap_or(f64::NEG_INFINITY);
+    match rules.exclusive_minimum {
+        Some(exclusive) if exclusive >= inclusive => (exclusive, true),
+        _ => (inclusive, false),
+    }
+}
+
+fn upper_bound(rules: &Rules) -> (f64, bool) {
+    let inclusive = rules.maximum.unwrap_or(f64::INFINITY);
+    match rules.exclusive_maximum {
+        Some(exclusive) if exclusive <= inclusive => (exclusive, true),
+        _ => (inclusive, false),
+    }
+}
+
+// window The range typical values are drawn from, within the bounds but of modest size
+fn window(lo: f64, hi: f64) -> (f64, f64) {
+    match (lo.is_finite(), hi.is_finite()) {
+        (true, true) => (lo, hi),
+        (true, false) => (lo, lo + 1000.0),
+        (false, true) => (hi - 1000.0, hi),
+        (false, false) => (-1000.0, 1000.0),
+    }
+}
+
+fn pick<'a, T>(rng: &mut Rng, items: &'a [T]) -> Option<&'a T> {
+    match items.is_empty() {
+        true => None,
+        false => Some(&items[rng.below(items.len())]),
+    }
+}
+
+fn unsatisfiable(path: &str, reason: &str) -> JError {
+    JError::new(
+        ErrorKind::Other,
+        format!("Schema at {path:?} cannot be satisfied: {reason}"),
+    )
+}
+
+// SplitMix64: small, fast and plenty random for test data
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // below A number in `0..n`, or 0 when `n` is 0
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+
+    // unit A number in `[0, 1)`
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        self.unit() < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, schema::Schema};
+
+    use super::{GenerateOptions, Generator};
+
+    fn schema(source: &str) -> Schema {
+        Schema::compile(&parse(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_generate_conforming() {
+        let schema = schema(
+            r#"{
+                "type": "object",
+                "required": ["id", "name", "tags"],
+                "properties": {
+                    "id": {"type": "integer", "minimum": 1, "exclusiveMaximum": 100},
+                    "name": {"type": "string", "minLength": 2, "maxLength": 10},
+                    "price": {"type": "number", "exclusiveMinimum": 0, "maximum": 9.99},
+                    "sku": {"type": "string", "pattern": "^[A-Z]{3}-[0-9]{4}$"},
+                    "tags": {"type": "array", "items": {"enum": ["a", "b", 3]}, "maxItems": 3},
+                    "state": {"const": "open"},
+                    "meta": {"additionalProperties": {"type": ["boolean", "null"]}},
+                    "any": true
+                },
+                "additionalProperties": false
+            }"#,
+        );
+        let options = GenerateOptions::default().edge_bias(0.5);
+        for doc in Generator::new(&schema, 7).options(options).take(200) {
+            let doc = doc.unwrap();
+            assert_eq!(schema.validate(&doc), Ok(()), "{doc}");
+        }
+
+        // the same seed gives the same documents
+        let first = Generator::new(&schema, 42)
+            .take(5)
+            .collect::<Result<Vec<_>, _>>();
+        let again = Generator::new(&schema, 42)
+            .take(5)
+            .collect::<Result<Vec<_>, _>>();
+        assert_eq!(first.unwrap(), again.unwrap());
+        assert_ne!(schema.generate(1).unwrap(), schema.generate(2).unwrap());
+    }
+
+    #[test]
+    fn it_generate_from_inferred_schema() {
+        let samples = [
+            parse(r#"{"id": 1, "tags": ["x"], "score": 1.5, "owner": {"name": "ann"}}"#).unwrap(),
+            parse(r#"{"id": 2, "tags": [], "score": 2, "note": null}"#).unwrap(),
+        ];
+        let schema = Schema::infer(&samples);
+        assert!(samples.iter().all(|s| schema.is_valid(s)));
+        assert!(!schema.is_valid(&parse(r#"{"id": 1.5, "tags": [], "score": 1}"#).unwrap()));
+        assert!(!schema.is_valid(&parse(r#"{"id": 1, "score": 1}"#).unwrap()));
+
+        let options = GenerateOptions::default().max_depth(1).max_items(2);
+        for doc in Generator::new(&schema, 3).options(options).take(100) {
+            let doc = doc.unwrap();
+            assert!(schema.is_valid(&doc), "{doc}");
+            assert!(doc.as_object().unwrap().contains_key("tags"));
+        }
+    }
+
+    #[test]
+    fn it_generate_edge_cases() {
+        let schema = schema(r#"{"type": "array", "items": {"type": "integer"}}"#);
+        let options = GenerateOptions::default().edge_bias(1.0);
+        let docs = Generator::new(&schema, 0)
+            .options(options)
+            .take(20)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(docs.iter().all(|doc| doc.as_array().unwrap().is_empty()));
+
+        let strings = self::schema(r#"{"type": "string", "minLength": 3}"#);
+        let unusual = Generator::new(&strings, 5)
+            .options(options)
+            .take(20)
+            .map(|doc| doc.unwrap())
+            .any(|doc| !doc.as_str().unwrap().is_ascii());
+        assert!(unusual);
+    }
+
+    #[test]
+    fn it_generate_unsatisfiable() {
+        for source in [
+            "false",
+            r#"{"type": "integer", "minimum": 1.2, "maximum": 1.8}"#,
+            r#"{"type": "string", "minLength": 3, "maxLength": 2}"#,
+            r#"{"enum": [1, 2], "type": "string"}"#,
+            r#"{"properties": {"a": false}, "required": ["a"]}"#,
+        ] {
+            let err = schema(source).generate(0).unwrap_err();
+            assert!(
+                err.message().contains("cannot be satisfied"),
+                "{source}: {err}"
+            );
+        }
+    }
+}
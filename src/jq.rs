@@ -0,0 +1,656 @@
+//! A core subset of the jq filter language.
+//!
+//! Supported: `.`, `.foo`, `."key"`, `.[0]` (negative indices count from the end),
+//! `.[]`, `|`, `,`, literals, `[...]` array construction, parentheses, `+ - * / %`,
+//! `== != < <= > >=`, `and`/`or`, and the builtins `select(f)`, `map(f)`, `length`,
+//! `keys` and `not`. Values are ordered across types the way jq orders them.
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::cmp::Ordering;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alpha1, alphanumeric1, anychar, char, multispace0, none_of},
+    combinator::{all_consuming, map, opt, recognize, value},
+    multi::{many0, many0_count},
+    number::complete::recognize_float,
+    sequence::{delimited, pair, preceded},
+};
+
+use crate::{
+    error::{ErrorKind, JError},
+    value::unescape,
+    JsonValue, Map,
+};
+
+/// A parsed jq filter, ready to be applied to any number of values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Identity,
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Iterate(Box<Expr>),
+    Literal(JsonValue),
+    Array(Option<Box<Expr>>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Select(Box<Expr>),
+    Map(Box<Expr>),
+    Length,
+    Keys,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Filter, JError> {
+        match all_consuming(ws(pipe))(source) {
+            Ok((_, expr)) => Ok(Filter { expr }),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(JError::new(
+                ErrorKind::Syntax,
+                format!(
+                    "Invalid filter {source:?} at offset {}",
+                    source.len() - e.input.len()
+                ),
+            )),
+            Err(nom::Err::Incomplete(_)) => Err(JError::new(
+                ErrorKind::Eof,
+                format!("Incomplete filter {source:?}"),
+            )),
+        }
+    }
+
+    // eval Apply the filter, collecting every output
+    pub fn eval(&self, input: &JsonValue) -> Result<Vec<JsonValue>, JError> {
+        let mut out = Vec::new();
+        evaluate(&self.expr, input, &mut out)?;
+        Ok(out)
+    }
+}
+
+// eval Parse `filter` and apply it to `input`
+pub fn eval(filter: &str, input: &JsonValue) -> Result<Vec<JsonValue>, JError> {
+    Filter::parse(filter)?.eval(input)
+}
+
+type PResult<'a, O> = nom::IResult<&'a str, O>;
+
+fn ws<'a, O>(
+    inner: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, O> {
+    delimited(multispace0, inner, multispace0)
+}
+
+// binary Parse a left-associative chain of `operand (op operand)*`
+fn binary<'a>(
+    input: &'a str,
+    operand: fn(&'a str) -> PResult<'a, Expr>,
+    op: fn(&'a str) -> PResult<'a, BinOp>,
+) -> PResult<'a, Expr> {
+    let (mut input, mut expr) = operand(input)?;
+    while let Ok((rest, (op, rhs))) = pair(ws(op), operand)(input) {
+        expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        input = rest;
+    }
+    Ok((input, expr))
+}
+
+fn pipe(input: &str) -> PResult<'_, Expr> {
+    let (mut input, mut expr) = comma(input)?;
+    while let Ok((rest, rhs)) = preceded(ws(char('|')), comma)(input) {
+        expr = Expr::Pipe(Box::new(expr), Box::new(rhs));
+        input = rest;
+    }
+    Ok((input, expr))
+}
+
+fn comma(input: &str) -> PResult<'_, Expr> {
+    let (mut input, mut expr) = or(input)?;
+    while let Ok((rest, rhs)) = preceded(ws(char(',')), or)(input) {
+        expr = Expr::Comma(Box::new(expr), Box::new(rhs));
+        input = rest;
+    }
+    Ok((input, expr))
+}
+
+fn or(input: &str) -> PResult<'_, Expr> {
+    binary(input, and, |i| value(BinOp::Or, keyword("or"))(i))
+}
+
+fn and(input: &str) -> PResult<'_, Expr> {
+    binary(input, comparison, |i| value(BinOp::And, keyword("and"))(i))
+}
+
+fn comparison(input: &str) -> PResult<'_, Expr> {
+    binary(input, additive, |i| {
+        alt((
+            value(BinOp::Eq, tag("==")),
+            value(BinOp::Ne, tag("!=")),
+            value(BinOp::Le, tag("<=")),
+            value(BinOp::Ge, tag(">=")),
+            value(BinOp::Lt, tag("<")),
+            value(BinOp::Gt, tag(">")),
+        ))(i)
+    })
+}
+
+fn additive(input: &str) -> PResult<'_, Expr> {
+    binary(input, multiplicative, |i| {
+        alt((value(BinOp::Add, char('+')), value(BinOp::Sub, char('-'))))(i)
+    })
+}
+
+fn multiplicative(input: &str) -> PResult<'_, Expr> {
+    binary(input, postfix, |i| {
+        alt((
+            value(BinOp::Mul, char('*')),
+            value(BinOp::Div, char('/')),
+            value(BinOp::Rem, char('%')),
+        ))(i)
+    })
+}
+
+enum Suffix {
+    Field(String),
+    Index(Expr),
+    Iterate,
+}
+
+fn postfix(input: &str) -> PResult<'_, Expr> {
+    let (mut input, mut expr) = ws(primary)(input)?;
+    while let Ok((rest, suffix)) = suffix(input) {
+        expr = apply(expr, suffix);
+        input = rest;
+    }
+    Ok((input, expr))
+}
+
+fn apply(expr: Expr, suffix: Suffix) -> Expr {
+    match suffix {
+        Suffix::Field(name) => Expr::Field(Box::new(expr), name),
+        Suffix::Index(idx) => Expr::Index(Box::new(expr), Box::new(idx)),
+        Suffix::Iterate => Expr::Iterate(Box::new(expr)),
+    }
+}
+
+fn suffix(input: &str) -> PResult<'_, Suffix> {
+    alt((
+        preceded(char('.'), map(field_name, Suffix::Field)),
+        preceded(opt(char('.')), bracket),
+    ))(input)
+}
+
+fn bracket(input: &str) -> PResult<'_, Suffix> {
+    delimited(
+        char('['),
+        map(opt(ws(pipe)), |idx| {
+            idx.map_or(Suffix::Iterate, Suffix::Index)
+        }),
+        ws(char(']')),
+    )(input)
+}
+
+fn primary(input: &str) -> PResult<'_, Expr> {
+    alt((
+        map(preceded(char('.'), opt(field_name)), |name| match name {
+            Some(name) => Expr::Field(Box::new(Expr::Identity), name),
+            None => Expr::Identity,
+        }),
+        map(string_literal, |s| Expr::Literal(JsonValue::String(s))),
+        map(recognize_float, |n: &str| {
+            Expr::Literal(JsonValue::Number(n.parse().unwrap_or_default()))
+        }),
+        delimited(char('('), ws(pipe), char(')')),
+        map(delimited(char('['), opt(ws(pipe)), char(']')), |e| {
+            Expr::Array(e.map(Box::new))
+        }),
+        call,
+    ))(input)
+}
+
+fn call(input: &str) -> PResult<'_, Expr> {
+    let (rest, name) = identifier(input)?;
+    let argument = |rest| delimited(ws(char('(')), pipe, char(')'))(rest);
+    match name {
+        "true" => Ok((rest, Expr::Literal(JsonValue::Bool(true)))),
+        "false" => Ok((rest, Expr::Literal(JsonValue::Bool(false)))),
+        "null" => Ok((rest, Expr::Literal(JsonValue::Null))),
+        "length" => Ok((rest, Expr::Length)),
+        "keys" => Ok((rest, Expr::Keys)),
+        "not" => Ok((rest, Expr::Not)),
+        "select" => map(argument, |f| Expr::Select(Box::new(f)))(rest),
+        "map" => map(argument, |f| Expr::Map(Box::new(f)))(rest),
+        _ => Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        ))),
+    }
+}
+
+fn identifier(input: &str) -> PResult<'_, &str> {
+    recognize(pair(
+        alt((alpha1, tag("_"))),
+        many0_count(alt((alphanumeric1, tag("_")))),
+    ))(input)
+}
+
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> PResult<'a, &'a str> {
+    move |input| {
+        let (rest, name) = identifier(input)?;
+        match name == word {
+            true => Ok((rest, name)),
+            false => Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Tag,
+            ))),
+        }
+    }
+}
+
+fn field_name(input: &str) -> PResult<'_, String> {
+    alt((map(identifier, ToString::to_string), string_literal))(input)
+}
+
+fn string_literal(input: &str) -> PResult<'_, String> {
+    let (rest, raw) = delimited(
+        char('"'),
+        recognize(many0(alt((
+            recognize(none_of("\\\"")),
+            recognize(pair(char('\\'), anychar)),
+        )))),
+        char('"'),
+    )(input)?;
+    match unescape(raw) {
+        Ok(s) => Ok((rest, s)),
+        Err(_) => Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Escaped,
+        ))),
+    }
+}
+
+fn evaluate(expr: &Expr, input: &JsonValue, out: &mut Vec<JsonValue>) -> Result<(), JError> {
+    match expr {
+        Expr::Identity => out.push(input.clone()),
+        Expr::Literal(value) => out.push(value.clone()),
+        Expr::Field(target, name) => {
+            for target in outputs(target, input)? {
+                out.push(match target {
+                    JsonValue::Object(obj) => obj.get(name).cloned().unwrap_or(JsonValue::Null),
+                    JsonValue::Null => JsonValue::Null,
+                    other => {
+                        return Err(JError::new(
+                            ErrorKind::Type,
+                            format!("Cannot index {} with {name:?}", other.type_name()),
+                        ))
+                    }
+                });
+            }
+        }
+        Expr::Index(target, idx) => {
+            for idx in outputs(idx, input)? {
+                for target in outputs(target, input)? {
+                    out.push(index(&target, &idx)?);
+                }
+            }
+        }
+        Expr::Iterate(target) => {
+            for target in outputs(target, input)? {
+                match target {
+                    JsonValue::Array(arr) => out.extend(arr),
+                    JsonValue::Object(obj) => out.extend(obj.into_values()),
+                    other => {
+                        return Err(JError::new(
+                            ErrorKind::Type,
+                            format!("Cannot iterate over {}", other.type_name()),
+                        ))
+                    }
+                }
+            }
+        }
+        Expr::Array(items) => {
+            let items = match items {
+                Some(items) => outputs(items, input)?,
+                None => Vec::new(),
+            };
+            out.push(JsonValue::Array(items));
+        }
+        Expr::Pipe(lhs, rhs) => {
+            for value in outputs(lhs, input)? {
+                evaluate(rhs, &value, out)?;
+            }
+        }
+        Expr::Comma(lhs, rhs) => {
+            evaluate(lhs, input, out)?;
+            evaluate(rhs, input, out)?;
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            for r in outputs(rhs, input)? {
+                for l in outputs(lhs, input)? {
+                    out.push(binary_op(*op, l, &r)?);
+                }
+            }
+        }
+        Expr::Select(cond) => {
+            if outputs(cond, input)?.iter().any(truthy) {
+                out.push(input.clone());
+            }
+        }
+        Expr::Map(f) => match input {
+            JsonValue::Array(arr) => {
+                let mut mapped = Vec::new();
+                for item in arr {
+                    evaluate(f, item, &mut mapped)?;
+                }
+                out.push(JsonValue::Array(mapped));
+            }
+            other => {
+                return Err(JError::new(
+                    ErrorKind::Type,
+                    format!("Cannot map over {}", other.type_name()),
+                ))
+            }
+        },
+        Expr::Length => out.push(JsonValue::Number(match input {
+            JsonValue::Null => 0.0,
+            JsonValue::Bool(_) => {
+                return Err(JError::new(ErrorKind::Type, "boolean has no length"))
+            }
+            JsonValue::Number(n) if *n < 0.0 => -n,
+            JsonValue::Number(n) => *n,
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(_) => input.as_f64().map_or(f64::NAN, f64::abs),
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(_) => input.as_f64().map_or(f64::NAN, f64::abs),
+            JsonValue::String(s) | JsonValue::Custom(_, s) => s.chars().count() as f64,
+            JsonValue::Array(arr) => arr.len() as f64,
+            JsonValue::Object(obj) => obj.len() as f64,
+        })),
+        Expr::Keys => out.push(match input {
+            JsonValue::Object(obj) => {
+                let mut keys = obj.keys().cloned().collect::<Vec<_>>();
+                keys.sort();
+                JsonValue::Array(keys.into_iter().map(JsonValue::String).collect())
+            }
+            JsonValue::Array(arr) => JsonValue::Array(
+                (0..arr.len())
+                    .map(|i| JsonValue::Number(i as f64))
+                    .collect(),
+            ),
+            other => {
+                return Err(JError::new(
+                    ErrorKind::Type,
+                    format!("{} has no keys", other.type_name()),
+                ))
+            }
+        }),
+        Expr::Not => out.push(JsonValue::Bool(!truthy(input))),
+    }
+    Ok(())
+}
+
+fn outputs(expr: &Expr, input: &JsonValue) -> Result<Vec<JsonValue>, JError> {
+    let mut out = Vec::new();
+    evaluate(expr, input, &mut out)?;
+    Ok(out)
+}
+
+fn index(target: &JsonValue, idx: &JsonValue) -> Result<JsonValue, JError> {
+    match (target, idx) {
+        (JsonValue::Null, _) => Ok(JsonValue::Null),
+        (JsonValue::Object(obj), JsonValue::String(key)) => {
+            Ok(obj.get(key).cloned().unwrap_or(JsonValue::Null))
+        }
+        (JsonValue::Array(arr), JsonValue::Number(n)) => {
+            let n = *n as i64;
+            let idx = if n < 0 { arr.len() as i64 + n } else { n };
+            Ok(usize::try_from(idx)
+                .ok()
+                .and_then(|idx| arr.get(idx))
+                .cloned()
+                .unwrap_or(JsonValue::Null))
+        }
+        _ => Err(JError::new(
+            ErrorKind::Type,
+            format!(
+                "Cannot index {} with {}",
+                target.type_name(),
+                idx.type_name()
+            ),
+        )),
+    }
+}
+
+fn truthy(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Null | JsonValue::Bool(false))
+}
+
+fn binary_op(op: BinOp, l: JsonValue, r: &JsonValue) -> Result<JsonValue, JError> {
+    let ordering = || order(&l, r);
+    Ok(match op {
+        BinOp::Eq => JsonValue::Bool(ordering() == Ordering::Equal),
+        BinOp::Ne => JsonValue::Bool(ordering() != Ordering::Equal),
+        BinOp::Lt => JsonValue::Bool(ordering() == Ordering::Less),
+        BinOp::Le => JsonValue::Bool(ordering() != Ordering::Greater),
+        BinOp::Gt => JsonValue::Bool(ordering() == Ordering::Greater),
+        BinOp::Ge => JsonValue::Bool(ordering() != Ordering::Less),
+        BinOp::And => JsonValue::Bool(truthy(&l) && truthy(r)),
+        BinOp::Or => JsonValue::Bool(truthy(&l) || truthy(r)),
+        BinOp::Add => match (l, r) {
+            (JsonValue::Null, r) => r.clone(),
+            (l, JsonValue::Null) => l,
+            (JsonValue::Number(a), JsonValue::Number(b)) => JsonValue::Number(a + b),
+            (JsonValue::String(a), JsonValue::String(b)) => JsonValue::String(a + b),
+            (JsonValue::Array(mut a), JsonValue::Array(b)) => {
+                a.extend(b.iter().cloned());
+                JsonValue::Array(a)
+            }
+            (JsonValue::Object(mut a), JsonValue::Object(b)) => {
+                a.extend(b.iter().map(|(k, v)| (k.clone(), v.clone())));
+                JsonValue::Object(a)
+            }
+            (l, r) => return Err(cannot(&l, r, "added")),
+        },
+        BinOp::Sub => match (l, r) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => JsonValue::Number(a - b),
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                JsonValue::Array(a.into_iter().filter(|item| !b.contains(item)).collect())
+            }
+            (l, r) => return Err(cannot(&l, r, "subtracted")),
+        },
+        BinOp::Mul => match (l, r) {
+            (JsonValue::Number(a), JsonValue::Number(b)) => JsonValue::Number(a * b),
+            (l, r) => return Err(cannot(&l, r, "multiplied")),
+        },
+        BinOp::Div => match (l, r) {
+            (JsonValue::Number(_), JsonValue::Number(b)) if *b == 0.0 => {
+                return Err(JError::new(ErrorKind::Other, "Division by zero"))
+            }
+            (JsonValue::Number(a), JsonValue::Number(b)) => JsonValue::Number(a / b),
+            (l, r) => return Err(cannot(&l, r, "divided")),
+        },
+        BinOp::Rem => match (l, r) {
+            (JsonValue::Number(_), JsonValue::Number(b)) if *b as i64 == 0 => {
+                return Err(JError::new(ErrorKind::Other, "Division by zero"))
+            }
+            (JsonValue::Number(a), JsonValue::Number(b)) => {
+                JsonValue::Number((a as i64 % *b as i64) as f64)
+            }
+            (l, r) => return Err(cannot(&l, r, "divided")),
+        },
+    })
+}
+
+fn cannot(l: &JsonValue, r: &JsonValue, verb: &str) -> JError {
+    JError::new(
+        ErrorKind::Type,
+        format!(
+            "{} ({l}) and {} ({r}) cannot be {verb}",
+            l.type_name(),
+            r.type_name()
+        ),
+    )
+}
+
+// order Total order over values: null < false < true < numbers < strings < arrays < objects
+fn order(a: &JsonValue, b: &JsonValue) -> Ordering {
+    fn rank(value: &JsonValue) -> u8 {
+        match value {
+            JsonValue::Null => 0,
+            JsonValue::Bool(false) => 1,
+            JsonValue::Bool(true) => 2,
+            JsonValue::Number(_) => 3,
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(_) => 3,
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(_) => 3,
+            JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_) => 6,
+            JsonValue::Custom(..) => 7,
+        }
+    }
+
+    match (a, b) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Less),
+        (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+        (JsonValue::Custom(ta, a), JsonValue::Custom(tb, b)) => (ta, a).cmp(&(tb, b)),
+        (JsonValue::Array(a), JsonValue::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| order(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let (ka, kb) = (sorted_keys(a), sorted_keys(b));
+            ka.cmp(&kb).then_with(|| {
+                ka.iter()
+                    .map(|k| order(&a[*k], &b[*k]))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn sorted_keys(obj: &Map<String, JsonValue>) -> Vec<&str> {
+    let mut keys = obj.keys().map(String::as_str).collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use crate::{parse, JsonValue};
+
+    use super::{eval, Filter};
+
+    fn run(filter: &str, input: &JsonValue) -> Vec<JsonValue> {
+        eval(filter, input).unwrap()
+    }
+
+    fn json(source: &str) -> JsonValue {
+        parse(source).unwrap()
+    }
+
+    #[test]
+    fn it_jq_paths() {
+        let doc = json(
+            r#"{"user": {"name": "John", "tags": ["a", "b"]}, "items": [{"id": 1}, {"id": 2}], "a b": 3}"#,
+        );
+
+        assert_eq!(run(".", &doc), vec![doc.clone()]);
+        assert_eq!(run(".user.name", &doc), vec![json(r#""John""#)]);
+        assert_eq!(run(".user.tags[1]", &doc), vec![json(r#""b""#)]);
+        assert_eq!(run(".user.tags.[-1]", &doc), vec![json(r#""b""#)]);
+        assert_eq!(run(r#"."a b""#, &doc), vec![json("3")]);
+        assert_eq!(run(".items[].id", &doc), vec![json("1"), json("2")]);
+        assert_eq!(run(".items[] | .id", &doc), vec![json("1"), json("2")]);
+        assert_eq!(run(".missing.deeper", &doc), vec![JsonValue::Null]);
+        assert_eq!(run(r#".user["name"]"#, &doc), vec![json(r#""John""#)]);
+        assert_eq!(
+            run(".user.name, .a", &doc),
+            vec![json(r#""John""#), JsonValue::Null]
+        );
+    }
+
+    #[test]
+    fn it_jq_operators_and_builtins() {
+        let doc = json(r#"[{"n": "x", "age": 30}, {"n": "y", "age": 17}, {"n": "z", "age": 45}]"#);
+
+        assert_eq!(
+            run("map(select(.age >= 18) | .n)", &doc),
+            vec![json(r#"["x", "z"]"#)]
+        );
+        assert_eq!(
+            run("[.[] | .age * 2 + 1]", &doc),
+            vec![json("[61, 35, 91]")]
+        );
+        assert_eq!(run("length", &doc), vec![json("3")]);
+        assert_eq!(run(".[0] | keys", &doc), vec![json(r#"["age", "n"]"#)]);
+        assert_eq!(
+            run(".[1].age % 5, 7 / 2", &doc),
+            vec![json("2"), json("3.5")]
+        );
+        assert_eq!(run(r#"(.[0].n + "!") == "x!""#, &doc), vec![json("true")]);
+        assert_eq!(
+            run("null < false and [1] > \"z\" | not", &doc),
+            vec![json("false")]
+        );
+        assert_eq!(
+            run("(1, 2) + (10, 20)", &doc),
+            vec![json("11"), json("12"), json("21"), json("22")]
+        );
+        assert_eq!(run("[.[].n] - [\"y\"]", &doc), vec![json(r#"["x", "z"]"#)]);
+    }
+
+    #[test]
+    fn it_jq_errors() {
+        let doc = json(r#"{"a": 1}"#);
+
+        assert!(Filter::parse(".a |").is_err());
+        assert!(Filter::parse("frobnicate(.)").is_err());
+        assert!(Filter::parse(".[").is_err());
+        assert_eq!(
+            eval(".a.b", &doc).unwrap_err().message(),
+            r#"Cannot index number with "b""#
+        );
+        assert_eq!(
+            eval(r#".a + "x""#, &doc).unwrap_err().message(),
+            r#"number (1) and string ("x") cannot be added"#
+        );
+        assert!(eval(".a / 0", &doc).is_err());
+        assert!(eval(".[]", &json("1")).is_err());
+    }
+}
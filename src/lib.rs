@@ -1,100 +1,319 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(dead_code)]
 
-use common::match_token;
-use error::JError;
-use indexmap::IndexMap;
+extern crate alloc;
+// Lets the `::jnom_rs` paths emitted by the derive macros resolve inside this crate
+#[cfg(feature = "derive")]
+extern crate self as jnom_rs;
+
+use alloc::{boxed::Box, format, vec::Vec};
+use core::ops::Range;
+
+use combinators::{match_token, mismatch, no_match};
+use error::Expected;
 use nom::{
     branch::alt,
-    combinator::map,
-    multi::separated_list0,
-    sequence::{delimited, tuple},
-    Slice,
+    combinator::{cut, map},
+    sequence::{delimited, preceded, tuple},
+};
+
+#[cfg(feature = "arena")]
+pub use arena::{parse_in, parse_in_interned};
+pub use combinators::context;
+pub use encoding::Utf8Policy;
+pub use error::{ErrorCode, ErrorKind, JError};
+#[cfg(feature = "mmap")]
+pub use mmap::{parse_file_mmap, MappedDocument};
+pub use options::{NumberMode, ParseOptions};
+#[cfg(feature = "parallel")]
+pub use parallel::parse_parallel;
+pub use shared::SharedValue;
+pub use token::{
+    token_at_offset, tokenize, tokenize_recovering, CustomLiteral, JsonToken, JsonTokenKind,
+    TokenBuffer, TokenInput, TokenKind, TokenStream,
 };
-use token::{JsonToken, JsonTokenKind};
+pub use validate::{validate, validate_with};
+pub use value::{BorrowedValue, JsonValue, Map};
 
-mod common;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_interop;
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod ast;
+#[cfg(feature = "base64")]
+pub mod binary;
+pub mod canonical;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "std")]
+pub mod codegen;
+pub mod combinators;
+pub mod compare;
+mod convert;
+#[cfg(feature = "std")]
+pub mod csv;
+#[cfg(feature = "chrono")]
+pub mod datetime;
+pub mod diff;
+pub mod encoding;
 mod error;
+pub mod event;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "std")]
+pub mod generate;
+pub mod jq;
+pub mod lines;
+pub mod lint;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod normalize;
+pub mod object;
+mod options;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod pointer;
+pub mod project;
+pub mod query;
+#[cfg(feature = "std")]
+pub mod reader;
+#[cfg(feature = "std")]
+pub mod redact;
+#[cfg(feature = "simd")]
+mod scan;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod seq;
+pub mod ser;
+#[cfg(feature = "serde_json")]
+mod serde_interop;
+mod shared;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod tape;
 mod token;
+pub mod typed;
+mod validate;
+mod value;
+#[cfg(feature = "yaml")]
+pub mod yaml;
 
+/// A token slice, the simplest [`TokenInput`]; see [`combinators`] for writing parsers over it.
 pub type Input<'a> = &'a [JsonToken<'a>];
+/// Result of a parser over an [`Input`], failing with a [`JError`].
 pub type IResult<'a, Output> = nom::IResult<Input<'a>, Output, error::JError>;
+type PResult<'a, I, Output> = nom::IResult<I, Output, error::JError>;
 
 #[derive(Debug, PartialEq)]
 enum JsonExpr<'a> {
-    Object(Box<IndexMap<&'a str, JsonExpr<'a>>>),
+    Object(Box<Map<&'a str, JsonExpr<'a>>>),
     Array(Vec<JsonExpr<'a>>),
     String(&'a str),
     Number(f64),
     Boolean(bool),
     Null,
+    Custom(&'static str, &'a str),
+    // An integer literal too large for i64 and u64
+    #[cfg(feature = "num-bigint")]
+    BigInt(&'a str),
+    // A fractional literal, read as a float or a decimal depending on the number mode
+    #[cfg(feature = "rust_decimal")]
+    Fraction(f64, &'a str),
 }
 
-fn parse_json(i: Input) -> IResult<JsonExpr> {
-    alt((
-        parse_obj,
-        parse_array,
-        parse_string,
-        parse_number,
-        parse_bool,
-    ))(i)
+// parse_json Parse one value, picking the rule from the next token so a failure names a
+// value rather than whichever alternative happened to be tried last
+//
+// Once the rule is picked the input cannot be anything else, so its errors are failures
+// and reach the caller from the token that broke, not from the enclosing container.
+fn parse_json<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
+    match i.peek().map(|token| &token.kind) {
+        Some(JsonTokenKind::OpenBrace) => cut(parse_obj)(i),
+        Some(JsonTokenKind::OpenBracket) => cut(parse_array)(i),
+        Some(JsonTokenKind::String) => cut(parse_string)(i),
+        Some(JsonTokenKind::Number(_)) => cut(parse_number)(i),
+        Some(JsonTokenKind::True | JsonTokenKind::False) => cut(parse_bool)(i),
+        Some(JsonTokenKind::Null) => cut(parse_null)(i),
+        Some(JsonTokenKind::Custom(_)) => cut(parse_custom)(i),
+        _ => Err(mismatch(&i, Expected::Value)),
+    }
 }
 
-fn parse_obj(i: Input) -> IResult<JsonExpr> {
+// parse Parse a complete JSON document into an owned value
+pub fn parse(source: &str) -> Result<JsonValue, JError> {
+    parse_with(source, &ParseOptions::default())
+}
+
+// parse_with Parse a complete JSON document, relaxing the checks chosen in `options`
+pub fn parse_with(source: &str, options: &ParseOptions) -> Result<JsonValue, JError> {
+    JsonValue::from_expr(parse_expr(source, options)?, options.number_mode)
+}
+
+// parse_borrowed Parse a complete JSON document, borrowing every string without escapes
+pub fn parse_borrowed(source: &str) -> Result<BorrowedValue<'_>, JError> {
+    let options = ParseOptions::default();
+    BorrowedValue::from_expr(parse_expr(source, &options)?, options.number_mode)
+}
+
+/// Parse a document from raw bytes.
+///
+/// UTF-16 (either byte order, with or without BOM) is detected and transcoded; bytes
+/// that are not valid in the detected encoding are handled as `policy` says.
+pub fn parse_bytes(bytes: &[u8], policy: Utf8Policy) -> Result<JsonValue, JError> {
+    parse(&encoding::decode(bytes, policy)?)
+}
+
+// parse_utf16 Parse UTF-16 bytes, big-endian only when marked by a BOM or the zero-byte pattern
+pub fn parse_utf16(bytes: &[u8]) -> Result<JsonValue, JError> {
+    let big_endian = encoding::Encoding::detect(bytes) == encoding::Encoding::Utf16Be;
+    parse(&encoding::decode_utf16(
+        bytes,
+        big_endian,
+        Utf8Policy::Error,
+    )?)
+}
+
+fn parse_expr<'a>(source: &'a str, options: &ParseOptions) -> Result<JsonExpr<'a>, JError> {
+    match parse_json(TokenStream::with_options(source, options)) {
+        Ok((rest, expr)) => match (rest.peek(), rest.lex_error()) {
+            (Some(token), _) => Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Unexpected trailing input {token:?}"),
+            )
+            .with_span(token.span.clone())),
+            (None, Some(e)) => Err(e),
+            (None, None) => Ok(expr),
+        },
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e),
+        Err(nom::Err::Incomplete(_)) => Err(JError::new(ErrorKind::Eof, "Unexpected end of input")),
+    }
+    .map_err(|e| e.locate_document(source))
+}
+
+fn parse_obj<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
     delimited(
         match_token(JsonTokenKind::OpenBrace),
-        separated_list0(
-            match_token(JsonTokenKind::Comma),
-            tuple((parse_string, match_token(JsonTokenKind::Colon), parse_json)),
-        ),
+        items(context(
+            "object member",
+            tuple((
+                parse_string,
+                cut(preceded(match_token(JsonTokenKind::Colon), parse_json)),
+            )),
+        )),
         match_token(JsonTokenKind::CloseBrace),
     )(i)
     .map(|(i, map_var)| {
         let out = map_var
             .into_iter()
-            .map(|(k, _, v)| match k {
+            .map(|(k, v)| match k {
                 JsonExpr::String(k) => (k, v),
                 _ => unreachable!(),
             })
-            .collect::<IndexMap<_, _>>();
+            .collect::<Map<_, _>>();
 
         (i, JsonExpr::Object(Box::new(out)))
     })
 }
 
-fn parse_array(i: Input) -> IResult<JsonExpr> {
+fn parse_array<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
     tuple((
         match_token(JsonTokenKind::OpenBracket),
-        separated_list0(match_token(JsonTokenKind::Comma), parse_json),
+        items(context("array element", parse_json)),
         match_token(JsonTokenKind::CloseBracket),
     ))(i)
     .map(|(i, (_, array_var, _))| (i, JsonExpr::Array(array_var)))
 }
 
-fn parse_string(i: Input) -> IResult<JsonExpr> {
-    match i.get(0) {
-        Some(JsonToken {
-            kind: JsonTokenKind::String(s),
-            ..
-        }) => Ok((i.slice(1..), JsonExpr::String(s.trim_matches('"')))),
-        _ => Err(nom::Err::Error(JError(format!(
-            "JsonToken Kind String does not match"
-        )))),
+// items Zero or more `item`s separated by commas; after a comma the item is required, so
+// a trailing comma is reported where the item is missing
+fn items<'a, I: TokenInput<'a>, O>(
+    mut item: impl FnMut(I) -> PResult<'a, I, O>,
+) -> impl FnMut(I) -> PResult<'a, I, Vec<O>> {
+    move |i: I| {
+        let mut out = Vec::new();
+        let mut rest = match item(i.clone()) {
+            Ok((rest, first)) => {
+                out.push(first);
+                rest
+            }
+            Err(nom::Err::Error(_)) => return Ok((i, out)),
+            Err(e) => return Err(e),
+        };
+        while let Ok((after, _)) = match_token(JsonTokenKind::Comma)(rest.clone()) {
+            let (after, next) = cut(&mut item)(after)?;
+            out.push(next);
+            rest = after;
+        }
+        Ok((rest, out))
     }
 }
 
-fn parse_number(i: Input) -> IResult<JsonExpr> {
-    match i.get(0) {
-        Some(JsonToken {
-            kind: JsonTokenKind::Number(n),
-            ..
-        }) => Ok((i.slice(1..), JsonExpr::Number(*n))),
-        _ => Err(nom::Err::Error(JError(format!(
-            "JsonToken Kind Number does not match"
-        )))),
+fn parse_string<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
+    match i.peek() {
+        Some(
+            token @ JsonToken {
+                kind: JsonTokenKind::String,
+                span,
+                ..
+            },
+        ) => {
+            let at = token.text();
+            let s = &at[1..at.len() - 1];
+            // The token is certainly a string, so a bad escape must not let `alt` try the next branch
+            if let Some(Err(e)) = s.contains('\\').then(|| value::unescape_with(s, |_| {})) {
+                return Err(nom::Err::Failure(
+                    JError::new(
+                        e.kind(),
+                        format!("{} at {}..{}", e.message(), span.start, span.end),
+                    )
+                    .with_span(span.clone()),
+                ));
+            }
+            Ok((i.advance(), JsonExpr::String(s)))
+        }
+        _ => Err(mismatch(&i, Expected::Kind(TokenKind::String))),
+    }
+}
+
+fn parse_number<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
+    match i.peek() {
+        Some(
+            token @ JsonToken {
+                kind: JsonTokenKind::Number(n),
+                ..
+            },
+        ) => {
+            let expr = number_expr(*n, token.text());
+            Ok((i.advance(), expr))
+        }
+        _ => Err(mismatch(&i, Expected::Kind(TokenKind::Number))),
     }
 }
 
-fn parse_bool(i: Input) -> IResult<JsonExpr> {
+// number_expr Keep the digits of literals that an exact number type enabled by a feature may need
+#[cfg_attr(
+    not(any(feature = "num-bigint", feature = "rust_decimal")),
+    allow(unused_variables)
+)]
+fn number_expr(n: f64, at: &str) -> JsonExpr<'_> {
+    #[cfg(feature = "num-bigint")]
+    if value::is_big_integer(at) {
+        return JsonExpr::BigInt(at);
+    }
+    #[cfg(feature = "rust_decimal")]
+    if at.contains(['.', 'e', 'E']) {
+        return JsonExpr::Fraction(n, at);
+    }
+    JsonExpr::Number(n)
+}
+
+fn parse_bool<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
     alt((
         map(match_token(JsonTokenKind::True), |_| {
             JsonExpr::Boolean(true)
@@ -105,10 +324,78 @@ fn parse_bool(i: Input) -> IResult<JsonExpr> {
     ))(i)
 }
 
+fn parse_null<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
+    map(match_token(JsonTokenKind::Null), |_| JsonExpr::Null)(i)
+}
+
+fn parse_custom<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, JsonExpr<'a>> {
+    match i.peek() {
+        Some(
+            token @ JsonToken {
+                kind: JsonTokenKind::Custom(tag),
+                ..
+            },
+        ) => {
+            let (tag, at) = (*tag, token.text());
+            Ok((i.advance(), JsonExpr::Custom(tag, at)))
+        }
+        _ => Err(mismatch(&i, Expected::Kind(TokenKind::Custom))),
+    }
+}
+
+/// Consume exactly one complete value without building anything.
+///
+/// Returns the input after the value and the byte span it covered. Only bracket balance
+/// is checked inside containers; use the full parser when the contents must be valid.
+pub fn skip_value<'a, I: TokenInput<'a>>(i: I) -> PResult<'a, I, Range<usize>> {
+    let start = match i.peek() {
+        Some(token) => token.span.start,
+        None => return Err(no_match(&i, "Expected a value to skip")),
+    };
+    // Open containers, `true` for objects
+    let mut open = Vec::new();
+    let mut rest = i;
+    loop {
+        let (kind, end) = match rest.peek() {
+            Some(token) => (TokenKind::from(&token.kind), token.span.end),
+            None => {
+                return Err(no_match(
+                    &rest,
+                    "Unexpected end of input while skipping a value",
+                ))
+            }
+        };
+        let balanced = match kind {
+            TokenKind::OpenBrace | TokenKind::OpenBracket => {
+                open.push(kind == TokenKind::OpenBrace);
+                true
+            }
+            TokenKind::CloseBrace | TokenKind::CloseBracket => {
+                open.pop() == Some(kind == TokenKind::CloseBrace)
+            }
+            TokenKind::Colon | TokenKind::Comma => !open.is_empty(),
+            TokenKind::Error => false,
+            _ => true,
+        };
+        if !balanced {
+            return Err(no_match(
+                &rest,
+                format!("Unexpected {kind:?} while skipping a value"),
+            ));
+        }
+        rest = rest.advance();
+        if open.is_empty() {
+            return Ok((rest, start..end));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::token::tokenize;
-    use crate::JsonExpr;
+    use alloc::{boxed::Box, format, string::ToString, vec, vec::Vec};
+
+    use crate::token::{tokenize, TokenInput, TokenStream};
+    use crate::{JsonExpr, JsonTokenKind, TokenBuffer, TokenKind, Utf8Policy};
 
     #[test]
     fn it_tokenize() {
@@ -126,14 +413,566 @@ mod tests {
             }
         "#;
         let tokens = tokenize(source);
+        assert!(!tokens.is_empty());
+        #[cfg(feature = "std")]
         println!("{:#?}", tokens);
     }
 
+    #[test]
+    fn it_tokenize_recovering() {
+        let source = "{\"a\": @@x, \"b\": [1, #, tru], \"c\": \"ok\"} ~";
+        let tokens = super::tokenize_recovering(source);
+        let errors = tokens
+            .iter()
+            .filter(|t| t.kind == JsonTokenKind::Error)
+            .map(|t| (t.text(), t.span.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![("@@x", 6..9), ("#", 20..21), ("tru", 23..26), ("~", 40..41)]
+        );
+        assert_eq!(tokens.last().map(|t| t.span.clone()), Some(40..41));
+        assert_eq!(
+            tokens
+                .iter()
+                .filter(|t| t.kind == JsonTokenKind::Comma)
+                .count(),
+            4
+        );
+        assert!(super::parse_json(tokens.as_slice()).is_err());
+        assert_eq!(super::tokenize_recovering("[1]").len(), 3);
+    }
+
+    #[test]
+    fn it_bom_and_utf8_policy() {
+        let source = "\u{feff}{\"a\": [1]}";
+        let tokens = tokenize(source);
+
+        assert_eq!(tokens[0].span, 3..4);
+        assert_eq!(
+            crate::parse(source).unwrap(),
+            crate::parse(r#"{"a": [1]}"#).unwrap()
+        );
+        assert_eq!(TokenBuffer::new(source).unwrap().span(0), Some(3..4));
+        assert!(crate::tape::Tape::parse(source).is_ok());
+        assert!(crate::parse("[\u{feff}1]").is_err());
+
+        let bytes = b"[\"a\xffb\", \"\xe2\x82\"]";
+        assert_eq!(
+            crate::parse_bytes(bytes, Utf8Policy::Error)
+                .unwrap_err()
+                .message(),
+            "Invalid UTF-8 at byte 3"
+        );
+        assert_eq!(
+            crate::parse_bytes(bytes, Utf8Policy::Strip).unwrap(),
+            crate::parse(r#"["ab", ""]"#).unwrap()
+        );
+        assert_eq!(
+            crate::parse_bytes(bytes, Utf8Policy::Replace).unwrap(),
+            crate::parse("[\"a\u{fffd}b\", \"\u{fffd}\"]").unwrap()
+        );
+        assert!(crate::parse_bytes(b"\xef\xbb\xbf[true]", Utf8Policy::Error).is_ok());
+    }
+
+    #[test]
+    fn it_utf16_input() {
+        let text = "\u{feff}{\"k\": \"é😀\"}";
+        let le = text
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        let be = text
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect::<Vec<_>>();
+        let expected = crate::parse(r#"{"k": "é😀"}"#).unwrap();
+
+        assert_eq!(crate::parse_utf16(&le).unwrap(), expected);
+        assert_eq!(crate::parse_utf16(&be).unwrap(), expected);
+        assert_eq!(crate::parse_utf16(&be[2..]).unwrap(), expected);
+        assert_eq!(
+            crate::parse_bytes(&le[2..], Utf8Policy::Error).unwrap(),
+            expected
+        );
+        assert_eq!(
+            crate::parse_bytes(&be, Utf8Policy::Error).unwrap(),
+            expected
+        );
+
+        let lone = [b'[', 0, b'"', 0, 0x00, 0xd8, b'"', 0, b']', 0];
+        assert_eq!(
+            crate::parse_bytes(&lone, Utf8Policy::Error)
+                .unwrap_err()
+                .message(),
+            "Invalid UTF-16 at byte 4"
+        );
+        assert_eq!(
+            crate::parse_bytes(&lone, Utf8Policy::Replace).unwrap(),
+            crate::parse("[\"\u{fffd}\"]").unwrap()
+        );
+        assert!(crate::parse_utf16(&le[..le.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn it_control_chars_in_strings() {
+        let source = "{\"a\": \"line\nbreak\", \"b\": \"\\n\"}";
+        let err = crate::parse(source).unwrap_err();
+        assert_eq!(
+            err.message(),
+            r#"Unescaped control character '\n' in string at 11..12"#
+        );
+        assert_eq!(
+            super::token::try_tokenize(source).unwrap_err().message(),
+            err.message()
+        );
+        assert_eq!(
+            TokenBuffer::new(source).unwrap_err().message(),
+            err.message()
+        );
+        assert!(crate::tape::Tape::parse("[\"\t\"]").is_err());
+
+        let lenient = crate::ParseOptions::new().allow_control_chars();
+        let value = crate::parse_with(source, &lenient).unwrap();
+        assert_eq!(value.get("a").and_then(|v| v.as_str()), Some("line\nbreak"));
+        assert!(crate::parse("[\"tab\\tescaped\"]").is_ok());
+    }
+
+    #[test]
+    fn it_non_finite_literals() {
+        let source = "[NaN, Infinity, -Infinity, -1]";
+        assert_eq!(
+            crate::parse(source).unwrap_err().message(),
+            "Non-finite number NaN at 1..4 is not allowed"
+        );
+
+        let options = crate::ParseOptions::new().allow_non_finite();
+        let value = crate::parse_with(source, &options).unwrap();
+        let numbers = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_f64().unwrap())
+            .collect::<Vec<_>>();
+        assert!(numbers[0].is_nan());
+        assert_eq!(numbers[1..], [f64::INFINITY, f64::NEG_INFINITY, -1.0]);
+        assert!(crate::parse_with("[nan]", &options).is_err());
+    }
+
+    #[test]
+    fn it_hex_numbers() {
+        let source = r#"{"mask": 0xFF, "neg": -0x10, "big": 0x1FFFFFFFFFFFFFFFF}"#;
+        assert_eq!(
+            crate::parse(source).unwrap_err().message(),
+            "Hexadecimal number 0xFF at 9..13 is not allowed"
+        );
+
+        let options = crate::ParseOptions::new().allow_hex();
+        assert_eq!(
+            crate::parse_with(source, &options).unwrap_err().message(),
+            "Hexadecimal number 0x1FFFFFFFFFFFFFFFF at 36..55 is out of range"
+        );
+        let value = crate::parse_with(r#"{"mask": 0xFF, "neg": -0x10}"#, &options).unwrap();
+        assert_eq!(value.get("mask").and_then(|v| v.as_f64()), Some(255.0));
+        assert_eq!(value.get("neg").and_then(|v| v.as_f64()), Some(-16.0));
+
+        let stream = TokenStream::with_options("0xFF", &options);
+        assert_eq!(stream.peek().map(|t| t.text()), Some("0xFF"));
+        assert!(crate::parse_with("0x", &options).is_err());
+
+        let exact = crate::parse_with("[0x20000000000000, -0x1FFFFFFFFFFFFF]", &options).unwrap();
+        let exact = exact.as_array().unwrap();
+        assert_eq!(exact[0].as_f64(), Some(9_007_199_254_740_992.0));
+        assert_eq!(exact[1].as_f64(), Some(-9_007_199_254_740_991.0));
+        assert_eq!(
+            crate::parse_with("0x20000000000001", &options)
+                .unwrap_err()
+                .message(),
+            "Hexadecimal number 0x20000000000001 at 0..16 is out of range"
+        );
+    }
+
+    #[test]
+    fn it_error_kinds() {
+        use crate::ErrorKind;
+
+        let err = crate::parse(r#"{"a": "bad \x escape"}"#).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Escape);
+        assert_eq!(err.message(), "Invalid escape \\x at 6..21");
+
+        let source = r#"["\u12"]"#;
+        let tokens = tokenize(source);
+        assert!(matches!(
+            super::parse_json(tokens.as_slice()),
+            Err(nom::Err::Failure(e)) if e.kind() == ErrorKind::Escape
+        ));
+        assert!(matches!(
+            super::parse_string(&tokens[..1]),
+            Err(nom::Err::Error(e)) if e.kind() == ErrorKind::Syntax
+        ));
+
+        assert_eq!(crate::parse("[1, @]").unwrap_err().kind(), ErrorKind::Lex);
+        assert_eq!(crate::parse("[1,").unwrap_err().kind(), ErrorKind::Syntax);
+        assert_eq!(crate::parse("[1] 2").unwrap_err().kind(), ErrorKind::Syntax);
+        assert_eq!(
+            crate::parse_bytes(b"[\"\xff\"]", Utf8Policy::Error)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Encoding
+        );
+
+        #[cfg(feature = "std")]
+        {
+            let boxed: Box<dyn std::error::Error> = Box::new(crate::parse("nul").unwrap_err());
+            assert_eq!(boxed.to_string(), r#"Invalid token "n" at 0..1"#);
+        }
+    }
+
+    #[test]
+    fn it_error_context() {
+        let err = crate::parse(r#"{"a": [1, {"b": @}]}"#).unwrap_err();
+        assert_eq!(
+            err.context(),
+            ["object member", "array element", "object member"]
+        );
+        assert_eq!(
+            err.to_string(),
+            r#"Invalid token "@" at 16..17 (in object member, in array element, in object member)"#
+        );
+        assert!(crate::parse("[true]").is_ok());
+
+        let tokens = tokenize("[1 2]");
+        let mut rule = crate::context("pair", super::parse_array);
+        let err = match rule(tokens.as_slice()) {
+            Err(nom::Err::Error(e)) => e,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(err.context(), ["pair"]);
+
+        let err = nom::error::context("number", super::parse_number)(tokens.as_slice());
+        assert!(matches!(err, Err(nom::Err::Error(e)) if e.context() == ["number"]));
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_error_codes() {
+        use crate::{lines::LineCol, ErrorCode};
+
+        let code = |source: &str| crate::parse(source).unwrap_err().code();
+        assert_eq!(code("[1] 2"), ErrorCode::UnexpectedToken);
+        assert_eq!(code(r#"["abc"#), ErrorCode::UnterminatedString);
+        assert_eq!(code("[1, @]"), ErrorCode::InvalidToken);
+        assert_eq!(code("[\"a\tb\"]"), ErrorCode::ControlCharacter);
+        assert_eq!(code("[NaN]"), ErrorCode::InvalidNumber);
+        assert_eq!(code(r#"["\x"]"#), ErrorCode::InvalidEscape);
+        assert_eq!(ErrorCode::InvalidEscape.as_str(), "E0006");
+
+        let source = "{\"a\": 1,\n \"b~\": [0, @]}";
+        let err = crate::parse(source).unwrap_err();
+        assert_eq!(err.span(), Some(20..21));
+        assert_eq!(err.line_col(), Some(LineCol { line: 1, col: 11 }));
+        assert_eq!(err.path(), Some("/b~0/1"));
+        assert_eq!(
+            crate::ser::to_string(&err.to_json()),
+            r#"{"code":"E0003","message":"Invalid token \"@\" at 20..21","context":["array element","object member"],"span":{"start":20,"end":21},"line":1,"col":11,"path":"/b~0/1"}"#
+        );
+
+        // a bad key reports its object, a bad value its member
+        let err = crate::parse(r#"{"a": {"b": 1, @: 2}}"#).unwrap_err();
+        assert_eq!(err.path(), Some("/a"));
+        let err = crate::parse(r#"{"a": {"b": "\u12"}}"#).unwrap_err();
+        assert_eq!(err.path(), Some("/a/b"));
+
+        // a structural error points at the token that broke, not at its container
+        let err = crate::parse("[true,\n [1 2]]").unwrap_err();
+        assert_eq!(
+            crate::ser::to_string(&err.to_json()),
+            r#"{"code":"E0001","message":"JsonToken Kind ] does not match at 11","context":["array element"],"span":{"start":11,"end":12},"line":1,"col":4,"path":"/1/0"}"#
+        );
+        assert_eq!(
+            crate::parse("[1,]").unwrap_err().to_string(),
+            "Expected a value at 3 (in array element)"
+        );
+        let err = crate::parse("[1, ").unwrap_err();
+        assert_eq!(
+            (err.code(), err.span()),
+            (ErrorCode::UnexpectedEof, Some(4..4))
+        );
+
+        let err = crate::JError::new(crate::ErrorKind::Io, "closed");
+        assert_eq!(err.code(), ErrorCode::Io);
+        assert_eq!(
+            crate::ser::to_string(&err.to_json()),
+            r#"{"code":"E0011","message":"closed","context":[],"span":null,"line":null,"col":null,"path":null}"#
+        );
+    }
+
+    #[test]
+    fn it_lazy_mismatch() {
+        let tokens = tokenize("[1, true]");
+        let err = match super::parse_obj(&tokens[1..]) {
+            Err(nom::Err::Error(e)) => e,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(err.message(), "JsonToken Kind { does not match at 1");
+
+        let err = match crate::combinators::match_text("false")(&tokens[3..]) {
+            Err(nom::Err::Error(e)) => e,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(err.to_string(), "Json Text false does not match at 4");
+
+        let err = match super::parse_number(&tokens[5..]) {
+            Err(nom::Err::Error(e)) => e,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(err.kind(), crate::ErrorKind::Syntax);
+        assert_eq!(
+            err.message(),
+            "JsonToken Kind Number does not match at the end of input"
+        );
+    }
+
+    #[test]
+    fn it_custom_literals() {
+        use crate::{CustomLiteral, JsonValue, ParseOptions};
+
+        fn date(rest: &str) -> Option<usize> {
+            let b = rest.as_bytes();
+            let digits = |r: core::ops::Range<usize>| {
+                b.get(r).is_some_and(|d| d.iter().all(u8::is_ascii_digit))
+            };
+            (digits(0..4) && b[4] == b'-' && digits(5..7) && b[7] == b'-' && digits(8..10))
+                .then_some(10)
+        }
+        fn directive(rest: &str) -> Option<usize> {
+            let name = rest.strip_prefix('@')?;
+            Some(
+                1 + name
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(name.len()),
+            )
+        }
+        const LITERALS: &[CustomLiteral] = &[
+            CustomLiteral::new("date", date),
+            CustomLiteral::new("directive", directive),
+        ];
+
+        let source = r#"{"n": 2024, "since": 2024-01-31, "use": [@cache, @retry]}"#;
+        assert!(crate::parse(source).is_err());
+        let options = ParseOptions::new().custom_literals(LITERALS);
+        let value = crate::parse_with(source, &options).unwrap();
+
+        assert_eq!(
+            value.get("since"),
+            Some(&JsonValue::Custom("date", "2024-01-31".into()))
+        );
+        assert_eq!(value.get("n").and_then(|v| v.as_f64()), Some(2024.0));
+        assert_eq!(
+            value.get("use").and_then(|v| v.as_array()).unwrap()[1].type_name(),
+            "directive"
+        );
+        // the literals are written back as is, which only the same options read again
+        let written = value.to_string();
+        assert!(written.contains(r#""since":2024-01-31"#));
+        assert!(crate::parse(&written).is_err());
+        assert_eq!(crate::parse_with(&written, &options).unwrap(), value);
+        assert!(crate::validate_with(source, &options).is_ok());
+
+        let stream = TokenStream::with_options("[@x]", &options);
+        let token = stream.advance().peek().cloned().unwrap();
+        assert_eq!(token.kind, JsonTokenKind::Custom("directive"));
+        assert_eq!(token.span, 1..3);
+        assert_eq!(
+            crate::parse_with("[@x, #]", &options)
+                .unwrap_err()
+                .message(),
+            r##"Invalid token "#" at 5..6"##
+        );
+
+        // structural errors name what was expected, custom literals or not
+        for options in [ParseOptions::new(), options] {
+            let message = |source| crate::parse_with(source, &options).unwrap_err().to_string();
+            assert_eq!(message("[1 2]"), "JsonToken Kind ] does not match at 3");
+            assert_eq!(
+                message(r#"{"a": 1 "b": 2}"#),
+                "JsonToken Kind } does not match at 8"
+            );
+            assert_eq!(message(""), "Expected a value at the end of input");
+        }
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn it_big_integers() {
+        use num_bigint::BigInt;
+
+        let source = "[18446744073709551615, 18446744073709551616, -9223372036854775809, 1e30]";
+        let value = crate::parse(source).unwrap();
+        let items = value.as_array().unwrap();
+
+        assert!(items[0].as_bigint().is_none());
+        assert_eq!(
+            items[1].as_bigint(),
+            Some(&"18446744073709551616".parse::<BigInt>().unwrap())
+        );
+        assert_eq!(items[2].type_name(), "number");
+        assert_eq!(items[2].as_f64(), Some(-9223372036854775809.0));
+        assert!(items[3].as_bigint().is_none());
+        assert_eq!(
+            crate::parse("[18446744073709551616, -9223372036854775809]")
+                .unwrap()
+                .to_string(),
+            "[18446744073709551616,-9223372036854775809]"
+        );
+        assert_eq!(
+            crate::parse_borrowed("123456789012345678901234567890")
+                .unwrap()
+                .into_owned(),
+            crate::JsonValue::BigInt("123456789012345678901234567890".parse().unwrap())
+        );
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn it_decimal_numbers() {
+        use rust_decimal::Decimal;
+
+        use crate::{NumberMode, ParseOptions};
+
+        let source = r#"{"price": 0.10, "qty": 3, "rate": 1.25e-2}"#;
+        assert_eq!(
+            crate::parse(source)
+                .unwrap()
+                .get("price")
+                .and_then(|v| v.as_decimal()),
+            None
+        );
+
+        let options = ParseOptions::new().number_mode(NumberMode::Decimal);
+        let value = crate::parse_with(source, &options).unwrap();
+        let price = value.get("price").and_then(|v| v.as_decimal()).unwrap();
+        assert_eq!(price * Decimal::from(3), "0.30".parse::<Decimal>().unwrap());
+        assert_eq!(value.get("qty").and_then(|v| v.as_f64()), Some(3.0));
+        assert_eq!(value.get("rate").and_then(|v| v.as_f64()), Some(0.0125));
+        assert_eq!(value.to_string(), r#"{"price":0.10,"qty":3,"rate":0.0125}"#);
+        assert_eq!(
+            crate::parse_with("[0.1234567890123456789012345678901]", &options)
+                .unwrap_err()
+                .kind(),
+            crate::ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn it_token_buffer() {
+        let buffer = TokenBuffer::new(r#"{"a": [1.5, null]}"#).unwrap();
+
+        assert_eq!(buffer.len(), 9);
+        assert_eq!(buffer.kind(3), Some(TokenKind::OpenBracket));
+        assert_eq!(buffer.text(1), Some(r#""a""#));
+        assert_eq!(buffer.span(4), Some(7..10));
+        assert_eq!(buffer.kind(9), None);
+        assert!(TokenBuffer::new("[1, @]").is_err());
+    }
+
+    #[test]
+    fn it_token_buffer_relex() {
+        fn tokens(buffer: &TokenBuffer) -> Vec<(TokenKind, core::ops::Range<usize>)> {
+            (0..buffer.len())
+                .map(|idx| (buffer.kind(idx).unwrap(), buffer.span(idx).unwrap()))
+                .collect()
+        }
+
+        let source = "\u{feff}{\"a\": [1, 22, 333], \"b\": \"x y\", \"c\": null}";
+        let edits = [
+            (7..8, ""),
+            (11..12, "5"),
+            (12..12, "0"),
+            (16..16, "\"open"),
+            (23..23, " \"k\": true,"),
+            (3..4, "[{"),
+            (0..3, ""),
+            (source.len() - 1..source.len(), ", \"d\": [] }"),
+            (34..38, "\"quoted\""),
+            (0..source.len(), "false"),
+        ];
+        for (range, text) in edits {
+            let edited = format!("{}{text}{}", &source[..range.start], &source[range.end..]);
+            let old = TokenBuffer::new(source).unwrap();
+            let relexed = old.relex(&edited, range.clone(), text);
+            match TokenBuffer::new(&edited) {
+                Ok(fresh) => assert_eq!(tokens(&relexed.unwrap()), tokens(&fresh), "{edited}"),
+                Err(e) => assert_eq!(relexed.unwrap_err().message(), e.message()),
+            }
+        }
+
+        let old = TokenBuffer::new("[1]").unwrap();
+        assert!(old.clone().relex("[12]", 1..2, "3").is_err());
+        assert!(old.relex("[1]", 2..9, "").is_err());
+    }
+
+    #[test]
+    fn it_token_stream() {
+        let source = r#"{"scores": [100, 90], "name": "John"}"#;
+        let tokens = tokenize(source);
+        let (rest, streamed) = super::parse_json(TokenStream::new(source)).unwrap();
+
+        assert!(rest.peek().is_none());
+        assert_eq!(streamed, super::parse_json(tokens.as_slice()).unwrap().1);
+
+        let stream = TokenStream::new("[1, @]");
+        assert_eq!(
+            stream.advance().advance().peek().map(|t| t.text()),
+            Some(",")
+        );
+        assert!(stream.advance().advance().advance().lex_error().is_some());
+
+        let err = crate::parse("[1, @]").unwrap_err();
+        assert_eq!(err.message(), r#"Invalid token "@" at 4..5"#);
+    }
+
+    #[test]
+    fn it_skip_value() {
+        let source = r#"[{"a": [1, {}]}, "x", 3] tail"#;
+        let tokens = tokenize(source);
+
+        let (rest, span) = super::skip_value(&tokens[1..]).unwrap();
+        assert_eq!(&source[span], r#"{"a": [1, {}]}"#);
+        assert_eq!(rest.peek().map(|t| t.text()), Some(","));
+
+        let (rest, span) = super::skip_value(TokenStream::new(source)).unwrap();
+        assert_eq!(span, 0..24);
+        assert!(rest.peek().is_none());
+
+        let (_, span) = super::skip_value(&tokens[5..]).unwrap();
+        assert_eq!(&source[span], "1");
+
+        assert!(super::skip_value(tokenize("]").as_slice()).is_err());
+        assert!(super::skip_value(tokenize("[1}").as_slice()).is_err());
+        assert!(super::skip_value(tokenize("[1, 2").as_slice()).is_err());
+        assert!(super::skip_value(tokenize(",").as_slice()).is_err());
+        assert!(super::skip_value(TokenStream::new("[1, @]")).is_err());
+
+        // rejected input kept by the recovering lexer is an error, not a panic
+        let recovered = crate::tokenize_recovering("[@]");
+        match super::skip_value(recovered.as_slice()) {
+            Err(nom::Err::Error(e)) => assert_eq!(e.kind(), crate::ErrorKind::Syntax),
+            other => panic!("unexpected {other:?}"),
+        }
+        let error = crate::combinators::match_token(JsonTokenKind::Error)(&recovered[1..]);
+        assert!(matches!(error, Err(nom::Err::Error(_))));
+    }
+
     #[test]
     fn it_parse_string() {
         let source = "\"abc\"";
         let tokens = tokenize(source);
-        let result = super::parse_string(&tokens);
+        let result = super::parse_string(tokens.as_slice());
         let string_var = result.unwrap().1;
         assert_eq!(string_var, JsonExpr::String("abc"));
     }
@@ -142,7 +981,7 @@ mod tests {
     fn it_parse_number() {
         let source = "123";
         let tokens = tokenize(source);
-        let result = super::parse_number(&tokens);
+        let result = super::parse_number(tokens.as_slice());
         let number_var = result.unwrap().1;
 
         assert_eq!(number_var, JsonExpr::Number(123.0));
@@ -152,7 +991,7 @@ mod tests {
     fn it_parse_bool() {
         let source = "true";
         let tokens = tokenize(source);
-        let result = super::parse_bool(&tokens);
+        let result = super::parse_bool(tokens.as_slice());
         let bool_var = result.unwrap().1;
 
         assert_eq!(bool_var, JsonExpr::Boolean(true));
@@ -162,7 +1001,7 @@ mod tests {
     fn it_parse_array() {
         let source = r#"["abc", "def", 1]"#;
         let tokens = tokenize(source);
-        let result = super::parse_array(&tokens);
+        let result = super::parse_array(tokens.as_slice());
         let array_var = result.unwrap().1;
 
         assert_eq!(
@@ -179,7 +1018,7 @@ mod tests {
     fn it_parse_obj() {
         let source = r#"{"name": "John Doe", "address": "杭州"}"#;
         let tokens = tokenize(source);
-        let result = super::parse_obj(&tokens);
+        let result = super::parse_obj(tokens.as_slice());
         let obj_var = result.unwrap().1;
 
         assert_eq!(
@@ -206,7 +1045,7 @@ mod tests {
                 "address": {"city": "Springfield", "state": [1, 12]}
             }"#;
         let tokens = tokenize(source);
-        let result = super::parse_obj(&tokens);
+        let result = super::parse_obj(tokens.as_slice());
         let obj_var = result.unwrap().1;
 
         assert_eq!(
@@ -0,0 +1,200 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt::Write, ops::Range};
+
+/// Unit in which columns are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnUnit {
+    /// Bytes of UTF-8, matching the spans reported by the lexer.
+    #[default]
+    Utf8,
+    /// UTF-16 code units, as used by the Language Server Protocol and JavaScript.
+    Utf16,
+}
+
+/// Zero-based line and column of a position in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Line start table of a source, built once for logarithmic offset lookups.
+///
+/// Lines end at `\n`; a `\r` before it counts as part of the line terminator.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let starts = core::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        LineIndex { source, starts }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    // line_col Position of a byte offset, which is clamped to the source and to a char boundary
+    pub fn line_col(&self, offset: usize, unit: ColumnUnit) -> LineCol {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let prefix = &self.source[self.starts[line]..offset];
+        let col = match unit {
+            ColumnUnit::Utf8 => prefix.len(),
+            ColumnUnit::Utf16 => prefix.encode_utf16().count(),
+        };
+        LineCol { line, col }
+    }
+
+    // offset Byte offset of a position, or `None` past the end of its line
+    pub fn offset(&self, pos: LineCol, unit: ColumnUnit) -> Option<usize> {
+        let span = self.line_span(pos.line)?;
+        let text = &self.source[span.clone()];
+        let col = match unit {
+            ColumnUnit::Utf8 => Some(pos.col).filter(|col| text.is_char_boundary(*col)),
+            ColumnUnit::Utf16 => {
+                let mut units = 0;
+                let mut found = None;
+                for (idx, c) in text.char_indices().chain([(text.len(), '\0')]) {
+                    if units == pos.col {
+                        found = Some(idx);
+                        break;
+                    }
+                    units += c.len_utf16();
+                }
+                found
+            }
+        }?;
+        Some(span.start + col)
+    }
+
+    // line_span Byte range of a line without its terminator
+    pub fn line_span(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.starts.get(line)?;
+        let end = self
+            .starts
+            .get(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+        let text = &self.source[start..end];
+        Some(start..start + text.strip_suffix('\r').unwrap_or(text).len())
+    }
+
+    /// Render a message with the source line of `span` and a caret marker under it.
+    ///
+    /// ```text
+    /// error: Invalid token "@" at 12..13
+    ///  --> 2:6
+    ///   |
+    /// 2 |   "a": @,
+    ///   |        ^
+    /// ```
+    ///
+    /// The reported column counts characters from 1. A span running over several lines is
+    /// marked up to the end of its first line.
+    pub fn render(&self, span: Range<usize>, message: &str) -> String {
+        let pos = self.line_col(span.start, ColumnUnit::Utf8);
+        let line = self.line_span(pos.line).unwrap_or_default();
+        let text = &self.source[line.clone()];
+        let before = text[..pos.col].chars().count();
+        let marked = self.source[span.start.min(line.end)..span.end.clamp(line.start, line.end)]
+            .chars()
+            .count()
+            .max(1);
+        let number = format!("{}", pos.line + 1);
+        let gutter = " ".repeat(number.len());
+
+        let mut out = String::new();
+        let _ = writeln!(out, "error: {message}");
+        let _ = writeln!(out, "{gutter}--> {}:{}", pos.line + 1, before + 1);
+        let _ = writeln!(out, "{gutter} |");
+        let _ = writeln!(out, "{number} | {text}");
+        let _ = write!(
+            out,
+            "{gutter} | {}{}",
+            " ".repeat(before),
+            "^".repeat(marked)
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnUnit, LineCol, LineIndex};
+
+    #[test]
+    fn it_line_col() {
+        let source = "{\r\n  \"é😀\": 1,\n  \"b\": @\n}";
+        let index = LineIndex::new(source);
+        let at = source.find('@').unwrap();
+        let one = source.find('1').unwrap();
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(
+            index.line_col(0, ColumnUnit::Utf8),
+            LineCol { line: 0, col: 0 }
+        );
+        assert_eq!(
+            index.line_col(at, ColumnUnit::Utf8),
+            LineCol { line: 2, col: 7 }
+        );
+        assert_eq!(
+            index.line_col(one, ColumnUnit::Utf8),
+            LineCol { line: 1, col: 12 }
+        );
+        assert_eq!(
+            index.line_col(one, ColumnUnit::Utf16),
+            LineCol { line: 1, col: 9 }
+        );
+        assert_eq!(
+            index.line_col(one - 5, ColumnUnit::Utf8),
+            LineCol { line: 1, col: 5 }
+        );
+        assert_eq!(
+            index.line_col(usize::MAX, ColumnUnit::Utf8),
+            LineCol { line: 3, col: 1 }
+        );
+
+        for unit in [ColumnUnit::Utf8, ColumnUnit::Utf16] {
+            for offset in [0, at, one, source.len()] {
+                assert_eq!(
+                    index.offset(index.line_col(offset, unit), unit),
+                    Some(offset)
+                );
+            }
+        }
+        assert_eq!(
+            index.offset(LineCol { line: 1, col: 5 }, ColumnUnit::Utf16),
+            None
+        );
+        assert_eq!(
+            index.offset(LineCol { line: 0, col: 2 }, ColumnUnit::Utf8),
+            None
+        );
+        assert_eq!(index.line_span(0), Some(0..1));
+        assert_eq!(index.line_span(4), None);
+    }
+
+    #[test]
+    fn it_render() {
+        let source = "{\n  \"a\": tru,\n  \"b\": 1\n}";
+        let start = source.find("tru").unwrap();
+        let index = LineIndex::new(source);
+
+        assert_eq!(
+            index.render(start..start + 3, "Invalid token \"tru\""),
+            "error: Invalid token \"tru\"\n --> 2:8\n  |\n2 |   \"a\": tru,\n  |        ^^^"
+        );
+        assert!(index
+            .render(source.len()..source.len(), "Unexpected end of input")
+            .ends_with("4 | }\n  |  ^"));
+    }
+}
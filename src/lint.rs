@@ -0,0 +1,421 @@
+//! Style and portability checks over the recovering tokenizer, with source spans.
+
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    token::{tokenize_recovering, JsonToken, JsonTokenKind},
+    value::unescape,
+};
+
+/// How much a finding matters; ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl core::fmt::Display for Severity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A check performed by [`lint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// Input the lexer cannot make sense of.
+    InvalidToken,
+    /// A key repeated within one object; most parsers keep only the last value.
+    DuplicateKey,
+    /// A number that does not survive the round trip through `f64`.
+    LossyNumber,
+    /// An array whose elements are not all of one type.
+    MixedArray,
+    /// Containers nested deeper than [`LintOptions::max_depth`].
+    DeepNesting,
+    /// A key with characters outside of ASCII.
+    NonAsciiKey,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [
+        Rule::InvalidToken,
+        Rule::DuplicateKey,
+        Rule::LossyNumber,
+        Rule::MixedArray,
+        Rule::DeepNesting,
+        Rule::NonAsciiKey,
+    ];
+
+    // id Stable identifier of the rule, for configuration files and editor integrations
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::InvalidToken => "invalid-token",
+            Rule::DuplicateKey => "duplicate-key",
+            Rule::LossyNumber => "lossy-number",
+            Rule::MixedArray => "mixed-array",
+            Rule::DeepNesting => "deep-nesting",
+            Rule::NonAsciiKey => "non-ascii-key",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Rule::InvalidToken => Severity::Error,
+            Rule::DuplicateKey | Rule::LossyNumber | Rule::DeepNesting => Severity::Warning,
+            Rule::MixedArray | Rule::NonAsciiKey => Severity::Info,
+        }
+    }
+}
+
+/// Which rules run and how severe their findings are.
+///
+/// Every rule starts enabled at its [`Rule::default_severity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintOptions {
+    pub max_depth: usize,
+    levels: [Option<Severity>; Rule::ALL.len()],
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions {
+            max_depth: 32,
+            levels: Rule::ALL.map(|rule| Some(rule.default_severity())),
+        }
+    }
+}
+
+impl LintOptions {
+    pub fn new() -> Self {
+        LintOptions::default()
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // severity Report findings of `rule` at `severity`
+    pub fn severity(mut self, rule: Rule, severity: Severity) -> Self {
+        self.levels[rule as usize] = Some(severity);
+        self
+    }
+
+    pub fn disable(mut self, rule: Rule) -> Self {
+        self.levels[rule as usize] = None;
+        self
+    }
+
+    // level Severity of `rule`, or `None` when it is disabled
+    pub fn level(&self, rule: Rule) -> Option<Severity> {
+        self.levels[rule as usize]
+    }
+}
+
+/// One finding, spanning the bytes of the source it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+// lint Check `source` with every rule at its default severity
+pub fn lint(source: &str) -> Vec<Lint> {
+    lint_with(source, &LintOptions::default())
+}
+
+/// Check `source` against the enabled rules, in source order.
+///
+/// Invalid input does not stop the checks: the rest of the document is still linted
+/// as far as its structure can be followed.
+pub fn lint_with(source: &str, options: &LintOptions) -> Vec<Lint> {
+    let mut linter = Linter {
+        options,
+        lints: Vec::new(),
+        stack: Vec::new(),
+    };
+    for token in tokenize_recovering(source) {
+        linter.token(&token);
+    }
+    linter.lints
+}
+
+struct Linter<'o> {
+    options: &'o LintOptions,
+    lints: Vec<Lint>,
+    stack: Vec<Frame>,
+}
+
+enum Frame {
+    Object {
+        keys: BTreeSet<String>,
+        // After `{` or `,`, where a string is a key
+        expect_key: bool,
+    },
+    Array {
+        // Type of the first element, and whether a different one was reported
+        first: Option<&'static str>,
+        mixed: bool,
+    },
+}
+
+impl Linter<'_> {
+    fn token(&mut self, token: &JsonToken) {
+        let span = token.span.clone();
+        match &token.kind {
+            JsonTokenKind::Error => {
+                self.report(
+                    Rule::InvalidToken,
+                    format!("Invalid token {:?}", token.text()),
+                    span,
+                );
+                return;
+            }
+            JsonTokenKind::CloseBrace | JsonTokenKind::CloseBracket => {
+                self.stack.pop();
+                return;
+            }
+            JsonTokenKind::Colon => {
+                if let Some(Frame::Object { expect_key, .. }) = self.stack.last_mut() {
+                    *expect_key = false;
+                }
+                return;
+            }
+            JsonTokenKind::Comma => {
+                if let Some(Frame::Object { expect_key, .. }) = self.stack.last_mut() {
+                    *expect_key = true;
+                }
+                return;
+            }
+            JsonTokenKind::Whitespace => return,
+            JsonTokenKind::String => {
+                if let Some(Frame::Object {
+                    expect_key: true, ..
+                }) = self.stack.last()
+                {
+                    self.key(token.text(), span);
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        self.element(type_name(&token.kind), span.clone());
+        match &token.kind {
+            JsonTokenKind::OpenBrace | JsonTokenKind::OpenBracket => {
+                self.stack.push(match token.kind {
+                    JsonTokenKind::OpenBrace => Frame::Object {
+                        keys: BTreeSet::new(),
+                        expect_key: true,
+                    },
+                    _ => Frame::Array {
+                        first: None,
+                        mixed: false,
+                    },
+                });
+                if self.stack.len() == self.options.max_depth + 1 {
+                    let message = format!("Nesting deeper than {} levels", self.options.max_depth);
+                    self.report(Rule::DeepNesting, message, span);
+                }
+            }
+            JsonTokenKind::Number(n) if is_lossy(token.text(), *n) => {
+                let message = format!("Number {} does not fit an f64 exactly", token.text());
+                self.report(Rule::LossyNumber, message, span);
+            }
+            _ => {}
+        }
+    }
+
+    fn key(&mut self, raw: &str, span: Range<usize>) {
+        let inner = &raw[1..raw.len() - 1];
+        let key = unescape(inner).unwrap_or_else(|_| String::from(inner));
+        if !key.is_ascii() {
+            self.report(
+                Rule::NonAsciiKey,
+                format!("Key {key:?} is not ASCII"),
+                span.clone(),
+            );
+        }
+        let Some(Frame::Object { keys, .. }) = self.stack.last_mut() else {
+            return;
+        };
+        if !keys.insert(key.clone()) {
+            self.report(Rule::DuplicateKey, format!("Duplicate key {key:?}"), span);
+        }
+    }
+
+    // element A value starts: check it against the other elements of an enclosing array
+    fn element(&mut self, kind: &'static str, span: Range<usize>) {
+        let Some(Frame::Array { first, mixed }) = self.stack.last_mut() else {
+            return;
+        };
+        match first {
+            None => *first = Some(kind),
+            Some(first) if !*mixed && *first != kind => {
+                *mixed = true;
+                let message = format!("Array mixes {first} and {kind} elements");
+                self.report(Rule::MixedArray, message, span);
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn report(&mut self, rule: Rule, message: String, span: Range<usize>) {
+        if let Some(severity) = self.options.level(rule) {
+            self.lints.push(Lint {
+                rule,
+                severity,
+                message,
+                span,
+            });
+        }
+    }
+}
+
+fn type_name(kind: &JsonTokenKind) -> &'static str {
+    match kind {
+        JsonTokenKind::OpenBrace => "object",
+        JsonTokenKind::OpenBracket => "array",
+        JsonTokenKind::True | JsonTokenKind::False => "boolean",
+        JsonTokenKind::Null => "null",
+        JsonTokenKind::Number(_) => "number",
+        JsonTokenKind::String => "string",
+        JsonTokenKind::Custom(tag) => tag,
+        _ => "invalid",
+    }
+}
+
+// is_lossy Whether the decimal literal names a different number than the f64 it parsed to
+fn is_lossy(literal: &str, n: f64) -> bool {
+    let unsigned = literal.trim_start_matches('-');
+    if !unsigned.starts_with(|c: char| c.is_ascii_digit()) || unsigned.contains(['x', 'X']) {
+        // hex and non-finite literals name exactly the value they stand for
+        return false;
+    }
+    if !n.is_finite() {
+        return true;
+    }
+    significant(literal) != significant(&format!("{:e}", n.abs()))
+}
+
+// significant The significant digits of a decimal literal and the exponent of the first
+fn significant(literal: &str) -> (String, i64) {
+    let literal = literal.trim_start_matches('-');
+    let (mantissa, exp) = match literal.split_once(['e', 'E']) {
+        Some((mantissa, exp)) => (mantissa, exp.parse::<i64>().unwrap_or(0)),
+        None => (literal, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{frac}");
+    let leading = digits.len() - digits.trim_start_matches('0').len();
+    let digits = digits.trim_matches('0');
+    if digits.is_empty() {
+        return (String::new(), 0);
+    }
+    let exp = exp + int.len() as i64 - 1 - leading as i64;
+    (String::from(digits), exp)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{is_lossy, lint, lint_with, LintOptions, Rule, Severity};
+
+    fn rules<'a>(source: &'a str, options: &LintOptions) -> Vec<(Rule, &'a str)> {
+        lint_with(source, options)
+            .into_iter()
+            .map(|l| (l.rule, &source[l.span]))
+            .collect()
+    }
+
+    #[test]
+    fn it_lint() {
+        let source =
+            r#"{"a": 1, "b": [1, "x", null], "a": 2, "café": 12345678901234567890, "c": [[]]}"#;
+        let lints = lint(source);
+        let found = lints
+            .iter()
+            .map(|l| (l.rule.id(), l.severity, &source[l.span.clone()]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("mixed-array", Severity::Info, r#""x""#),
+                ("duplicate-key", Severity::Warning, r#""a""#),
+                ("non-ascii-key", Severity::Info, r#""café""#),
+                ("lossy-number", Severity::Warning, "12345678901234567890"),
+            ]
+        );
+        assert_eq!(lints[1].message, r#"Duplicate key "a""#);
+
+        let options = LintOptions::new()
+            .max_depth(1)
+            .disable(Rule::MixedArray)
+            .severity(Rule::NonAsciiKey, Severity::Error);
+        assert_eq!(
+            rules(source, &options),
+            vec![
+                (Rule::DeepNesting, "["),
+                (Rule::DuplicateKey, r#""a""#),
+                (Rule::NonAsciiKey, r#""café""#),
+                (Rule::LossyNumber, "12345678901234567890"),
+                (Rule::DeepNesting, "["),
+            ]
+        );
+        assert_eq!(options.level(Rule::NonAsciiKey), Some(Severity::Error));
+        assert_eq!(Rule::from_id("deep-nesting"), Some(Rule::DeepNesting));
+    }
+
+    #[test]
+    fn it_lint_recovers_from_invalid_input() {
+        let source = r#"{"k": tru, "k": [1, @@], "k": 0}"#;
+        assert_eq!(
+            rules(source, &LintOptions::new()),
+            vec![
+                (Rule::InvalidToken, "tru"),
+                (Rule::DuplicateKey, r#""k""#),
+                (Rule::InvalidToken, "@@"),
+                (Rule::DuplicateKey, r#""k""#),
+            ]
+        );
+        assert!(lint("[1, 2.5, -0, 1e3]").is_empty());
+    }
+
+    #[test]
+    fn it_lossy_numbers() {
+        for exact in [
+            "0",
+            "-0.0",
+            "1.5",
+            "9007199254740992",
+            "1e300",
+            "0.1",
+            "100",
+            "1.50",
+        ] {
+            assert!(!is_lossy(exact, exact.parse().unwrap()), "{exact}");
+        }
+        for lossy in [
+            "9007199254740993",
+            "0.10000000000000000001",
+            "1e400",
+            "123456789012345678901234",
+        ] {
+            assert!(is_lossy(lossy, lossy.parse().unwrap()), "{lossy}");
+        }
+    }
+}
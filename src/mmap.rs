@@ -0,0 +1,108 @@
+//! Memory-mapped documents, behind the `mmap` feature.
+
+use std::{fs::File, path::Path};
+
+use memmap2::Mmap;
+
+use crate::{
+    error::{ErrorKind, JError},
+    BorrowedValue,
+};
+
+/// A document parsed in place over a read-only mapping of its file.
+///
+/// Strings without escapes borrow straight from the mapping, so the text is never
+/// copied into the heap. The file must not be modified while the document is alive.
+#[derive(Debug)]
+pub struct MappedDocument {
+    // Declared first so it is dropped before the mapping it borrows from
+    value: BorrowedValue<'static>,
+    map: Mmap,
+}
+
+impl MappedDocument {
+    // value The parsed document, borrowing from the mapping
+    pub fn value(&self) -> &BorrowedValue<'_> {
+        &self.value
+    }
+
+    // source The mapped text
+    pub fn source(&self) -> &str {
+        // Checked to be UTF-8 before the document was parsed
+        core::str::from_utf8(&self.map).unwrap_or_default()
+    }
+}
+
+/// Map the file at `path` and parse it with [`crate::parse_borrowed`].
+///
+/// The file has to be UTF-8; a leading byte order mark is skipped.
+pub fn parse_file_mmap(path: impl AsRef<Path>) -> Result<MappedDocument, JError> {
+    let path = path.as_ref();
+    let io_error = |e: std::io::Error| {
+        JError::new(
+            ErrorKind::Io,
+            format!("Failed to map {}: {e}", path.display()),
+        )
+    };
+    let file = File::open(path).map_err(io_error)?;
+    // SAFETY: the mapping is read-only; like any mmap-based reader, the caller must keep
+    // other processes from truncating or rewriting the file while it is mapped.
+    let map = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+
+    let value = {
+        let source = core::str::from_utf8(&map).map_err(|e| {
+            JError::new(
+                ErrorKind::Encoding,
+                format!("Invalid UTF-8 at byte {}", e.valid_up_to()),
+            )
+        })?;
+        // SAFETY: the mapped bytes live on after `map` is moved into the document and are
+        // only unmapped when it is dropped, after `value` (see the field order).
+        let source: &'static str = unsafe { &*(source as *const str) };
+        crate::parse_borrowed(source)?
+    };
+    Ok(MappedDocument { value, map })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{BorrowedValue, ErrorKind};
+
+    use super::parse_file_mmap;
+
+    #[test]
+    fn it_parse_file_mmap() {
+        let dir = std::env::temp_dir().join(format!("jnom-mmap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("doc.json");
+        std::fs::write(&path, "\u{feff}{\"name\": \"plain\", \"esc\": \"a\\nb\"}").unwrap();
+
+        let doc = parse_file_mmap(&path).unwrap();
+        let BorrowedValue::Object(members) = doc.value() else {
+            panic!("expected an object");
+        };
+        assert!(matches!(
+            members.get("name"),
+            Some(BorrowedValue::String(Cow::Borrowed("plain")))
+        ));
+        assert!(matches!(
+            members.get("esc"),
+            Some(BorrowedValue::String(Cow::Owned(s))) if s == "a\nb"
+        ));
+        assert!(doc.source().ends_with("\"a\\nb\"}"));
+
+        std::fs::write(&path, b"[\"\xff\"]").unwrap();
+        let err = parse_file_mmap(&path).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::Encoding);
+
+        std::fs::write(&path, "[1,").unwrap();
+        assert!(parse_file_mmap(&path).is_err());
+
+        let missing = parse_file_mmap(dir.join("missing.json")).err().unwrap();
+        assert_eq!(missing.kind(), ErrorKind::Io);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
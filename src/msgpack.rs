@@ -0,0 +1,385 @@
+use alloc::{borrow::Cow, format, vec::Vec};
+
+use crate::{
+    error::{ErrorKind, JError},
+    event::{JsonEvent, ValueBuilder},
+    JsonValue, NumberMode,
+};
+
+const MAX_DEPTH: usize = 512;
+
+// Extension type holding the literal text of a number too wide for an f64
+const RAW_NUMBER_EXT: i8 = 1;
+
+// to_msgpack Encode the value as MessagePack
+pub fn to_msgpack(value: &JsonValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    for event in value.events() {
+        match event {
+            JsonEvent::StartObject(len) => {
+                let len = len.expect("value events carry container lengths");
+                write_len(&mut out, len, 0x80, 0xde);
+            }
+            JsonEvent::StartArray(len) => {
+                let len = len.expect("value events carry container lengths");
+                write_len(&mut out, len, 0x90, 0xdc);
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {}
+            JsonEvent::Key(s) | JsonEvent::String(s) => {
+                match s.len() {
+                    len @ 0..=31 => out.push(0xa0 | len as u8),
+                    len @ 32..=0xff => out.extend_from_slice(&[0xd9, len as u8]),
+                    len @ 0x100..=0xffff => {
+                        out.push(0xda);
+                        out.extend_from_slice(&(len as u16).to_be_bytes());
+                    }
+                    len => {
+                        out.push(0xdb);
+                        out.extend_from_slice(&(len as u32).to_be_bytes());
+                    }
+                }
+                out.extend_from_slice(s.as_bytes());
+            }
+            JsonEvent::StringChunk(_) | JsonEvent::StringEnd => {
+                unreachable!("value events never split strings")
+            }
+            JsonEvent::Number(n) => write_number(&mut out, n),
+            JsonEvent::RawNumber(literal) => {
+                match literal.len() {
+                    len @ 0..=0xff => out.extend_from_slice(&[0xc7, len as u8]),
+                    len @ 0x100..=0xffff => {
+                        out.push(0xc8);
+                        out.extend_from_slice(&(len as u16).to_be_bytes());
+                    }
+                    len => {
+                        out.push(0xc9);
+                        out.extend_from_slice(&(len as u32).to_be_bytes());
+                    }
+                }
+                out.push(RAW_NUMBER_EXT as u8);
+                out.extend_from_slice(literal.as_bytes());
+            }
+            JsonEvent::Bool(false) => out.push(0xc2),
+            JsonEvent::Bool(true) => out.push(0xc3),
+            JsonEvent::Null => out.push(0xc0),
+        }
+    }
+    out
+}
+
+// write_len Write a container header: the fix form up to 15 entries, then the 16/32 bit forms
+fn write_len(out: &mut Vec<u8>, len: usize, fix: u8, wide: u8) {
+    match len {
+        0..=15 => out.push(fix | len as u8),
+        16..=0xffff => {
+            out.push(wide);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(wide + 1);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, n: f64) {
+    // integral values in the exactly representable range are written as integers
+    let exact = n.abs() <= 9_007_199_254_740_992.0 && n == (n as i64) as f64;
+    if exact && n.is_sign_positive() {
+        match n as u64 {
+            i @ 0..=0x7f => out.push(i as u8),
+            i @ 0x80..=0xff => out.extend_from_slice(&[0xcc, i as u8]),
+            i @ 0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend_from_slice(&(i as u16).to_be_bytes());
+            }
+            i @ 0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend_from_slice(&(i as u32).to_be_bytes());
+            }
+            i => {
+                out.push(0xcf);
+                out.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+    } else if exact && n < 0.0 {
+        match n as i64 {
+            i @ -32..=-1 => out.push(i as u8),
+            i @ -0x80..=-33 => out.extend_from_slice(&[0xd0, i as u8]),
+            i @ -0x8000..=-0x81 => {
+                out.push(0xd1);
+                out.extend_from_slice(&(i as i16).to_be_bytes());
+            }
+            i @ -0x8000_0000..=-0x8001 => {
+                out.push(0xd2);
+                out.extend_from_slice(&(i as i32).to_be_bytes());
+            }
+            i => {
+                out.push(0xd3);
+                out.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+    } else {
+        out.push(0xcb);
+        out.extend_from_slice(&n.to_bits().to_be_bytes());
+    }
+}
+
+// from_msgpack Decode a single MessagePack object into a value
+//
+// Binary and extension types have no JSON counterpart and are rejected,
+// as are map keys that are not strings; the one exception is the extension
+// `to_msgpack` writes for numbers wider than an f64.
+pub fn from_msgpack(bytes: &[u8]) -> Result<JsonValue, JError> {
+    from_msgpack_with(bytes, NumberMode::default())
+}
+
+// from_msgpack_with Decode like `from_msgpack`, storing numbers wider than an f64 as `mode` asks
+pub fn from_msgpack_with(bytes: &[u8], mode: NumberMode) -> Result<JsonValue, JError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        builder: ValueBuilder::with_number_mode(mode),
+    };
+    decoder.item(0)?;
+    if decoder.pos != bytes.len() {
+        return Err(JError::new(
+            ErrorKind::Syntax,
+            format!(
+                "Unexpected trailing MessagePack data at byte {}",
+                decoder.pos
+            ),
+        ));
+    }
+    decoder.builder.finish()
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    builder: ValueBuilder,
+}
+
+impl<'a> Decoder<'a> {
+    fn item(&mut self, depth: usize) -> Result<(), JError> {
+        if depth > MAX_DEPTH {
+            return Err(JError::new(
+                ErrorKind::Unsupported,
+                "MessagePack nesting is too deep",
+            ));
+        }
+        let at = self.pos;
+        let event = match self.byte()? {
+            b @ 0x00..=0x7f => JsonEvent::Number(b as f64),
+            b @ 0x80..=0x8f => return self.map((b & 0x0f) as usize, depth),
+            b @ 0x90..=0x9f => return self.array((b & 0x0f) as usize, depth),
+            b @ 0xa0..=0xbf => JsonEvent::String(Cow::Borrowed(self.text((b & 0x1f) as usize)?)),
+            0xc0 => JsonEvent::Null,
+            0xc2 => JsonEvent::Bool(false),
+            0xc3 => JsonEvent::Bool(true),
+            0xc7 => {
+                let len = self.byte()? as usize;
+                self.raw_number(len, at)?
+            }
+            0xc8 => {
+                let len = u16::from_be_bytes(self.array_of()?) as usize;
+                self.raw_number(len, at)?
+            }
+            0xc9 => {
+                let len = u32::from_be_bytes(self.array_of()?) as usize;
+                self.raw_number(len, at)?
+            }
+            0xca => JsonEvent::Number(f32::from_be_bytes(self.array_of()?) as f64),
+            0xcb => JsonEvent::Number(f64::from_be_bytes(self.array_of()?)),
+            0xcc => JsonEvent::Number(self.byte()? as f64),
+            0xcd => JsonEvent::Number(u16::from_be_bytes(self.array_of()?) as f64),
+            0xce => JsonEvent::Number(u32::from_be_bytes(self.array_of()?) as f64),
+            0xcf => JsonEvent::Number(u64::from_be_bytes(self.array_of()?) as f64),
+            0xd0 => JsonEvent::Number(self.byte()? as i8 as f64),
+            0xd1 => JsonEvent::Number(i16::from_be_bytes(self.array_of()?) as f64),
+            0xd2 => JsonEvent::Number(i32::from_be_bytes(self.array_of()?) as f64),
+            0xd3 => JsonEvent::Number(i64::from_be_bytes(self.array_of()?) as f64),
+            0xd9 => {
+                let len = self.byte()? as usize;
+                JsonEvent::String(Cow::Borrowed(self.text(len)?))
+            }
+            0xda => {
+                let len = u16::from_be_bytes(self.array_of()?) as usize;
+                JsonEvent::String(Cow::Borrowed(self.text(len)?))
+            }
+            0xdb => {
+                let len = u32::from_be_bytes(self.array_of()?) as usize;
+                JsonEvent::String(Cow::Borrowed(self.text(len)?))
+            }
+            0xdc => {
+                let len = u16::from_be_bytes(self.array_of()?) as usize;
+                return self.array(len, depth);
+            }
+            0xdd => {
+                let len = u32::from_be_bytes(self.array_of()?) as usize;
+                return self.array(len, depth);
+            }
+            0xde => {
+                let len = u16::from_be_bytes(self.array_of()?) as usize;
+                return self.map(len, depth);
+            }
+            0xdf => {
+                let len = u32::from_be_bytes(self.array_of()?) as usize;
+                return self.map(len, depth);
+            }
+            b @ 0xe0..=0xff => JsonEvent::Number(b as i8 as f64),
+            b => {
+                return Err(JError::new(
+                    ErrorKind::Unsupported,
+                    format!("Unsupported MessagePack type 0x{b:02x} at byte {at}"),
+                ))
+            }
+        };
+        self.builder.push(event)
+    }
+
+    // raw_number Read the body of an extension, which must carry a number literal
+    fn raw_number(&mut self, len: usize, at: usize) -> Result<JsonEvent<'a>, JError> {
+        let ext = self.byte()? as i8;
+        if ext != RAW_NUMBER_EXT {
+            return Err(JError::new(
+                ErrorKind::Unsupported,
+                format!("Unsupported MessagePack extension type {ext} at byte {at}"),
+            ));
+        }
+        Ok(JsonEvent::RawNumber(Cow::Borrowed(self.text(len)?)))
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<(), JError> {
+        self.builder.push(JsonEvent::StartArray(None))?;
+        for _ in 0..len {
+            self.item(depth + 1)?;
+        }
+        self.builder.push(JsonEvent::EndArray)
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<(), JError> {
+        self.builder.push(JsonEvent::StartObject(None))?;
+        for _ in 0..len {
+            let at = self.pos;
+            let len = match self.byte()? {
+                b @ 0xa0..=0xbf => (b & 0x1f) as usize,
+                0xd9 => self.byte()? as usize,
+                0xda => u16::from_be_bytes(self.array_of()?) as usize,
+                0xdb => u32::from_be_bytes(self.array_of()?) as usize,
+                _ => {
+                    return Err(JError::new(
+                        ErrorKind::Syntax,
+                        format!("Map keys must be strings at byte {at}"),
+                    ))
+                }
+            };
+            let key = self.text(len)?;
+            self.builder.push(JsonEvent::Key(Cow::Borrowed(key)))?;
+            self.item(depth + 1)?;
+        }
+        self.builder.push(JsonEvent::EndObject)
+    }
+
+    fn byte(&mut self) -> Result<u8, JError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array_of<const N: usize>(&mut self) -> Result<[u8; N], JError> {
+        Ok(self.take(N)?.try_into().expect("take returns N bytes"))
+    }
+
+    fn text(&mut self, len: usize) -> Result<&'a str, JError> {
+        let at = self.pos;
+        core::str::from_utf8(self.take(len)?).map_err(|_| {
+            JError::new(
+                ErrorKind::Encoding,
+                format!("Invalid UTF-8 in string at byte {at}"),
+            )
+        })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], JError> {
+        match self.bytes.get(self.pos..self.pos.saturating_add(len)) {
+            Some(slice) => {
+                self.pos += len;
+                Ok(slice)
+            }
+            None => Err(JError::new(
+                ErrorKind::Eof,
+                "Unexpected end of MessagePack data",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::{from_msgpack, to_msgpack};
+
+    #[test]
+    fn it_msgpack_round_trip() {
+        let value = parse(
+            r#"{"a": 1, "b": [-10, -200, 1.5, 300, 70000], "c": "杭州", "d": null, "e": true}"#,
+        )
+        .unwrap();
+        let bytes = to_msgpack(&value);
+
+        assert_eq!(&bytes[..4], &[0x85, 0xa1, b'a', 0x01]);
+        assert_eq!(from_msgpack(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn it_msgpack_encode_integers() {
+        let value = parse("[127, 128, -32, -33, 65536]").unwrap();
+
+        assert_eq!(
+            to_msgpack(&value),
+            vec![0x95, 0x7f, 0xcc, 0x80, 0xe0, 0xd0, 0xdf, 0xce, 0x00, 0x01, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn it_msgpack_rejects_invalid_input() {
+        assert!(from_msgpack(&[0x92, 0x01]).is_err());
+        assert!(from_msgpack(&[0xc4, 0x01, 0x00]).is_err());
+        assert!(from_msgpack(&[0x81, 0x01, 0x02]).is_err());
+        assert!(from_msgpack(&[0xa2, 0xff, 0xfe]).is_err());
+        assert!(from_msgpack(&[0x01, 0x02]).is_err());
+        assert!(from_msgpack(&[0xc7, 0x01, 0x02, b'1']).is_err());
+        assert!(from_msgpack(&[0xc7, 0x01, 0x01, b'x']).is_err());
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn it_msgpack_big_integer_round_trip() {
+        let value = parse("[123456789012345678901234567890, -98765432109876543210]").unwrap();
+        let bytes = to_msgpack(&value);
+
+        assert_eq!(&bytes[..4], &[0x92, 0xc7, 30, 0x01]);
+        assert_eq!(from_msgpack(&bytes).unwrap(), value);
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn it_msgpack_decimal_round_trip() {
+        use crate::{parse_with, NumberMode, ParseOptions};
+
+        use super::from_msgpack_with;
+
+        let options = ParseOptions::new().number_mode(NumberMode::Decimal);
+        let value = parse_with("[0.10, -2.5e-3]", &options).unwrap();
+        let bytes = to_msgpack(&value);
+
+        assert_eq!(
+            from_msgpack_with(&bytes, NumberMode::Decimal).unwrap(),
+            value
+        );
+        assert_eq!(
+            from_msgpack(&bytes).unwrap(),
+            parse("[0.1, -0.0025]").unwrap()
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use crate::{JsonValue, Map};
+
+/// Cleanups applied by [`JsonValue::normalize`], all off by default.
+///
+/// They run bottom-up, so a parent sees its children already normalized: two elements
+/// differing only in key order or string padding collapse into one under
+/// `dedupe_arrays` once `trim_strings` has run on them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    pub sort_keys: bool,
+    pub drop_nulls: bool,
+    pub dedupe_arrays: bool,
+    pub trim_strings: bool,
+}
+
+impl NormalizeOptions {
+    pub fn new() -> Self {
+        NormalizeOptions::default()
+    }
+
+    /// Order object members by key.
+    ///
+    /// The `sorted_map` backend is always in key order and the unordered `hash_map`
+    /// backend has no order to change, so this only affects the default map.
+    pub fn sort_keys(mut self) -> Self {
+        self.sort_keys = true;
+        self
+    }
+
+    // drop_nulls Remove object members whose value is `null`; array elements are kept
+    pub fn drop_nulls(mut self) -> Self {
+        self.drop_nulls = true;
+        self
+    }
+
+    // dedupe_arrays Keep only the first of equal array elements
+    pub fn dedupe_arrays(mut self) -> Self {
+        self.dedupe_arrays = true;
+        self
+    }
+
+    // trim_strings Strip leading and trailing whitespace from string values (not keys)
+    pub fn trim_strings(mut self) -> Self {
+        self.trim_strings = true;
+        self
+    }
+}
+
+impl JsonValue {
+    // normalize Copy of the value with the cleanups chosen in `options` applied
+    pub fn normalize(&self, options: &NormalizeOptions) -> JsonValue {
+        match self {
+            JsonValue::String(s) if options.trim_strings => JsonValue::String(s.trim().into()),
+            JsonValue::Array(arr) => {
+                let items = arr.iter().map(|item| item.normalize(options));
+                match options.dedupe_arrays {
+                    true => JsonValue::Array(dedupe(items)),
+                    false => JsonValue::Array(items.collect()),
+                }
+            }
+            JsonValue::Object(obj) => {
+                #[allow(unused_mut)]
+                let mut out = obj
+                    .iter()
+                    .filter(|(_, value)| !(options.drop_nulls && value.is_null()))
+                    .map(|(key, value)| (key.clone(), value.normalize(options)))
+                    .collect::<Map<String, JsonValue>>();
+                #[cfg(not(any(feature = "sorted_map", feature = "hash_map")))]
+                if options.sort_keys {
+                    out.sort_keys();
+                }
+                JsonValue::Object(out)
+            }
+            other => other.clone(),
+        }
+    }
+}
+
+// dedupe Drop elements equal to an earlier one, bucketing by canonical digest
+fn dedupe(items: impl Iterator<Item = JsonValue>) -> Vec<JsonValue> {
+    let mut out = Vec::<JsonValue>::new();
+    let mut seen = BTreeMap::<u64, Vec<usize>>::new();
+    for item in items {
+        let bucket = seen.entry(item.canonical_digest()).or_default();
+        if bucket.iter().all(|idx| !out[*idx].deep_eq(&item)) {
+            bucket.push(out.len());
+            out.push(item);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::NormalizeOptions;
+
+    #[test]
+    fn it_normalize() {
+        let value = parse(
+            r#"{"b": [" x", {"k": 1, "j": null}, "x ", {"j": 2, "k": 1}, 1, 1.0], "a": null, "c": " y "}"#,
+        )
+        .unwrap();
+
+        assert_eq!(value.normalize(&NormalizeOptions::new()), value);
+
+        let all = NormalizeOptions::new()
+            .sort_keys()
+            .drop_nulls()
+            .dedupe_arrays()
+            .trim_strings();
+        let normalized = value.normalize(&all);
+        assert_eq!(
+            normalized,
+            parse(r#"{"b": ["x", {"k": 1}, {"j": 2, "k": 1}, 1], "c": "y"}"#).unwrap()
+        );
+        #[cfg(not(feature = "hash_map"))]
+        assert_eq!(
+            crate::ser::to_string(&normalized),
+            r#"{"b":["x",{"k":1},{"j":2,"k":1},1],"c":"y"}"#
+        );
+
+        let deduped = parse(r#"[{"a": 1, "b": 2}, {"b": 2, "a": 1}, [null], [null]]"#)
+            .unwrap()
+            .normalize(&NormalizeOptions::new().dedupe_arrays());
+        assert_eq!(deduped, parse(r#"[{"a": 1, "b": 2}, [null]]"#).unwrap());
+    }
+}
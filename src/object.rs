@@ -0,0 +1,238 @@
+use alloc::string::String;
+
+use crate::{JsonValue, Map};
+
+impl JsonValue {
+    // get Look up an object member by key
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(obj) => obj.get_mut(key),
+            _ => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Set an object member, returning the value it replaced.
+    ///
+    /// A new member goes last, an existing one keeps its position. `null` is turned
+    /// into an empty object first, so members can be added to a fresh value.
+    ///
+    /// # Panics
+    ///
+    /// If the value is neither an object nor `null`.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> Option<JsonValue> {
+        self.object_mut().insert(key.into(), value.into())
+    }
+
+    /// Set an object member and move it to position `index` (clamped to the end).
+    ///
+    /// Positions only exist for the default insertion-ordered map; with the
+    /// `sorted_map` or `hash_map` backends this is a plain [`insert`](Self::insert).
+    ///
+    /// # Panics
+    ///
+    /// If the value is neither an object nor `null`.
+    pub fn insert_at(
+        &mut self,
+        index: usize,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> Option<JsonValue> {
+        let obj = self.object_mut();
+        #[cfg(not(any(feature = "sorted_map", feature = "hash_map")))]
+        {
+            let (from, old) = obj.insert_full(key.into(), value.into());
+            obj.move_index(from, index.min(obj.len() - 1));
+            old
+        }
+        #[cfg(any(feature = "sorted_map", feature = "hash_map"))]
+        {
+            let _ = index;
+            obj.insert(key.into(), value.into())
+        }
+    }
+
+    /// Set an object member and place it right before the member `anchor`, or last
+    /// when there is no such member.
+    ///
+    /// Like [`insert_at`](Self::insert_at), ordering only applies to the default map.
+    ///
+    /// # Panics
+    ///
+    /// If the value is neither an object nor `null`.
+    pub fn insert_before(
+        &mut self,
+        anchor: &str,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> Option<JsonValue> {
+        let obj = self.object_mut();
+        #[cfg(not(any(feature = "sorted_map", feature = "hash_map")))]
+        {
+            let (from, old) = obj.insert_full(key.into(), value.into());
+            if let Some(to) = obj.get_index_of(anchor) {
+                obj.move_index(from, if from < to { to - 1 } else { to });
+            }
+            old
+        }
+        #[cfg(any(feature = "sorted_map", feature = "hash_map"))]
+        {
+            let _ = anchor;
+            obj.insert(key.into(), value.into())
+        }
+    }
+
+    // remove Remove an object member, keeping the order of the others
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry(&mut self, key: &str) -> Option<(String, JsonValue)> {
+        match self {
+            #[cfg(not(any(feature = "sorted_map", feature = "hash_map")))]
+            JsonValue::Object(obj) => obj.shift_remove_entry(key),
+            #[cfg(any(feature = "sorted_map", feature = "hash_map"))]
+            JsonValue::Object(obj) => obj.remove_entry(key),
+            _ => None,
+        }
+    }
+
+    // retain Keep only the object members for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &mut JsonValue) -> bool) {
+        if let JsonValue::Object(obj) = self {
+            obj.retain(|key, value| keep(key, value));
+        }
+    }
+
+    /// Entry for an object member, for in-place insertion or update.
+    ///
+    /// # Panics
+    ///
+    /// If the value is neither an object nor `null`.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        Entry {
+            map: self.object_mut(),
+            key: key.into(),
+        }
+    }
+
+    fn object_mut(&mut self) -> &mut Map<String, JsonValue> {
+        if self.is_null() {
+            *self = JsonValue::Object(Map::default());
+        }
+        match self {
+            JsonValue::Object(obj) => obj,
+            other => panic!("cannot set a member on a JSON {}", other.type_name()),
+        }
+    }
+}
+
+/// A member slot of an object, which may or may not be occupied yet.
+pub struct Entry<'a> {
+    map: &'a mut Map<String, JsonValue>,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn or_insert(self, default: impl Into<JsonValue>) -> &'a mut JsonValue {
+        self.or_insert_with(|| default.into())
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> JsonValue) -> &'a mut JsonValue {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key.clone(), default());
+        }
+        self.map
+            .get_mut(&self.key)
+            .expect("the member was just ensured")
+    }
+
+    // and_modify Update the member in place if it is already present
+    pub fn and_modify(self, f: impl FnOnce(&mut JsonValue)) -> Self {
+        if let Some(value) = self.map.get_mut(&self.key) {
+            f(value);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use crate::{parse, JsonValue};
+
+    #[test]
+    fn it_object_members() {
+        let mut value = JsonValue::Null;
+        value.insert("name", "John");
+        value.insert("age", 30);
+        assert_eq!(value.insert("age", 31), Some(JsonValue::from(30)));
+
+        *value.get_mut("name").unwrap() = "Jane".into();
+        *value.entry("visits").or_insert(0) = JsonValue::from(1);
+        value
+            .entry("visits")
+            .and_modify(|v| *v = JsonValue::from(v.as_f64().unwrap() + 1.0))
+            .or_insert(0);
+        value.entry("tags").or_insert_with(|| vec!["a"].into());
+
+        assert_eq!(
+            value,
+            parse(r#"{"name": "Jane", "age": 31, "visits": 2, "tags": ["a"]}"#).unwrap()
+        );
+        assert!(value.contains_key("tags"));
+        assert_eq!(value.get("missing"), None);
+
+        value.retain(|key, _| key != "tags");
+        assert_eq!(
+            value.remove_entry("age"),
+            Some(("age".to_string(), 31.into()))
+        );
+        assert_eq!(value.remove("age"), None);
+        assert_eq!(value.as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "sorted_map", feature = "hash_map")))]
+    fn it_ordered_insertion() {
+        use alloc::vec::Vec;
+
+        let mut value = parse(r#"{"a": 1, "b": 2, "c": 3}"#).unwrap();
+        value.insert_at(0, "z", 0);
+        value.insert_before("c", "a", 10);
+        value.insert_before("missing", "y", 9);
+        value.insert_at(99, "b", 20);
+        value.remove("z");
+
+        let keys = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["a", "c", "y", "b"]);
+        assert_eq!(value.get("a"), Some(&JsonValue::from(10)));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot set a member on a JSON array")]
+    fn it_insert_into_non_object_panics() {
+        JsonValue::from(vec![1]).insert("a", 1);
+    }
+}
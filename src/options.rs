@@ -0,0 +1,63 @@
+use crate::token::CustomLiteral;
+
+/// Knobs for [`parse_with`](crate::parse_with); the defaults follow RFC 8259 strictly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Accept raw control characters (U+0000 to U+001F) inside strings.
+    pub allow_control_chars: bool,
+    /// Accept the `NaN`, `Infinity` and `-Infinity` literals written by Python and others.
+    pub allow_non_finite: bool,
+    /// Accept hexadecimal integers such as `0xFF`, e.g. in hand-written configs.
+    /// Literals beyond 2^53 in magnitude are rejected as out of range.
+    pub allow_hex: bool,
+    /// Extra literals the lexer accepts as values, see [`CustomLiteral`].
+    pub custom_literals: &'static [CustomLiteral],
+    /// How numbers with a fraction or exponent are stored.
+    pub number_mode: NumberMode,
+}
+
+/// Representation of parsed numbers with a fraction or an exponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum NumberMode {
+    /// `f64`, the nearest double to the literal.
+    #[default]
+    Float,
+    /// An exact `rust_decimal::Decimal`, e.g. for monetary amounts; literals that a
+    /// decimal cannot hold exactly fail to parse.
+    #[cfg(feature = "rust_decimal")]
+    Decimal,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        ParseOptions::default()
+    }
+
+    // allow_control_chars Keep accepting unescaped control characters, for dirty inputs
+    pub fn allow_control_chars(mut self) -> Self {
+        self.allow_control_chars = true;
+        self
+    }
+
+    pub fn allow_non_finite(mut self) -> Self {
+        self.allow_non_finite = true;
+        self
+    }
+
+    pub fn allow_hex(mut self) -> Self {
+        self.allow_hex = true;
+        self
+    }
+
+    // custom_literals Recognize `literals` as values, e.g. for a JSON superset with dates
+    pub fn custom_literals(mut self, literals: &'static [CustomLiteral]) -> Self {
+        self.custom_literals = literals;
+        self
+    }
+
+    pub fn number_mode(mut self, mode: NumberMode) -> Self {
+        self.number_mode = mode;
+        self
+    }
+}
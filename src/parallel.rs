@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use rayon::prelude::*;
+
+use crate::{
+    error::{ErrorKind, JError},
+    parse,
+    token::{lex_error, lexer, JsonTokenKind},
+    JsonValue,
+};
+
+/// Elements handed to a rayon task at a time, so tiny elements don't drown in
+/// scheduling overhead.
+const MIN_ELEMENTS_PER_TASK: usize = 64;
+
+// parse_parallel Parse a document, building the elements of a top-level array in parallel
+//
+// The source is lexed once to find the top-level element boundaries, then every element
+// is parsed on the rayon pool and the results are collected in source order. Documents
+// whose root is not an array are parsed as usual.
+pub fn parse_parallel(source: &str) -> Result<JsonValue, JError> {
+    match split_top_level(source)? {
+        Some(spans) => spans
+            .into_par_iter()
+            .with_min_len(MIN_ELEMENTS_PER_TASK)
+            .map(|span| {
+                parse(&source[span.clone()]).map_err(|e| {
+                    JError::new(
+                        e.kind(),
+                        format!(
+                            "{} (in the array element at {}..{})",
+                            e.message(),
+                            span.start,
+                            span.end
+                        ),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(JsonValue::Array),
+        None => parse(source),
+    }
+}
+
+// split_top_level Byte ranges of the elements of a top-level array, or `None` for other roots
+fn split_top_level(source: &str) -> Result<Option<Vec<Range<usize>>>, JError> {
+    let mut lexer = lexer(source);
+    match lexer.next() {
+        Some(Ok(JsonTokenKind::OpenBracket)) => {}
+        _ => return Ok(None),
+    }
+
+    let mut spans = Vec::new();
+    let mut open = Vec::new();
+    let mut element: Option<Range<usize>> = None;
+    let mut closed = false;
+    while let Some(token) = lexer.next() {
+        let span = lexer.span();
+        let token = token.map_err(|_| lex_error(&lexer))?;
+        if closed {
+            return Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Unexpected trailing input {token} at {}", span.start),
+            ));
+        }
+
+        match (&token, open.is_empty()) {
+            (JsonTokenKind::Comma | JsonTokenKind::CloseBracket, true) => {
+                match element.take() {
+                    Some(element) => spans.push(element),
+                    // `[]` is the only place where a separator may follow nothing
+                    None if token == JsonTokenKind::CloseBracket && spans.is_empty() => {}
+                    None => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!("Expected an array element before {token} at {}", span.start),
+                        ))
+                    }
+                }
+                closed = token == JsonTokenKind::CloseBracket;
+                continue;
+            }
+            (JsonTokenKind::OpenBracket | JsonTokenKind::OpenBrace, _) => open.push(token.clone()),
+            (JsonTokenKind::CloseBracket, false) => {
+                close(&mut open, JsonTokenKind::OpenBracket, &span)?
+            }
+            (JsonTokenKind::CloseBrace, _) => close(&mut open, JsonTokenKind::OpenBrace, &span)?,
+            _ => {}
+        }
+        match &mut element {
+            Some(element) => element.end = span.end,
+            None => element = Some(span),
+        }
+    }
+
+    match closed {
+        true => Ok(Some(spans)),
+        false => Err(JError::new(ErrorKind::Eof, "Unexpected end of input")),
+    }
+}
+
+fn close(
+    open: &mut Vec<JsonTokenKind>,
+    expected: JsonTokenKind,
+    span: &Range<usize>,
+) -> Result<(), JError> {
+    match open.pop() {
+        Some(kind) if kind == expected => Ok(()),
+        _ => Err(JError::new(
+            ErrorKind::Syntax,
+            format!("Unbalanced brackets at {}", span.start),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::parse_parallel;
+
+    #[test]
+    fn it_parse_parallel() {
+        let records = (0..1000)
+            .map(|i| format!(r#"{{"id": {i}, "tags": ["a", "b"], "nested": {{"x": [{i}]}}}}"#))
+            .collect::<Vec<_>>();
+        let source = format!("[{}]", records.join(",\n"));
+
+        assert_eq!(parse_parallel(&source).unwrap(), parse(&source).unwrap());
+        assert_eq!(parse_parallel(" [ ] ").unwrap(), parse("[]").unwrap());
+        assert_eq!(
+            parse_parallel(r#"{"a": 1}"#).unwrap(),
+            parse(r#"{"a": 1}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_parse_parallel_rejects_invalid_input() {
+        for source in [
+            "[1,]", "[,1]", "[1,,2]", "[1 2]", "[1, 2", "[1] 2", "[[1}]", "[1, @]",
+        ] {
+            assert!(parse_parallel(source).is_err(), "{source}");
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::{
+    error::{ErrorKind, JError},
+    JsonValue, Map,
+};
+
+// parse_pointer Split an RFC 6901 JSON pointer into its unescaped reference tokens
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, JError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/').ok_or_else(|| {
+        JError::new(
+            ErrorKind::Syntax,
+            format!("JSON pointer {pointer:?} must start with '/'"),
+        )
+    })?;
+    rest.split('/').map(unescape_token).collect()
+}
+
+fn unescape_token(token: &str) -> Result<String, JError> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => out.push('~'),
+            Some('1') => out.push('/'),
+            _ => {
+                return Err(JError::new(
+                    ErrorKind::Escape,
+                    format!("Invalid escape in JSON pointer token {token:?}"),
+                ))
+            }
+        }
+    }
+    Ok(out)
+}
+
+// escape_token Escape a key for use as a JSON pointer reference token
+pub fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+// array_index Resolve a reference token against an array of the given length
+pub(crate) fn array_index(token: &str, len: usize) -> Option<usize> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    token.parse().ok().filter(|idx| valid && *idx < len)
+}
+
+impl JsonValue {
+    // pointer Look up a value by JSON pointer, e.g. `/address/city` or `/scores/0`
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        parse_pointer(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |target, token| match target {
+                JsonValue::Object(obj) => obj.get(token),
+                JsonValue::Array(arr) => arr.get(array_index(token, arr.len())?),
+                _ => None,
+            })
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        parse_pointer(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |target, token| match target {
+                JsonValue::Object(obj) => obj.get_mut(token),
+                JsonValue::Array(arr) => {
+                    let idx = array_index(token, arr.len())?;
+                    arr.get_mut(idx)
+                }
+                _ => None,
+            })
+    }
+
+    // get_at Look up a value by JSON pointer, failing with an error that names the path
+    pub fn get_at(&self, pointer: &str) -> Result<&JsonValue, JError> {
+        parse_pointer(pointer)?;
+        self.pointer(pointer).ok_or_else(|| {
+            JError::new(
+                ErrorKind::Type,
+                format!("no value at {}", display_path(pointer)),
+            )
+        })
+    }
+
+    pub fn get_str_at(&self, pointer: &str) -> Result<&str, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_str()
+            .ok_or_else(|| type_mismatch("string", pointer, found))
+    }
+
+    pub fn get_f64_at(&self, pointer: &str) -> Result<f64, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_f64()
+            .ok_or_else(|| type_mismatch("number", pointer, found))
+    }
+
+    // get_i64_at Read an integral number that fits in an i64
+    pub fn get_i64_at(&self, pointer: &str) -> Result<i64, JError> {
+        let found = self.get_at(pointer)?;
+        match found.as_f64() {
+            Some(n) if n == (n as i64) as f64 && n < i64::MAX as f64 => Ok(n as i64),
+            Some(n) => Err(JError::new(
+                ErrorKind::Type,
+                format!(
+                    "expected integer at {}, found number {n}",
+                    display_path(pointer)
+                ),
+            )),
+            None => Err(type_mismatch("integer", pointer, found)),
+        }
+    }
+
+    pub fn get_bool_at(&self, pointer: &str) -> Result<bool, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_bool()
+            .ok_or_else(|| type_mismatch("boolean", pointer, found))
+    }
+
+    pub fn get_array_at(&self, pointer: &str) -> Result<&Vec<JsonValue>, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_array()
+            .ok_or_else(|| type_mismatch("array", pointer, found))
+    }
+
+    pub fn get_object_at(&self, pointer: &str) -> Result<&Map<String, JsonValue>, JError> {
+        let found = self.get_at(pointer)?;
+        found
+            .as_object()
+            .ok_or_else(|| type_mismatch("object", pointer, found))
+    }
+}
+
+/// A value together with the way to it from the root, as produced by
+/// [`JsonValue::nodes`] and [`JsonValue::node`].
+///
+/// Keeping the ancestors is what lets [`relative`](Self::relative) move up the tree.
+#[derive(Debug, Clone)]
+pub struct NodeRef<'a> {
+    value: &'a JsonValue,
+    // Each ancestor, outermost first, with the step taken out of it
+    ancestors: Vec<(&'a JsonValue, Step<'a>)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// What a relative JSON pointer resolves to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelativeTarget<'a> {
+    Value(&'a JsonValue),
+    /// The member name reached with a trailing `#`.
+    Key(&'a str),
+    /// The array index reached with a trailing `#`.
+    Index(usize),
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    // depth Number of containers above the value
+    pub fn depth(&self) -> usize {
+        self.ancestors.len()
+    }
+
+    // pointer The absolute JSON pointer of the value
+    pub fn pointer(&self) -> String {
+        let mut out = String::new();
+        for (_, step) in &self.ancestors {
+            out.push('/');
+            match step {
+                Step::Key(key) => out.push_str(&escape_token(key)),
+                Step::Index(idx) => out.push_str(&format!("{idx}")),
+            }
+        }
+        out
+    }
+
+    pub fn parent(&self) -> Option<NodeRef<'a>> {
+        let mut ancestors = self.ancestors.clone();
+        let (value, _) = ancestors.pop()?;
+        Some(NodeRef { value, ancestors })
+    }
+
+    /// Evaluate a relative JSON pointer such as `1/foo`, `0-1` or `2#` from this value.
+    ///
+    /// The leading number climbs that many containers; an optional `+n` or `-n` then
+    /// moves to a sibling within the array reached. What follows is either an ordinary
+    /// JSON pointer from there, or `#` for the key or index under which that value sits
+    /// in its parent. `None` if the pointer is malformed or leads nowhere, including
+    /// climbing above the root and `#` on the root.
+    pub fn relative(&self, pointer: &str) -> Option<RelativeTarget<'a>> {
+        let digits = pointer.bytes().take_while(u8::is_ascii_digit).count();
+        let up: usize = parse_unsigned(&pointer[..digits])?;
+        let mut rest = &pointer[digits..];
+        let keep = self.ancestors.len().checked_sub(up)?;
+        let mut value = match keep {
+            len if len == self.ancestors.len() => self.value,
+            _ => self.ancestors[keep].0,
+        };
+        let mut step = keep.checked_sub(1).map(|idx| self.ancestors[idx]);
+
+        if let Some(sign @ (b'+' | b'-')) = rest.bytes().next() {
+            let digits = rest[1..].bytes().take_while(u8::is_ascii_digit).count();
+            let shift: usize = parse_unsigned(&rest[1..=digits])?;
+            rest = &rest[1 + digits..];
+            let (parent, Step::Index(idx)) = step? else {
+                return None;
+            };
+            let idx = match sign {
+                b'+' => idx.checked_add(shift)?,
+                _ => idx.checked_sub(shift)?,
+            };
+            value = parent.as_array()?.get(idx)?;
+            step = Some((parent, Step::Index(idx)));
+        }
+
+        match rest {
+            "#" => match step? {
+                (_, Step::Key(key)) => Some(RelativeTarget::Key(key)),
+                (_, Step::Index(idx)) => Some(RelativeTarget::Index(idx)),
+            },
+            _ if rest.is_empty() || rest.starts_with('/') => {
+                value.pointer(rest).map(RelativeTarget::Value)
+            }
+            _ => None,
+        }
+    }
+}
+
+// parse_unsigned A non-negative integer without leading zeros
+fn parse_unsigned(digits: &str) -> Option<usize> {
+    let valid = !digits.is_empty() && (digits == "0" || !digits.starts_with('0'));
+    digits.parse().ok().filter(|_| valid)
+}
+
+/// Depth-first, pre-order iterator over a value and everything inside it, see
+/// [`JsonValue::nodes`].
+pub struct Nodes<'a> {
+    pending: Vec<NodeRef<'a>>,
+}
+
+impl<'a> Iterator for Nodes<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<NodeRef<'a>> {
+        let node = self.pending.pop()?;
+        let child = |step| {
+            let mut ancestors = node.ancestors.clone();
+            ancestors.push((node.value, step));
+            ancestors
+        };
+        match node.value {
+            JsonValue::Array(arr) => {
+                self.pending
+                    .extend(arr.iter().enumerate().rev().map(|(idx, value)| NodeRef {
+                        value,
+                        ancestors: child(Step::Index(idx)),
+                    }))
+            }
+            JsonValue::Object(obj) => {
+                let children = obj
+                    .iter()
+                    .map(|(key, value)| NodeRef {
+                        value,
+                        ancestors: child(Step::Key(key)),
+                    })
+                    .collect::<Vec<_>>();
+                self.pending.extend(children.into_iter().rev());
+            }
+            _ => {}
+        }
+        Some(node)
+    }
+}
+
+impl JsonValue {
+    // nodes Iterate over the value and all values nested in it, each with its path
+    pub fn nodes(&self) -> Nodes<'_> {
+        Nodes {
+            pending: vec![self.root_node()],
+        }
+    }
+
+    // node The value at a JSON pointer, as a handle that relative pointers can start from
+    pub fn node(&self, pointer: &str) -> Option<NodeRef<'_>> {
+        parse_pointer(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self.root_node(), |node, token| {
+                let (value, step) = match node.value {
+                    JsonValue::Object(obj) => {
+                        let (key, value) = obj.get_key_value(token.as_str())?;
+                        (value, Step::Key(key))
+                    }
+                    JsonValue::Array(arr) => {
+                        let idx = array_index(token, arr.len())?;
+                        (&arr[idx], Step::Index(idx))
+                    }
+                    _ => return None,
+                };
+                let mut ancestors = node.ancestors;
+                ancestors.push((node.value, step));
+                Some(NodeRef { value, ancestors })
+            })
+    }
+
+    fn root_node(&self) -> NodeRef<'_> {
+        NodeRef {
+            value: self,
+            ancestors: Vec::new(),
+        }
+    }
+}
+
+pub(crate) fn type_mismatch(expected: &str, pointer: &str, found: &JsonValue) -> JError {
+    JError::new(
+        ErrorKind::Type,
+        format!(
+            "expected {expected} at {}, found {}",
+            display_path(pointer),
+            found.type_name()
+        ),
+    )
+}
+
+// display_path Show the root pointer as `/` rather than an empty string
+pub(crate) fn display_path(pointer: &str) -> &str {
+    match pointer {
+        "" => "/",
+        _ => pointer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use crate::{parse, JsonValue};
+
+    use super::{escape_token, parse_pointer};
+
+    #[test]
+    fn it_parse_pointer() {
+        assert_eq!(parse_pointer("").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_pointer("/a~1b/~0c/").unwrap(), vec!["a/b", "~c", ""]);
+        assert!(parse_pointer("a").is_err());
+        assert!(parse_pointer("/a~2").is_err());
+        assert_eq!(escape_token("a/b~c"), "a~1b~0c");
+    }
+
+    #[test]
+    fn it_typed_getters() {
+        let value = parse(
+            r#"{"address": {"city": "Springfield"}, "age": 30, "ratio": 0.5, "ok": true, "tags": []}"#,
+        )
+        .unwrap();
+
+        assert_eq!(value.get_str_at("/address/city").unwrap(), "Springfield");
+        assert_eq!(value.get_i64_at("/age").unwrap(), 30);
+        assert_eq!(value.get_f64_at("/ratio").unwrap(), 0.5);
+        assert!(value.get_bool_at("/ok").unwrap());
+        assert!(value.get_array_at("/tags").unwrap().is_empty());
+        assert_eq!(value.get_object_at("").unwrap().len(), 5);
+
+        fn err<T: core::fmt::Debug>(result: Result<T, crate::JError>) -> String {
+            result.unwrap_err().to_string()
+        }
+        assert_eq!(
+            err(value.get_str_at("/address")),
+            "expected string at /address, found object"
+        );
+        assert_eq!(
+            err(value.get_i64_at("/ratio")),
+            "expected integer at /ratio, found number 0.5"
+        );
+        assert_eq!(
+            err(value.get_bool_at("")),
+            "expected boolean at /, found object"
+        );
+        assert_eq!(
+            err(value.get_str_at("/address/zip")),
+            "no value at /address/zip"
+        );
+        assert!(err(value.get_str_at("address")).contains("must start with '/'"));
+    }
+
+    #[test]
+    fn it_pointer_lookup() {
+        let mut value =
+            parse(r#"{"address": {"city": "Springfield"}, "scores": [100, 90], "a/b": 1}"#)
+                .unwrap();
+
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(
+            value.pointer("/address/city"),
+            Some(&JsonValue::String("Springfield".to_string()))
+        );
+        assert_eq!(value.pointer("/scores/1"), Some(&JsonValue::Number(90.0)));
+        assert_eq!(value.pointer("/a~1b"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(value.pointer("/scores/01"), None);
+        assert_eq!(value.pointer("/scores/2"), None);
+        assert_eq!(value.pointer("/scores/-"), None);
+        assert_eq!(value.pointer("/address/city/x"), None);
+
+        *value.pointer_mut("/scores/0").unwrap() = JsonValue::Null;
+        assert_eq!(value.pointer("/scores/0"), Some(&JsonValue::Null));
+    }
+
+    #[test]
+    fn it_relative_pointer() {
+        use super::RelativeTarget::{Index, Key, Value};
+
+        // The examples of the Relative JSON Pointer draft
+        let doc =
+            parse(r#"{"foo": ["bar", "baz"], "highly": {"nested": {"objects": true}}}"#).unwrap();
+        let baz = doc.node("/foo/1").unwrap();
+        assert_eq!(baz.relative("0"), Some(Value(&JsonValue::from("baz"))));
+        assert_eq!(baz.relative("1/0"), Some(Value(&JsonValue::from("bar"))));
+        assert_eq!(baz.relative("0-1"), Some(Value(&JsonValue::from("bar"))));
+        assert_eq!(
+            baz.relative("2/highly/nested/objects"),
+            Some(Value(&JsonValue::Bool(true)))
+        );
+        assert_eq!(baz.relative("0#"), Some(Index(1)));
+        assert_eq!(baz.relative("0-1#"), Some(Index(0)));
+        assert_eq!(baz.relative("1#"), Some(Key("foo")));
+
+        let nested = doc.node("/highly/nested").unwrap();
+        assert_eq!(
+            nested.relative("0/objects"),
+            Some(Value(&JsonValue::Bool(true)))
+        );
+        assert_eq!(nested.relative("0#"), Some(Key("nested")));
+        assert_eq!(nested.relative("1#"), Some(Key("highly")));
+        assert_eq!(nested.relative("2"), Some(Value(&doc)));
+
+        assert_eq!(nested.relative("3"), None);
+        assert_eq!(nested.relative("2#"), None);
+        assert_eq!(nested.relative("0+1"), None);
+        assert_eq!(baz.relative("0+1"), None);
+        assert_eq!(baz.relative("01"), None);
+        assert_eq!(baz.relative("/foo"), None);
+        assert_eq!(baz.relative("1foo"), None);
+        assert_eq!(baz.pointer(), "/foo/1");
+    }
+
+    #[test]
+    fn it_nodes() {
+        let doc = parse(r#"{"a": [1, {"b~": null}], "c": true}"#).unwrap();
+        let mut pointers = doc.nodes().map(|node| node.pointer()).collect::<Vec<_>>();
+        if cfg!(feature = "hash_map") {
+            pointers.sort();
+        }
+        assert_eq!(pointers, ["", "/a", "/a/0", "/a/1", "/a/1/b~0", "/c"]);
+        // every handle leads back to its own value
+        for node in doc.nodes() {
+            assert_eq!(doc.pointer(&node.pointer()), Some(node.value()));
+            assert_eq!(
+                node.relative("0"),
+                Some(super::RelativeTarget::Value(node.value()))
+            );
+        }
+        let leaf = doc.node("/a/1/b~0").unwrap();
+        assert_eq!(leaf.depth(), 3);
+        assert_eq!(leaf.parent().unwrap().pointer(), "/a/1");
+        assert!(doc.node("/a/2").is_none());
+    }
+}
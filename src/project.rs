@@ -0,0 +1,301 @@
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+
+use crate::{
+    error::JError,
+    event::JsonEvent,
+    query::{compile_pointer, Segment, Step},
+    JsonValue, Map,
+};
+
+/// How a location relates to the projected paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reach {
+    // A path ends here: the value is kept whole
+    Full,
+    // A path runs through here: a container is kept with only the projected parts
+    Prefix,
+    None,
+}
+
+fn reach(patterns: &[Vec<Step>], path: &[Segment]) -> Reach {
+    let mut reach = Reach::None;
+    for steps in patterns {
+        if steps.len() < path.len() || !steps.iter().zip(path).all(|(s, p)| s.matches(p)) {
+            continue;
+        }
+        if steps.len() == path.len() {
+            return Reach::Full;
+        }
+        reach = Reach::Prefix;
+    }
+    reach
+}
+
+fn compile(paths: &[&str]) -> Result<Vec<Vec<Step>>, JError> {
+    paths.iter().map(|path| compile_pointer(path)).collect()
+}
+
+impl JsonValue {
+    /// Keep only the values at `paths`, with the objects and arrays leading to them.
+    ///
+    /// Paths are JSON pointers where a `*` token matches every member or element, e.g.
+    /// `/items/*/sku`. Containers along a path are kept even when nothing inside them
+    /// matches, so `/items/*/sku` keeps one (possibly empty) object per item. Members keep
+    /// their order; array elements keep theirs but close up over dropped ones. A document
+    /// no path reaches, such as a scalar without the empty pointer, projects to `null`.
+    pub fn project(&self, paths: &[&str]) -> Result<JsonValue, JError> {
+        let patterns = compile(paths)?;
+        Ok(project_node(self, &patterns, &mut Vec::new()).unwrap_or(JsonValue::Null))
+    }
+}
+
+fn project_node(
+    value: &JsonValue,
+    patterns: &[Vec<Step>],
+    path: &mut Vec<Segment>,
+) -> Option<JsonValue> {
+    match (reach(patterns, path), value) {
+        (Reach::Full, _) => Some(value.clone()),
+        (Reach::Prefix, JsonValue::Object(obj)) => {
+            let mut out = Map::default();
+            for (key, item) in obj {
+                path.push(Segment::Key(key.clone()));
+                if let Some(item) = project_node(item, patterns, path) {
+                    out.insert(key.clone(), item);
+                }
+                path.pop();
+            }
+            Some(JsonValue::Object(out))
+        }
+        (Reach::Prefix, JsonValue::Array(arr)) => {
+            let mut out = Vec::new();
+            for (idx, item) in arr.iter().enumerate() {
+                path.push(Segment::Index(idx));
+                out.extend(project_node(item, patterns, path));
+                path.pop();
+            }
+            Some(JsonValue::Array(out))
+        }
+        _ => None,
+    }
+}
+
+/// Projects an event stream as [`JsonValue::project`] does a value, one event at a time.
+///
+/// Memory use is bounded by the nesting depth: dropped values are skipped as their
+/// events go by and nothing is buffered but the key of the member about to start.
+#[derive(Debug)]
+pub struct Projector<'a> {
+    patterns: Vec<Vec<Step>>,
+    // Location of each open, kept container's current member or next element
+    frames: Vec<Segment>,
+    // Key event held back until its value shows whether the member is kept
+    key: Option<JsonEvent<'a>>,
+    // Open containers (and chunked strings) of a value being kept whole or skipped
+    depth: usize,
+    keep: bool,
+    in_string: bool,
+}
+
+impl<'a> Projector<'a> {
+    pub fn new(paths: &[&str]) -> Result<Projector<'a>, JError> {
+        Ok(Projector {
+            patterns: compile(paths)?,
+            frames: Vec::new(),
+            key: None,
+            depth: 0,
+            keep: false,
+            in_string: false,
+        })
+    }
+
+    // push Feed one event, passing on those of the projection to `emit`
+    pub fn push(&mut self, event: JsonEvent<'a>, mut emit: impl FnMut(JsonEvent<'a>)) {
+        // inside a value kept whole or skipped, only its nesting is followed
+        if self.depth > 0 {
+            match event {
+                JsonEvent::StartObject(_) | JsonEvent::StartArray(_) => self.depth += 1,
+                JsonEvent::StringChunk(_) if !self.in_string => {
+                    self.in_string = true;
+                    self.depth += 1;
+                }
+                JsonEvent::EndObject | JsonEvent::EndArray => self.depth -= 1,
+                JsonEvent::StringEnd => {
+                    self.in_string = false;
+                    self.depth -= 1;
+                }
+                _ => {}
+            }
+            if self.keep {
+                emit(event);
+            }
+            if self.depth == 0 {
+                self.value_done();
+            }
+            return;
+        }
+
+        match event {
+            JsonEvent::Key(ref key) => {
+                if let Some(frame) = self.frames.last_mut() {
+                    *frame = Segment::Key(String::from(key.as_ref()));
+                }
+                self.key = Some(event);
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                self.frames.pop();
+                emit(event);
+                self.value_done();
+            }
+            event => {
+                let key = self.key.take();
+                let container =
+                    matches!(event, JsonEvent::StartObject(_) | JsonEvent::StartArray(_));
+                let chunked = matches!(event, JsonEvent::StringChunk(_));
+                let reach = reach(&self.patterns, &self.frames);
+                let kept = match reach {
+                    Reach::Full => true,
+                    Reach::Prefix => container,
+                    Reach::None => false,
+                };
+                if kept {
+                    emit_key(key, &mut emit);
+                }
+                if reach == Reach::Prefix && container {
+                    self.frames.push(match event {
+                        JsonEvent::StartObject(_) => Segment::Key(String::new()),
+                        _ => Segment::Index(0),
+                    });
+                    emit(event);
+                    return;
+                }
+                if kept {
+                    emit(event);
+                }
+                match container || chunked {
+                    true => {
+                        self.depth = 1;
+                        self.keep = kept;
+                        self.in_string = chunked;
+                    }
+                    false => self.value_done(),
+                }
+            }
+        }
+    }
+
+    // value_done A value of the innermost kept container has ended
+    fn value_done(&mut self) {
+        self.keep = false;
+        if let Some(Segment::Index(idx)) = self.frames.last_mut() {
+            *idx += 1;
+        }
+    }
+}
+
+fn emit_key<'a>(key: Option<JsonEvent<'a>>, emit: &mut impl FnMut(JsonEvent<'a>)) {
+    if let Some(key) = key {
+        emit(key);
+    }
+}
+
+// project_events Project an event stream lazily, see `JsonValue::project`
+pub fn project_events<'a, I: IntoIterator<Item = JsonEvent<'a>>>(
+    events: I,
+    paths: &[&str],
+) -> Result<Projected<'a, I::IntoIter>, JError> {
+    Ok(Projected {
+        events: events.into_iter(),
+        projector: Projector::new(paths)?,
+        ready: VecDeque::new(),
+    })
+}
+
+pub struct Projected<'a, I> {
+    events: I,
+    projector: Projector<'a>,
+    ready: VecDeque<JsonEvent<'a>>,
+}
+
+impl<'a, I: Iterator<Item = JsonEvent<'a>>> Iterator for Projected<'a, I> {
+    type Item = JsonEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(event);
+            }
+            let event = self.events.next()?;
+            let ready = &mut self.ready;
+            self.projector.push(event, |event| ready.push_back(event));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonValue};
+
+    use super::project_events;
+
+    #[test]
+    fn it_project() {
+        let doc = parse(
+            r#"{"user": {"id": 7, "name": "ann", "email": "a@x"}, "note": "x",
+                "items": [{"sku": "a1", "qty": 2}, {"qty": 1}, {"sku": {"v": 3}, "n": [1]}, 5]}"#,
+        )
+        .unwrap();
+        let paths = ["/user/id", "/user/name", "/items/*/sku", "/missing/x"];
+        let expected = parse(
+            r#"{"user": {"id": 7, "name": "ann"}, "items": [{"sku": "a1"}, {}, {"sku": {"v": 3}}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(doc.project(&paths).unwrap(), expected);
+        let events = project_events(doc.events(), &paths).unwrap();
+        assert_eq!(JsonValue::from_events(events).unwrap(), expected);
+
+        assert_eq!(
+            doc.project(&["/items/1"]).unwrap(),
+            parse(r#"{"items": [{"qty": 1}]}"#).unwrap()
+        );
+        assert_eq!(doc.project(&[""]).unwrap(), doc);
+        assert_eq!(doc.project(&[]).unwrap(), JsonValue::Null);
+        assert_eq!(
+            parse("3").unwrap().project(&["/a"]).unwrap(),
+            JsonValue::Null
+        );
+        assert_eq!(
+            project_events(parse("3").unwrap().events(), &["/a"])
+                .unwrap()
+                .count(),
+            0
+        );
+        assert!(doc.project(&["user"]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_project_chunked_strings() {
+        use alloc::vec::Vec;
+
+        use crate::{event::JsonEvent, reader::EventReader};
+
+        let source =
+            r#"{"keep": "abcdefgh", "drop": "ijklmnop", "list": ["qrstuvwx", {"k": "yz0123"}]}"#;
+        let events = EventReader::new(source.as_bytes())
+            .chunk_strings(3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, JsonEvent::StringChunk(_))));
+
+        let paths = ["/keep", "/list/1"];
+        let projected = project_events(events, &paths).unwrap();
+        assert_eq!(
+            JsonValue::from_events(projected).unwrap(),
+            parse(source).unwrap().project(&paths).unwrap()
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::{
+    error::{ErrorKind, JError},
+    event::{JsonEvent, ValueBuilder},
+    jq::Filter,
+    pointer::{array_index, escape_token, parse_pointer},
+    JsonValue,
+};
+
+/// A query parsed once, to be run against any number of documents.
+///
+/// [`compile`](Self::compile) picks the syntax from the first character:
+///
+/// * `""` or `/...` is a JSON pointer, where a `*` token matches every member or element;
+/// * `$...` is a JSONPath made of `.name`, `.*`, `['name']`, `[0]` and `[*]` selectors;
+/// * anything else is a [jq filter](crate::jq).
+///
+/// Pointers and paths can also run over an event stream without assembling the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledQuery {
+    plan: Plan,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Plan {
+    Path(Vec<Step>),
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Step {
+    // Pointer token, naming an object member or an array index
+    Token(String),
+    Key(String),
+    Index(usize),
+    Any,
+}
+
+impl Step {
+    pub(crate) fn matches(&self, segment: &Segment) -> bool {
+        match (self, segment) {
+            (Step::Any, _) => true,
+            (Step::Token(t) | Step::Key(t), Segment::Key(k)) => t == k,
+            (Step::Index(i), Segment::Index(j)) => i == j,
+            (Step::Token(t), Segment::Index(j)) => array_index(t, usize::MAX) == Some(*j),
+            _ => false,
+        }
+    }
+}
+
+impl CompiledQuery {
+    pub fn compile(expr: &str) -> Result<CompiledQuery, JError> {
+        let plan = match expr.chars().next() {
+            None | Some('/') => Plan::Path(compile_pointer(expr)?),
+            Some('$') => Plan::Path(compile_path(expr)?),
+            Some(_) => Plan::Filter(Filter::parse(expr)?),
+        };
+        Ok(CompiledQuery { plan })
+    }
+
+    // eval Run the query against a value, collecting every result
+    pub fn eval(&self, value: &JsonValue) -> Result<Vec<JsonValue>, JError> {
+        match &self.plan {
+            Plan::Path(steps) => {
+                let mut out = Vec::new();
+                select(value, steps, &mut out);
+                Ok(out)
+            }
+            Plan::Filter(filter) => filter.eval(value),
+        }
+    }
+
+    /// Run the query against a document given as events.
+    ///
+    /// Pointers and paths only assemble the matched values; a jq filter needs the whole
+    /// document, which is built first.
+    pub fn eval_stream<'a>(
+        &self,
+        events: impl IntoIterator<Item = JsonEvent<'a>>,
+    ) -> Result<Vec<JsonValue>, JError> {
+        match &self.plan {
+            Plan::Path(steps) => {
+                let mut matcher = PathMatcher::new(vec![steps.clone()]);
+                let mut out = Vec::new();
+                for event in events {
+                    if let Some((_, _, value)) = matcher.push(event)? {
+                        out.push(value);
+                    }
+                }
+                matcher.finish()?;
+                Ok(out)
+            }
+            Plan::Filter(filter) => filter.eval(&JsonValue::from_events(events)?),
+        }
+    }
+}
+
+pub(crate) fn compile_pointer(pointer: &str) -> Result<Vec<Step>, JError> {
+    Ok(parse_pointer(pointer)?
+        .into_iter()
+        .map(|token| match token.as_str() {
+            "*" => Step::Any,
+            _ => Step::Token(token),
+        })
+        .collect())
+}
+
+fn compile_path(path: &str) -> Result<Vec<Step>, JError> {
+    let invalid = |at: &str| {
+        JError::new(
+            ErrorKind::Syntax,
+            format!("Invalid JSONPath {path:?} at {at:?}"),
+        )
+    };
+    let mut rest = path.strip_prefix('$').ok_or_else(|| invalid(path))?;
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            steps.push(match &after[..end] {
+                "" => return Err(invalid(rest)),
+                "*" => Step::Any,
+                name => Step::Key(name.to_string()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid(rest))?;
+            let selector = &after[..end];
+            steps.push(match selector.as_bytes() {
+                b"*" => Step::Any,
+                [q @ (b'\'' | b'"'), .., last] if last == q && selector.len() > 1 => {
+                    Step::Key(selector[1..selector.len() - 1].to_string())
+                }
+                _ => Step::Index(selector.parse().map_err(|_| invalid(rest))?),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid(rest));
+        }
+    }
+    Ok(steps)
+}
+
+fn select(value: &JsonValue, steps: &[Step], out: &mut Vec<JsonValue>) {
+    let Some((step, rest)) = steps.split_first() else {
+        out.push(value.clone());
+        return;
+    };
+    match value {
+        JsonValue::Object(obj) => match step {
+            Step::Any => obj.values().for_each(|item| select(item, rest, out)),
+            Step::Token(key) | Step::Key(key) => {
+                if let Some(item) = obj.get(key) {
+                    select(item, rest, out)
+                }
+            }
+            Step::Index(_) => {}
+        },
+        JsonValue::Array(arr) => match step {
+            Step::Any => arr.iter().for_each(|item| select(item, rest, out)),
+            Step::Index(idx) => {
+                if let Some(item) = arr.get(*idx) {
+                    select(item, rest, out)
+                }
+            }
+            Step::Token(token) => {
+                if let Some(idx) = array_index(token, arr.len()) {
+                    select(&arr[idx], rest, out)
+                }
+            }
+            Step::Key(_) => {}
+        },
+        _ => {}
+    }
+}
+
+/// Position of a value inside its parent container.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Watches an event stream and assembles the values found at any of the patterns.
+///
+/// A value nested inside one that is already being captured is not reported separately.
+pub(crate) struct PathMatcher {
+    patterns: Vec<Vec<Step>>,
+    // One segment per open container: the current key, or the index of the next element
+    frames: Vec<Segment>,
+    capture: Option<Capture>,
+    // Between the first chunk of a string value and its end
+    in_string: bool,
+}
+
+struct Capture {
+    pattern: usize,
+    pointer: String,
+    depth: usize,
+    builder: ValueBuilder,
+}
+
+impl PathMatcher {
+    pub(crate) fn new(patterns: Vec<Vec<Step>>) -> Self {
+        PathMatcher {
+            patterns,
+            frames: Vec::new(),
+            capture: None,
+            in_string: false,
+        }
+    }
+
+    // push Feed one event, returning the pattern index, pointer and value of a completed match
+    pub(crate) fn push(
+        &mut self,
+        event: JsonEvent,
+    ) -> Result<Option<(usize, String, JsonValue)>, JError> {
+        match event {
+            JsonEvent::Key(key) => {
+                match self.frames.last_mut() {
+                    Some(Segment::Key(slot)) => {
+                        slot.clear();
+                        slot.push_str(&key);
+                    }
+                    _ => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!("Unexpected key {key:?}"),
+                        ))
+                    }
+                }
+                if let Some(capture) = &mut self.capture {
+                    capture.builder.push(JsonEvent::Key(key))?;
+                }
+                Ok(None)
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                let closes_object = matches!(event, JsonEvent::EndObject);
+                match self.frames.pop() {
+                    Some(Segment::Key(_)) if closes_object => {}
+                    Some(Segment::Index(_)) if !closes_object => {}
+                    _ => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!("Unexpected {event:?}"),
+                        ))
+                    }
+                }
+                if let Some(capture) = &mut self.capture {
+                    capture.builder.push(event)?;
+                }
+                self.complete()
+            }
+            JsonEvent::StartObject(_) | JsonEvent::StartArray(_) => {
+                let frame = match event {
+                    JsonEvent::StartObject(_) => Segment::Key(String::new()),
+                    _ => Segment::Index(0),
+                };
+                self.start(event)?;
+                self.frames.push(frame);
+                Ok(None)
+            }
+            JsonEvent::StringChunk(_) if self.in_string => {
+                if let Some(capture) = &mut self.capture {
+                    capture.builder.push(event)?;
+                }
+                Ok(None)
+            }
+            JsonEvent::StringChunk(_) => {
+                self.in_string = true;
+                self.start(event)?;
+                Ok(None)
+            }
+            JsonEvent::StringEnd if self.in_string => {
+                self.in_string = false;
+                if let Some(capture) = &mut self.capture {
+                    capture.builder.push(event)?;
+                }
+                self.complete()
+            }
+            JsonEvent::StringEnd => Err(JError::new(ErrorKind::Syntax, "Unexpected end of string")),
+            scalar => {
+                self.start(scalar)?;
+                self.complete()
+            }
+        }
+    }
+
+    // wants_next Whether the value about to start could be, or contain, a match
+    pub(crate) fn wants_next(&self) -> bool {
+        self.capture.is_some()
+            || self.patterns.iter().any(|steps| {
+                steps.len() >= self.frames.len()
+                    && steps.iter().zip(&self.frames).all(|(s, f)| s.matches(f))
+            })
+    }
+
+    // skipped Account for a value the caller skipped without producing events
+    pub(crate) fn skipped(&mut self) {
+        if let Some(Segment::Index(idx)) = self.frames.last_mut() {
+            *idx += 1;
+        }
+    }
+
+    // finish Check that the stream closed every container it opened
+    pub(crate) fn finish(&self) -> Result<(), JError> {
+        match self.frames.is_empty() && self.capture.is_none() {
+            true => Ok(()),
+            false => Err(JError::new(ErrorKind::Eof, "Incomplete event sequence")),
+        }
+    }
+
+    fn start(&mut self, event: JsonEvent) -> Result<(), JError> {
+        if self.capture.is_none() {
+            let frames = &self.frames;
+            let matched = self.patterns.iter().position(|steps| {
+                steps.len() == frames.len() && steps.iter().zip(frames).all(|(s, f)| s.matches(f))
+            });
+            if let Some(pattern) = matched {
+                self.capture = Some(Capture {
+                    pattern,
+                    pointer: to_pointer(frames),
+                    depth: frames.len(),
+                    builder: ValueBuilder::new(),
+                });
+            }
+        }
+        match &mut self.capture {
+            Some(capture) => capture.builder.push(event),
+            None => Ok(()),
+        }
+    }
+
+    // complete A value just ended: move past it and hand back a finished capture
+    fn complete(&mut self) -> Result<Option<(usize, String, JsonValue)>, JError> {
+        if let Some(Segment::Index(idx)) = self.frames.last_mut() {
+            *idx += 1;
+        }
+        match self.capture.take() {
+            Some(capture) if capture.depth == self.frames.len() => Ok(Some((
+                capture.pattern,
+                capture.pointer,
+                capture.builder.finish()?,
+            ))),
+            capture => {
+                self.capture = capture;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// A value found by [`extract`], with the index of the path that matched it and its
+/// concrete location as a JSON pointer.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extracted {
+    pub path: usize,
+    pub pointer: String,
+    pub value: JsonValue,
+}
+
+/// Stream the values at `paths` out of a document without building the rest of it.
+///
+/// Paths are JSON pointers where a `*` token matches every member or element, e.g.
+/// `/items/*/id`. Subtrees that cannot contain a match are skipped undecoded, so memory
+/// stays bounded by the nesting depth and the size of the matched values.
+#[cfg(feature = "std")]
+pub fn extract<R: std::io::Read>(reader: R, paths: &[&str]) -> Result<Extract<R>, JError> {
+    let patterns = paths
+        .iter()
+        .map(|path| compile_pointer(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Extract {
+        reader: crate::reader::EventReader::new(reader),
+        matcher: PathMatcher::new(patterns),
+    })
+}
+
+#[cfg(feature = "std")]
+pub struct Extract<R> {
+    reader: crate::reader::EventReader<R>,
+    matcher: PathMatcher,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Extract<R> {
+    fn advance(&mut self) -> Result<Option<Extracted>, JError> {
+        loop {
+            if !self.matcher.wants_next() && self.reader.skip_value()? {
+                self.matcher.skipped();
+                continue;
+            }
+            let Some(event) = self.reader.next().transpose()? else {
+                return Ok(None);
+            };
+            if let Some((path, pointer, value)) = self.matcher.push(event)? {
+                return Ok(Some(Extracted {
+                    path,
+                    pointer,
+                    value,
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Iterator for Extract<R> {
+    type Item = Result<Extracted, JError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance().transpose()
+    }
+}
+
+fn to_pointer(frames: &[Segment]) -> String {
+    frames.iter().fold(String::new(), |mut out, segment| {
+        out.push('/');
+        match segment {
+            Segment::Key(key) => out.push_str(&escape_token(key)),
+            Segment::Index(idx) => out.push_str(&idx.to_string()),
+        }
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use crate::{parse, JsonValue};
+
+    use super::CompiledQuery;
+
+    fn run(expr: &str, value: &JsonValue) -> Vec<JsonValue> {
+        let query = CompiledQuery::compile(expr).unwrap();
+        let from_value = query.eval(value).unwrap();
+        assert_eq!(query.eval_stream(value.events()).unwrap(), from_value);
+        from_value
+    }
+
+    #[test]
+    fn it_compiled_query() {
+        let doc = parse(
+            r#"{"items": [{"id": 1, "tags": ["x"]}, {"id": 2}, {"name": "z"}], "a/b": {"0": true}}"#,
+        )
+        .unwrap();
+        let ids = vec![JsonValue::from(1), JsonValue::from(2)];
+
+        assert_eq!(run("/items/*/id", &doc), ids);
+        assert_eq!(run("$.items[*].id", &doc), ids);
+        assert_eq!(run("$['items'].*.id", &doc), ids);
+        assert_eq!(run(".items[].id | select(. != null)", &doc), ids);
+        assert_eq!(run("/items/0/tags", &doc), vec![parse(r#"["x"]"#).unwrap()]);
+        assert_eq!(run("/a~1b/0", &doc), vec![JsonValue::Bool(true)]);
+        assert_eq!(run("$.items.0", &doc), vec![]);
+        assert_eq!(run("$.items[5]", &doc), vec![]);
+        assert_eq!(run("", &doc), vec![doc.clone()]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_compiled_query_chunked_strings() {
+        use crate::reader::EventReader;
+
+        let source = r#"[{"id": "abcdef"}, {"id": 2}]"#;
+        let chunked = EventReader::new(source.as_bytes())
+            .chunk_strings(2)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            CompiledQuery::compile("/*/id")
+                .unwrap()
+                .eval_stream(chunked)
+                .unwrap(),
+            vec![JsonValue::from("abcdef"), JsonValue::from(2)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_extract() {
+        let source = r#"{"items": [{"id": 1, "big": [[1, 2], {"k": "v"}]}, {"id": "x/y"}, 3],
+                         "meta": {"ts": 17, "id": 0}, "tail": [true]}"#;
+        let found = super::extract(source.as_bytes(), &["/items/*/id", "/meta/ts", "/nope"])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let summary = found
+            .iter()
+            .map(|e| (e.path, e.pointer.as_str(), e.value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (0, "/items/0/id", JsonValue::from(1)),
+                (0, "/items/1/id", JsonValue::from("x/y")),
+                (1, "/meta/ts", JsonValue::from(17)),
+            ]
+        );
+
+        let whole = super::extract(source.as_bytes(), &["/items/0/big"])
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(whole.value, parse(r#"[[1, 2], {"k": "v"}]"#).unwrap());
+
+        assert!(super::extract(&b"[1, {]"[..], &["/0"])
+            .unwrap()
+            .any(|r| r.is_err()));
+        assert!(super::extract(&b""[..], &["a"]).is_err());
+    }
+
+    #[test]
+    fn it_compiled_query_errors() {
+        assert!(CompiledQuery::compile("$..id").is_err());
+        assert!(CompiledQuery::compile("$.items[x]").is_err());
+        assert!(CompiledQuery::compile("/a~2").is_err());
+        assert!(CompiledQuery::compile(".a |").is_err());
+
+        let query = CompiledQuery::compile("/a").unwrap();
+        let events = parse(r#"{"a": [1]}"#).unwrap();
+        let truncated = events.events().take(3).collect::<Vec<_>>();
+        assert!(query.eval_stream(truncated).is_err());
+    }
+}
@@ -0,0 +1,585 @@
+use std::{borrow::Cow, io::Read};
+
+use crate::{
+    encoding::UTF8_BOM,
+    error::{ErrorKind, JError},
+    event::JsonEvent,
+    value::{unescape, valid_number},
+};
+
+/// Pull parser producing [`JsonEvent`]s from any reader, in constant memory apart from
+/// the nesting stack and the string being decoded.
+///
+/// Container counts are unknown up front, so starts are reported as `None`.
+/// With [`EventReader::chunk_strings`] long string values are handed out in pieces
+/// instead of being decoded whole.
+pub struct EventReader<R> {
+    reader: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    filled: usize,
+    // Bytes consumed before the current buffer, for error positions
+    offset: usize,
+    stack: Vec<u8>,
+    expect: Expect,
+    scratch: Vec<u8>,
+    chunk_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    FirstMember,
+    Member,
+    FirstElement,
+    AfterValue,
+    // Inside a string value that is being handed out in chunks
+    InString,
+    // The last chunk of a string was handed out, its `StringEnd` has not
+    StringEnd,
+    Done,
+}
+
+impl<R: Read> EventReader<R> {
+    pub fn new(reader: R) -> Self {
+        EventReader::with_capacity(8 * 1024, reader)
+    }
+
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        EventReader {
+            reader,
+            buf: vec![0; capacity.max(1)].into_boxed_slice(),
+            pos: 0,
+            filled: 0,
+            offset: 0,
+            stack: Vec::new(),
+            expect: Expect::Value,
+            scratch: Vec::new(),
+            chunk_size: None,
+        }
+    }
+
+    /// Report string values longer than `size` bytes of raw input as
+    /// [`JsonEvent::StringChunk`]s followed by a [`JsonEvent::StringEnd`].
+    ///
+    /// Chunks never split an escape sequence or a UTF-8 character, so each one is
+    /// decoded on its own. Shorter strings still arrive as a single
+    /// [`JsonEvent::String`].
+    pub fn chunk_strings(mut self, size: usize) -> Self {
+        self.chunk_size = Some(size.max(1));
+        self
+    }
+
+    // byte_offset Byte offset of the next unread input
+    pub fn byte_offset(&self) -> usize {
+        self.offset + self.pos
+    }
+
+    /// Skip the next value without decoding it, if a value comes next.
+    ///
+    /// Returns `false`, consuming nothing, when the next token cannot start a value
+    /// (an object key or a closing bracket). Skipped values are only checked for
+    /// balanced brackets and terminated strings.
+    pub fn skip_value(&mut self) -> Result<bool, JError> {
+        if matches!(self.expect, Expect::InString | Expect::StringEnd) {
+            return Ok(false);
+        }
+        self.skip_whitespace()?;
+        if self.expect == Expect::AfterValue
+            && self.stack.last() == Some(&b'[')
+            && self.peek()? == Some(b',')
+        {
+            self.pos += 1;
+            self.skip_whitespace()?;
+            self.expect = Expect::Value;
+        }
+        match (self.expect, self.peek()?) {
+            (Expect::Value, _) => {}
+            (Expect::FirstElement, Some(b)) if b != b']' => {}
+            _ => return Ok(false),
+        }
+
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                None => return Err(self.unexpected_end()),
+                Some(b'"') => {
+                    self.pos += 1;
+                    self.scan_string(false, usize::MAX)?;
+                }
+                Some(b'{' | b'[') => {
+                    self.pos += 1;
+                    depth += 1;
+                }
+                Some(b'}' | b']') if depth > 0 => {
+                    self.pos += 1;
+                    depth -= 1;
+                }
+                Some(_) if depth > 0 => self.pos += 1,
+                Some(b'}' | b']' | b',') => return Err(self.unexpected()),
+                Some(_) => {
+                    while let Some(b) = self.peek()? {
+                        if matches!(b, b',' | b'}' | b']') || b.is_ascii_whitespace() {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+            }
+            if depth == 0 {
+                self.expect = Expect::AfterValue;
+                return Ok(true);
+            }
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Option<JsonEvent<'static>>, JError> {
+        match self.expect {
+            Expect::InString => return self.read_chunk().map(Some),
+            Expect::StringEnd => {
+                self.expect = Expect::AfterValue;
+                return Ok(Some(JsonEvent::StringEnd));
+            }
+            _ => {}
+        }
+        loop {
+            self.skip_whitespace()?;
+            let Some(b) = self.peek()? else {
+                return match self.expect {
+                    Expect::AfterValue if self.stack.is_empty() => {
+                        self.expect = Expect::Done;
+                        Ok(None)
+                    }
+                    Expect::Done => Ok(None),
+                    _ => Err(self.unexpected_end()),
+                };
+            };
+
+            match self.expect {
+                Expect::Done => return Err(self.unexpected()),
+                Expect::InString | Expect::StringEnd => unreachable!("handled above"),
+                Expect::AfterValue => {
+                    let top = self.stack.last().copied();
+                    match (top, b) {
+                        (None, _) => return Err(self.unexpected()),
+                        (Some(b'{'), b',') => self.expect = Expect::Member,
+                        (Some(b'['), b',') => self.expect = Expect::Value,
+                        (Some(b'{'), b'}') => return Ok(Some(self.close(JsonEvent::EndObject))),
+                        (Some(b'['), b']') => return Ok(Some(self.close(JsonEvent::EndArray))),
+                        _ => return Err(self.unexpected()),
+                    }
+                    self.pos += 1;
+                }
+                Expect::FirstMember if b == b'}' => {
+                    return Ok(Some(self.close(JsonEvent::EndObject)))
+                }
+                Expect::FirstMember | Expect::Member => {
+                    if b != b'"' {
+                        return Err(self.unexpected());
+                    }
+                    self.pos += 1;
+                    let key = self.read_string()?;
+                    self.skip_whitespace()?;
+                    match self.peek()? {
+                        Some(b':') => self.pos += 1,
+                        Some(_) => return Err(self.unexpected()),
+                        None => return Err(self.unexpected_end()),
+                    }
+                    self.expect = Expect::Value;
+                    return Ok(Some(JsonEvent::Key(Cow::Owned(key))));
+                }
+                Expect::FirstElement if b == b']' => {
+                    return Ok(Some(self.close(JsonEvent::EndArray)))
+                }
+                Expect::Value | Expect::FirstElement => return self.read_value(b).map(Some),
+            }
+        }
+    }
+
+    fn read_value(&mut self, first: u8) -> Result<JsonEvent<'static>, JError> {
+        let event = match first {
+            b'{' | b'[' => {
+                self.pos += 1;
+                self.stack.push(first);
+                if first == b'{' {
+                    self.expect = Expect::FirstMember;
+                    return Ok(JsonEvent::StartObject(None));
+                }
+                self.expect = Expect::FirstElement;
+                return Ok(JsonEvent::StartArray(None));
+            }
+            b'"' if self.chunk_size.is_some() => {
+                self.pos += 1;
+                return self.read_chunk();
+            }
+            b'"' => {
+                self.pos += 1;
+                JsonEvent::String(Cow::Owned(self.read_string()?))
+            }
+            b't' => self.read_literal(b"true", JsonEvent::Bool(true))?,
+            b'f' => self.read_literal(b"false", JsonEvent::Bool(false))?,
+            b'n' => self.read_literal(b"null", JsonEvent::Null)?,
+            b'-' | b'0'..=b'9' => JsonEvent::Number(self.read_number()?),
+            _ => return Err(self.unexpected()),
+        };
+        self.expect = Expect::AfterValue;
+        Ok(event)
+    }
+
+    fn close(&mut self, event: JsonEvent<'static>) -> JsonEvent<'static> {
+        self.pos += 1;
+        self.stack.pop();
+        self.expect = Expect::AfterValue;
+        event
+    }
+
+    fn read_literal(
+        &mut self,
+        literal: &[u8],
+        event: JsonEvent<'static>,
+    ) -> Result<JsonEvent<'static>, JError> {
+        for expected in literal {
+            match self.peek()? {
+                Some(b) if b == *expected => self.pos += 1,
+                Some(_) => return Err(self.unexpected()),
+                None => return Err(self.unexpected_end()),
+            }
+        }
+        Ok(event)
+    }
+
+    fn read_number(&mut self) -> Result<f64, JError> {
+        let start = self.byte_offset();
+        self.scratch.clear();
+        while let Some(b @ (b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) = self.peek()? {
+            self.scratch.push(b);
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.scratch).unwrap_or_default();
+        match valid_number(&self.scratch) {
+            true => Ok(text.parse().unwrap_or_default()),
+            false => Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Invalid number {text:?} at byte {start}"),
+            )),
+        }
+    }
+
+    // read_chunk Hand out the next piece of a string value, chunking it if it runs long
+    fn read_chunk(&mut self) -> Result<JsonEvent<'static>, JError> {
+        let limit = self.chunk_size.unwrap_or(usize::MAX);
+        let started = self.expect == Expect::InString;
+        let (closed, text) = self.read_string_part(limit)?;
+        Ok(match (started, closed) {
+            (false, true) => {
+                self.expect = Expect::AfterValue;
+                JsonEvent::String(Cow::Owned(text))
+            }
+            (true, true) if text.is_empty() => {
+                self.expect = Expect::AfterValue;
+                JsonEvent::StringEnd
+            }
+            (true, true) => {
+                self.expect = Expect::StringEnd;
+                JsonEvent::StringChunk(Cow::Owned(text))
+            }
+            (_, false) => {
+                self.expect = Expect::InString;
+                JsonEvent::StringChunk(Cow::Owned(text))
+            }
+        })
+    }
+
+    // read_string Decode a string whose opening quote has been consumed
+    fn read_string(&mut self) -> Result<String, JError> {
+        self.read_string_part(usize::MAX).map(|(_, text)| text)
+    }
+
+    // read_string_part Decode at least `limit` raw bytes of a string, or up to its end
+    fn read_string_part(&mut self, limit: usize) -> Result<(bool, String), JError> {
+        let start = self.byte_offset();
+        self.scratch.clear();
+        let closed = self.scan_string(true, limit)?;
+        let raw = std::str::from_utf8(&self.scratch).map_err(|_| {
+            JError::new(
+                ErrorKind::Encoding,
+                format!("Invalid UTF-8 in string at byte {start}"),
+            )
+        })?;
+        Ok((closed, unescape(raw)?))
+    }
+
+    /// Move past the closing quote, keeping the raw contents in `scratch` if asked.
+    ///
+    /// Stops early, returning `false`, once `scratch` holds `limit` bytes and the next
+    /// byte starts a new character outside of any escape sequence.
+    fn scan_string(&mut self, keep: bool, limit: usize) -> Result<bool, JError> {
+        // 0 outside an escape, 1 after the backslash, then 5 down to 2 in `\uXXXX`
+        let mut escape = 0u8;
+        // A high surrogate escape was just read and its low half must not be split off
+        let mut surrogate = false;
+        loop {
+            let b = match self.peek()? {
+                Some(b) => b,
+                None => return Err(self.unexpected_end()),
+            };
+            if b == b'"' && escape != 1 {
+                self.pos += 1;
+                return Ok(true);
+            }
+            if self.scratch.len() >= limit && escape == 0 && !surrogate && b & 0xc0 != 0x80 {
+                return Ok(false);
+            }
+            self.pos += 1;
+            if b < 0x20 {
+                return Err(JError::new(
+                    ErrorKind::Lex,
+                    format!(
+                        "Unescaped control character in string at byte {}",
+                        self.byte_offset() - 1
+                    ),
+                ));
+            }
+            if keep {
+                self.scratch.push(b);
+            }
+            escape = match (escape, b) {
+                (0, b'\\') => 1,
+                (1, b'u') => 5,
+                (0 | 1, _) => {
+                    surrogate = false;
+                    0
+                }
+                (2, _) => {
+                    surrogate = keep && is_high_surrogate(&self.scratch[self.scratch.len() - 4..]);
+                    0
+                }
+                (n, _) => n - 1,
+            };
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), JError> {
+        if self.byte_offset() == 0 && self.peek()? == Some(0xef) {
+            for expected in UTF8_BOM.bytes() {
+                match self.peek()? {
+                    Some(b) if b == expected => self.pos += 1,
+                    Some(_) => return Err(self.unexpected()),
+                    None => return Err(self.unexpected_end()),
+                }
+            }
+        }
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, JError> {
+        if self.pos == self.filled {
+            self.offset += self.filled;
+            self.pos = 0;
+            self.filled = loop {
+                match self.reader.read(&mut self.buf) {
+                    Ok(n) => break n,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        return Err(JError::new(
+                            ErrorKind::Io,
+                            format!("Failed to read input: {e}"),
+                        ))
+                    }
+                }
+            };
+        }
+        Ok(self.buf[..self.filled].get(self.pos).copied())
+    }
+
+    fn unexpected(&self) -> JError {
+        let found = self.buf[self.pos] as char;
+        JError::new(
+            ErrorKind::Syntax,
+            format!("Unexpected {found:?} at byte {}", self.byte_offset()),
+        )
+    }
+
+    fn unexpected_end(&self) -> JError {
+        JError::new(ErrorKind::Eof, "Unexpected end of input")
+    }
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = Result<JsonEvent<'static>, JError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_event() {
+            Ok(event) => event.map(Ok),
+            Err(e) => {
+                self.expect = Expect::Done;
+                self.pos = self.filled;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// is_high_surrogate Whether the four hex digits of a `\u` escape lie in D800..=DBFF
+fn is_high_surrogate(hex: &[u8]) -> bool {
+    matches!(
+        hex,
+        [b'd' | b'D', b'8' | b'9' | b'a' | b'b' | b'A' | b'B', _, _]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, io::Read};
+
+    use crate::{event::JsonEvent, parse, JsonValue};
+
+    use super::EventReader;
+
+    // Hands out one byte per read, to exercise every buffer boundary
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.split_first() {
+                Some((b, rest)) if !buf.is_empty() => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn it_event_reader() {
+        let source = r#" {"a": [1, -2.5e3, true, null], "b\n": {"c": "dé"}, "e": []} "#;
+        let events = EventReader::new(Trickle(source.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            JsonValue::from_events(events.clone()).unwrap(),
+            parse(source).unwrap()
+        );
+        assert_eq!(events[0], JsonEvent::StartObject(None));
+        assert_eq!(events.len(), 17);
+    }
+
+    #[test]
+    fn it_event_reader_errors() {
+        let err = |source: &str| {
+            EventReader::new(source.as_bytes())
+                .find_map(Result::err)
+                .map(|e| e.to_string())
+        };
+
+        assert_eq!(err("[1, 2]"), None);
+        assert_eq!(err("\u{feff} [1]"), None);
+        assert_eq!(err("[1, 2"), Some("Unexpected end of input".to_string()));
+        assert_eq!(err("[1 2]"), Some("Unexpected '2' at byte 3".to_string()));
+        assert_eq!(
+            err("{\"a\" 1}"),
+            Some("Unexpected '1' at byte 5".to_string())
+        );
+        assert_eq!(
+            err("[01.]"),
+            Some("Invalid number \"01.\" at byte 1".to_string())
+        );
+        assert_eq!(err("[tru]"), Some("Unexpected ']' at byte 4".to_string()));
+        assert_eq!(err("1 2"), Some("Unexpected '2' at byte 2".to_string()));
+        assert_eq!(err(""), Some("Unexpected end of input".to_string()));
+    }
+
+    #[test]
+    fn it_event_reader_string_chunks() {
+        let source = r#"{"long key": "abcdefgh", "esc": "ab\u00e9\ud83d\ude00\n\"é", "s": "ab"}"#;
+        let events = EventReader::with_capacity(3, source.as_bytes())
+            .chunk_strings(2)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            events[1],
+            JsonEvent::Key(Cow::Borrowed("long key")),
+            "keys are never chunked"
+        );
+        assert_eq!(
+            events[2..7],
+            [
+                JsonEvent::StringChunk(Cow::Borrowed("ab")),
+                JsonEvent::StringChunk(Cow::Borrowed("cd")),
+                JsonEvent::StringChunk(Cow::Borrowed("ef")),
+                JsonEvent::StringChunk(Cow::Borrowed("gh")),
+                JsonEvent::StringEnd,
+            ]
+        );
+        let chunks = events
+            .iter()
+            .skip_while(|e| **e != JsonEvent::Key(Cow::Borrowed("esc")))
+            .skip(1)
+            .take_while(|e| **e != JsonEvent::StringEnd)
+            .map(|e| match e {
+                JsonEvent::StringChunk(s) => s.to_string(),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(chunks, ["ab", "é", "😀", "\n", "\"", "é"]);
+        assert_eq!(
+            events[events.len() - 2],
+            JsonEvent::String(Cow::Borrowed("ab"))
+        );
+        assert_eq!(
+            JsonValue::from_events(events).unwrap(),
+            parse(source).unwrap()
+        );
+
+        let mut reader = EventReader::new(r#"["abcdef", 1]"#.as_bytes()).chunk_strings(4);
+        reader.next();
+        reader.next();
+        assert!(!reader.skip_value().unwrap());
+        assert_eq!(
+            reader.collect::<Result<Vec<_>, _>>().unwrap(),
+            [
+                JsonEvent::StringChunk(Cow::Borrowed("ef")),
+                JsonEvent::StringEnd,
+                JsonEvent::Number(1.0),
+                JsonEvent::EndArray,
+            ]
+        );
+
+        let err = EventReader::new(r#"["ab\x"]"#.as_bytes())
+            .chunk_strings(2)
+            .find_map(Result::err);
+        assert!(err.is_some());
+    }
+
+    #[test]
+    fn it_event_reader_skip_value() {
+        let source = r#"[{"x": [1, "]"]}, 2, {"y": 3}]"#;
+        let mut reader = EventReader::with_capacity(3, source.as_bytes());
+
+        assert_eq!(reader.next().unwrap().unwrap(), JsonEvent::StartArray(None));
+        assert!(reader.skip_value().unwrap());
+        assert!(reader.skip_value().unwrap());
+        assert_eq!(
+            reader.next().unwrap().unwrap(),
+            JsonEvent::StartObject(None)
+        );
+        assert!(!reader.skip_value().unwrap());
+        assert!(matches!(reader.next(), Some(Ok(JsonEvent::Key(_)))));
+        assert!(reader.skip_value().unwrap());
+        assert_eq!(reader.next().unwrap().unwrap(), JsonEvent::EndObject);
+        assert!(!reader.skip_value().unwrap());
+        assert_eq!(reader.next().unwrap().unwrap(), JsonEvent::EndArray);
+        assert!(reader.next().is_none());
+        assert_eq!(reader.byte_offset(), source.len());
+    }
+}
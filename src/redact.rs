@@ -0,0 +1,259 @@
+use regex::Regex;
+
+use crate::{
+    error::{ErrorKind, JError},
+    pointer::parse_pointer,
+    JsonValue,
+};
+
+/// Text written in place of a masked value.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// What a [`RedactRule`] does to the values it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedactAction {
+    /// Replace the value with the string [`REDACTED`].
+    #[default]
+    Mask,
+    /// Replace the value with the hex [`JsonValue::canonical_digest`] of it, so equal
+    /// secrets can still be correlated without being revealed.
+    Hash,
+    /// Drop the value: the member from its object or the element from its array. A
+    /// removed document root becomes `null`.
+    Remove,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    // Reference tokens of a JSON pointer
+    Pointer(Vec<String>),
+    // Path segments, each `**` or a key pattern with `*` wildcards
+    Glob(Vec<String>),
+    // Pattern searched for in string values
+    Value(Regex),
+}
+
+/// A value to redact, found by location or by content, and the action to take on it.
+#[derive(Debug, Clone)]
+pub struct RedactRule {
+    matcher: Matcher,
+    action: RedactAction,
+}
+
+impl RedactRule {
+    // pointer Match the single value at an RFC 6901 JSON pointer
+    pub fn pointer(pointer: &str) -> Result<RedactRule, JError> {
+        Ok(RedactRule::new(Matcher::Pointer(parse_pointer(pointer)?)))
+    }
+
+    /// Match values by path, written as `/`-separated segments.
+    ///
+    /// A segment matches one key or array index, with `*` standing for any run of
+    /// characters (`*_token`); a `**` segment matches any number of levels, so
+    /// `**/password` finds every `password` member however deeply it is nested.
+    pub fn key_glob(pattern: &str) -> Result<RedactRule, JError> {
+        let segments = pattern
+            .strip_prefix('/')
+            .unwrap_or(pattern)
+            .split('/')
+            .map(String::from)
+            .collect::<Vec<_>>();
+        if segments.iter().any(String::is_empty) {
+            return Err(JError::new(
+                ErrorKind::Syntax,
+                format!("Key glob {pattern:?} has an empty segment"),
+            ));
+        }
+        Ok(RedactRule::new(Matcher::Glob(segments)))
+    }
+
+    // value_regex Match string values in which the regular expression finds a match
+    pub fn value_regex(pattern: &str) -> Result<RedactRule, JError> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            JError::new(
+                ErrorKind::Other,
+                format!("Invalid pattern {pattern:?}: {e}"),
+            )
+        })?;
+        Ok(RedactRule::new(Matcher::Value(regex)))
+    }
+
+    fn new(matcher: Matcher) -> RedactRule {
+        RedactRule {
+            matcher,
+            action: RedactAction::default(),
+        }
+    }
+
+    pub fn action(mut self, action: RedactAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn hash(self) -> Self {
+        self.action(RedactAction::Hash)
+    }
+
+    pub fn remove(self) -> Self {
+        self.action(RedactAction::Remove)
+    }
+
+    fn matches(&self, path: &[String], value: &JsonValue) -> bool {
+        match &self.matcher {
+            Matcher::Pointer(tokens) => tokens == path,
+            Matcher::Glob(segments) => glob_path(segments, path),
+            Matcher::Value(regex) => matches!(value, JsonValue::String(s) if regex.is_match(s)),
+        }
+    }
+}
+
+/// Apply the rules to every value in the document, returning how many were redacted.
+///
+/// The first matching rule wins and a redacted value is not searched any further.
+/// Objects keep their member order.
+pub fn redact(value: &mut JsonValue, rules: &[RedactRule]) -> usize {
+    let mut path = Vec::new();
+    let mut count = 0;
+    if !redact_node(value, rules, &mut path, &mut count) {
+        *value = JsonValue::Null;
+    }
+    count
+}
+
+// redact_node Redact within `value`, returning `false` when it is to be removed
+fn redact_node(
+    value: &mut JsonValue,
+    rules: &[RedactRule],
+    path: &mut Vec<String>,
+    count: &mut usize,
+) -> bool {
+    if let Some(rule) = rules.iter().find(|rule| rule.matches(path, value)) {
+        *count += 1;
+        match rule.action {
+            RedactAction::Mask => *value = JsonValue::String(String::from(REDACTED)),
+            RedactAction::Hash => {
+                *value = JsonValue::String(format!("{:016x}", value.canonical_digest()))
+            }
+            RedactAction::Remove => return false,
+        }
+        return true;
+    }
+    match value {
+        JsonValue::Array(arr) => {
+            let mut idx = 0;
+            arr.retain_mut(|item| {
+                path.push(idx.to_string());
+                idx += 1;
+                let keep = redact_node(item, rules, path, count);
+                path.pop();
+                keep
+            });
+        }
+        JsonValue::Object(obj) => obj.retain(|key, item| {
+            path.push(key.clone());
+            let keep = redact_node(item, rules, path, count);
+            path.pop();
+            keep
+        }),
+        _ => {}
+    }
+    true
+}
+
+fn glob_path(segments: &[String], path: &[String]) -> bool {
+    match segments.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| glob_path(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path)) => glob_segment(first, segment) && glob_path(rest, path),
+            None => false,
+        },
+    }
+}
+
+// glob_segment Match one path segment against a pattern where `*` is any run of characters
+fn glob_segment(pattern: &str, segment: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = segment.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonValue};
+
+    use super::{glob_segment, redact, RedactRule, REDACTED};
+
+    #[test]
+    fn it_redact() {
+        let mut doc = parse(
+            r#"{"user": {"name": "ann", "password": "hunter2", "api_token": "t1"},
+                "sessions": [{"password": "p", "ip": "10.0.0.1"}, {"note": "card 4111-1111-1111-1111"}],
+                "password": "top", "debug": [1, 2]}"#,
+        )
+        .unwrap();
+        let rules = [
+            RedactRule::pointer("/debug").unwrap().remove(),
+            RedactRule::key_glob("**/password").unwrap(),
+            RedactRule::key_glob("user/*_token").unwrap().hash(),
+            RedactRule::key_glob("/sessions/*/ip").unwrap().remove(),
+            RedactRule::value_regex(r"\d{4}-\d{4}-\d{4}-\d{4}").unwrap(),
+        ];
+
+        assert_eq!(redact(&mut doc, &rules), 7);
+        let token = format!("{:016x}", JsonValue::from("t1").canonical_digest());
+        let expected = parse(&format!(
+            r#"{{"user": {{"name": "ann", "password": "{REDACTED}", "api_token": "{token}"}},
+                "sessions": [{{"password": "{REDACTED}"}}, {{"note": "{REDACTED}"}}],
+                "password": "{REDACTED}"}}"#
+        ))
+        .unwrap();
+        assert_eq!(doc, expected);
+
+        let mut arr = parse("[1, 2, 3, 4]").unwrap();
+        let odd = [
+            RedactRule::key_glob("1").unwrap().remove(),
+            RedactRule::key_glob("3").unwrap().remove(),
+        ];
+        assert_eq!(redact(&mut arr, &odd), 2);
+        assert_eq!(arr, parse("[1, 3]").unwrap());
+
+        let mut root = parse(r#"{"a": 1}"#).unwrap();
+        assert_eq!(
+            redact(&mut root, &[RedactRule::pointer("").unwrap().remove()]),
+            1
+        );
+        assert_eq!(root, JsonValue::Null);
+
+        assert!(RedactRule::pointer("a").is_err());
+        assert!(RedactRule::key_glob("a//b").is_err());
+        assert!(RedactRule::value_regex("(").is_err());
+    }
+
+    #[test]
+    fn it_glob_segments() {
+        assert!(glob_segment("password", "password"));
+        assert!(!glob_segment("password", "passwords"));
+        assert!(glob_segment("*_token", "api_token"));
+        assert!(glob_segment("*", ""));
+        assert!(glob_segment("a*b*c", "aXbYc"));
+        assert!(glob_segment("a*b*c", "abc"));
+        assert!(!glob_segment("a*b*c", "acb"));
+        assert!(!glob_segment("ab*ba", "aba"));
+    }
+}
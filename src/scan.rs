@@ -0,0 +1,263 @@
+//! Byte scanners behind the lexer's string and whitespace tokens.
+//!
+//! Each scanner has a SIMD implementation (SSE2 or AVX2 on x86_64, NEON on aarch64)
+//! and a scalar fallback used for other targets and for the tail of the input.
+
+use logos::{Lexer, Skip};
+
+use crate::token::{check_control_chars, JsonTokenKind};
+
+// lex_string Lex the rest of a string literal whose opening quote was just matched
+pub(crate) fn lex_string(lex: &mut Lexer<JsonTokenKind>) -> Option<()> {
+    let rest = lex.remainder();
+    let bytes = rest.as_bytes();
+    let mut pos = 0;
+    loop {
+        pos += find_special(&bytes[pos..])?;
+        if bytes[pos] == b'"' {
+            lex.bump(pos + 1);
+            return check_control_chars(lex);
+        }
+        match rest[pos + 1..].chars().next()? {
+            '\n' => return None,
+            escaped => pos += 1 + escaped.len_utf8(),
+        }
+    }
+}
+
+// skip_whitespace Extend a matched whitespace character over the rest of the run
+pub(crate) fn skip_whitespace(lex: &mut Lexer<JsonTokenKind>) -> Skip {
+    let len = whitespace_len(lex.remainder().as_bytes());
+    lex.bump(len);
+    Skip
+}
+
+// find_special Index of the first `"` or `\` in `bytes`
+pub(crate) fn find_special(bytes: &[u8]) -> Option<usize> {
+    let (offset, tail) = simd::find_special(bytes);
+    tail.iter()
+        .position(|&b| b == b'"' || b == b'\\')
+        .map(|idx| offset + idx)
+}
+
+// whitespace_len Length of the leading run of JSON whitespace in `bytes`
+pub(crate) fn whitespace_len(bytes: &[u8]) -> usize {
+    let (offset, tail) = simd::whitespace_len(bytes);
+    offset + tail.iter().take_while(|&&b| is_whitespace(b)).count()
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+// The SIMD scanners skip whole blocks that contain no match and hand back the
+// offset of the first unresolved block plus the remaining bytes, which the scalar
+// loop above finishes.
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use core::arch::x86_64::*;
+
+    pub(super) fn find_special(bytes: &[u8]) -> (usize, &[u8]) {
+        #[cfg(feature = "std")]
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked.
+            return unsafe { find_special_avx2(bytes) };
+        }
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        unsafe { find_special_sse2(bytes) }
+    }
+
+    pub(super) fn whitespace_len(bytes: &[u8]) -> (usize, &[u8]) {
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        unsafe { whitespace_len_sse2(bytes) }
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn find_special_sse2(bytes: &[u8]) -> (usize, &[u8]) {
+        let quote = _mm_set1_epi8(b'"' as i8);
+        let backslash = _mm_set1_epi8(b'\\' as i8);
+        let mut offset = 0;
+        while offset + 16 <= bytes.len() {
+            let chunk = _mm_loadu_si128(bytes.as_ptr().add(offset) as *const __m128i);
+            let hits = _mm_or_si128(
+                _mm_cmpeq_epi8(chunk, quote),
+                _mm_cmpeq_epi8(chunk, backslash),
+            );
+            if _mm_movemask_epi8(hits) != 0 {
+                break;
+            }
+            offset += 16;
+        }
+        (offset, &bytes[offset..])
+    }
+
+    #[cfg(feature = "std")]
+    #[target_feature(enable = "avx2")]
+    unsafe fn find_special_avx2(bytes: &[u8]) -> (usize, &[u8]) {
+        let quote = _mm256_set1_epi8(b'"' as i8);
+        let backslash = _mm256_set1_epi8(b'\\' as i8);
+        let mut offset = 0;
+        while offset + 32 <= bytes.len() {
+            let chunk = _mm256_loadu_si256(bytes.as_ptr().add(offset) as *const __m256i);
+            let hits = _mm256_or_si256(
+                _mm256_cmpeq_epi8(chunk, quote),
+                _mm256_cmpeq_epi8(chunk, backslash),
+            );
+            if _mm256_movemask_epi8(hits) != 0 {
+                break;
+            }
+            offset += 32;
+        }
+        (offset, &bytes[offset..])
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn whitespace_len_sse2(bytes: &[u8]) -> (usize, &[u8]) {
+        let mut offset = 0;
+        while offset + 16 <= bytes.len() {
+            let chunk = _mm_loadu_si128(bytes.as_ptr().add(offset) as *const __m128i);
+            let space = _mm_or_si128(
+                _mm_or_si128(
+                    _mm_cmpeq_epi8(chunk, _mm_set1_epi8(b' ' as i8)),
+                    _mm_cmpeq_epi8(chunk, _mm_set1_epi8(b'\t' as i8)),
+                ),
+                _mm_or_si128(
+                    _mm_cmpeq_epi8(chunk, _mm_set1_epi8(b'\n' as i8)),
+                    _mm_cmpeq_epi8(chunk, _mm_set1_epi8(b'\r' as i8)),
+                ),
+            );
+            if _mm_movemask_epi8(space) != 0xffff {
+                break;
+            }
+            offset += 16;
+        }
+        (offset, &bytes[offset..])
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod simd {
+    use core::arch::aarch64::*;
+
+    pub(super) fn find_special(bytes: &[u8]) -> (usize, &[u8]) {
+        let mut offset = 0;
+        // SAFETY: NEON is part of the aarch64 baseline and every load stays in bounds.
+        unsafe {
+            let quote = vdupq_n_u8(b'"');
+            let backslash = vdupq_n_u8(b'\\');
+            while offset + 16 <= bytes.len() {
+                let chunk = vld1q_u8(bytes.as_ptr().add(offset));
+                let hits = vorrq_u8(vceqq_u8(chunk, quote), vceqq_u8(chunk, backslash));
+                if vmaxvq_u8(hits) != 0 {
+                    break;
+                }
+                offset += 16;
+            }
+        }
+        (offset, &bytes[offset..])
+    }
+
+    pub(super) fn whitespace_len(bytes: &[u8]) -> (usize, &[u8]) {
+        let mut offset = 0;
+        // SAFETY: NEON is part of the aarch64 baseline and every load stays in bounds.
+        unsafe {
+            while offset + 16 <= bytes.len() {
+                let chunk = vld1q_u8(bytes.as_ptr().add(offset));
+                let space = vorrq_u8(
+                    vorrq_u8(
+                        vceqq_u8(chunk, vdupq_n_u8(b' ')),
+                        vceqq_u8(chunk, vdupq_n_u8(b'\t')),
+                    ),
+                    vorrq_u8(
+                        vceqq_u8(chunk, vdupq_n_u8(b'\n')),
+                        vceqq_u8(chunk, vdupq_n_u8(b'\r')),
+                    ),
+                );
+                if vminvq_u8(space) == 0 {
+                    break;
+                }
+                offset += 16;
+            }
+        }
+        (offset, &bytes[offset..])
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod simd {
+    pub(super) fn find_special(bytes: &[u8]) -> (usize, &[u8]) {
+        (0, bytes)
+    }
+
+    pub(super) fn whitespace_len(bytes: &[u8]) -> (usize, &[u8]) {
+        (0, bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token::tokenize;
+
+    use super::{find_special, whitespace_len};
+
+    #[test]
+    fn it_find_special() {
+        for len in [0, 1, 15, 16, 17, 31, 32, 33, 70] {
+            let mut bytes = vec![b'a'; len];
+            assert_eq!(find_special(&bytes), None);
+            for idx in [0, len / 2, len.saturating_sub(1)]
+                .into_iter()
+                .filter(|&i| i < len)
+            {
+                bytes[idx] = b'\\';
+                assert_eq!(find_special(&bytes), Some(idx), "len {len}");
+                bytes[idx] = b'"';
+                assert_eq!(find_special(&bytes), Some(idx), "len {len}");
+                bytes[idx] = b'a';
+            }
+        }
+    }
+
+    #[test]
+    fn it_whitespace_len() {
+        for len in [0, 1, 15, 16, 17, 40] {
+            let mut bytes = b" \t\r\n"
+                .repeat(len)
+                .into_iter()
+                .take(len)
+                .collect::<Vec<_>>();
+            assert_eq!(whitespace_len(&bytes), len);
+            bytes.push(b'x');
+            bytes.extend_from_slice(b"   ");
+            assert_eq!(whitespace_len(&bytes), len, "len {len}");
+        }
+    }
+
+    #[test]
+    fn it_lex_long_strings() {
+        let body = "x".repeat(100);
+        let source = format!(
+            "  \n\t  [\"{body}\", \"{body}\\\"{body}\",\n{}\"\\u00e9\"]",
+            " ".repeat(50)
+        );
+        let kinds = tokenize(&source)
+            .into_iter()
+            .map(|t| t.text().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                "[".to_string(),
+                format!("\"{body}\""),
+                ",".to_string(),
+                format!("\"{body}\\\"{body}\""),
+                ",".to_string(),
+                "\"\\u00e9\"".to_string(),
+                "]".to_string(),
+            ]
+        );
+        assert_eq!(tokenize("\"unclosed").len(), 0);
+        assert_eq!(tokenize("\"a\\\nb\"").len(), 0);
+    }
+}
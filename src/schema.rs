@@ -0,0 +1,513 @@
+use indexmap::IndexMap;
+use regex::Regex;
+
+use crate::{
+    error::{ErrorKind, JError},
+    pointer::escape_token,
+    JsonValue,
+};
+
+/// Compiled JSON Schema (draft 2020-12 core subset).
+///
+/// Supported keywords: `type`, `enum`, `const`, `required`, `properties`,
+/// `additionalProperties`, `items`, `minimum`, `maximum`, `exclusiveMinimum`,
+/// `exclusiveMaximum`, `minLength`, `maxLength`, `minItems`, `maxItems` and `pattern`.
+/// Other keywords are ignored.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub(crate) root: Node,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Node {
+    Bool(bool),
+    Rules(Box<Rules>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Rules {
+    pub(crate) types: Option<Vec<Type>>,
+    pub(crate) enumeration: Option<Vec<JsonValue>>,
+    pub(crate) constant: Option<JsonValue>,
+    pub(crate) required: Vec<String>,
+    pub(crate) properties: IndexMap<String, Node>,
+    pub(crate) additional_properties: Option<Node>,
+    pub(crate) items: Option<Node>,
+    pub(crate) minimum: Option<f64>,
+    pub(crate) maximum: Option<f64>,
+    pub(crate) exclusive_minimum: Option<f64>,
+    pub(crate) exclusive_maximum: Option<f64>,
+    pub(crate) min_length: Option<usize>,
+    pub(crate) max_length: Option<usize>,
+    pub(crate) min_items: Option<usize>,
+    pub(crate) max_items: Option<usize>,
+    pub(crate) pattern: Option<Regex>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Type {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Type {
+    fn from_name(name: &str) -> Option<Type> {
+        Some(match name {
+            "null" => Type::Null,
+            "boolean" => Type::Boolean,
+            "integer" => Type::Integer,
+            "number" => Type::Number,
+            "string" => Type::String,
+            "array" => Type::Array,
+            "object" => Type::Object,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Integer => "integer",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+
+    fn matches(self, value: &JsonValue) -> bool {
+        match (self, value) {
+            (Type::Null, JsonValue::Null)
+            | (Type::Boolean, JsonValue::Bool(_))
+            | (Type::Number, JsonValue::Number(_))
+            | (Type::String, JsonValue::String(_))
+            | (Type::Array, JsonValue::Array(_))
+            | (Type::Object, JsonValue::Object(_)) => true,
+            (Type::Integer, JsonValue::Number(n)) => n.fract() == 0.0,
+            _ => false,
+        }
+    }
+}
+
+/// A single validation failure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// JSON pointer of the offending value.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+impl Schema {
+    // compile Compile a schema document
+    pub fn compile(schema: &JsonValue) -> Result<Schema, JError> {
+        Ok(Schema {
+            root: compile_node(schema, "")?,
+        })
+    }
+
+    // validate Validate the value, collecting every violation
+    pub fn validate(&self, value: &JsonValue) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        validate_node(&self.root, value, &mut String::new(), &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    pub fn is_valid(&self, value: &JsonValue) -> bool {
+        self.validate(value).is_ok()
+    }
+
+    /// Infer a schema that every one of `samples` satisfies.
+    ///
+    /// Each location gets the `type`s seen there (`integer` when every number at it is
+    /// integral), objects get the `properties` seen and, as `required`, those present in
+    /// every sample, and arrays an `items` schema covering all their elements.
+    pub fn infer(samples: &[JsonValue]) -> Schema {
+        Schema {
+            root: infer_node(&samples.iter().collect::<Vec<_>>()),
+        }
+    }
+}
+
+fn infer_node(values: &[&JsonValue]) -> Node {
+    if values.is_empty() {
+        return Node::Bool(true);
+    }
+    let mut rules = Rules::default();
+    let mut types = Vec::new();
+    let mut untyped = false;
+    let mut objects = Vec::new();
+    let mut elements = None;
+    for value in values {
+        let ty = match value {
+            JsonValue::Null => Type::Null,
+            JsonValue::Bool(_) => Type::Boolean,
+            JsonValue::Number(n) if n.fract() == 0.0 => Type::Integer,
+            JsonValue::Number(_) => Type::Number,
+            JsonValue::String(_) => Type::String,
+            JsonValue::Array(arr) => {
+                elements.get_or_insert_with(Vec::new).extend(arr);
+                Type::Array
+            }
+            JsonValue::Object(obj) => {
+                objects.push(obj);
+                Type::Object
+            }
+            // Custom literals and exact numbers have no type keyword of their own
+            _ => {
+                untyped = true;
+                continue;
+            }
+        };
+        if !types.contains(&ty) {
+            types.push(ty);
+        }
+    }
+    if types.contains(&Type::Number) {
+        types.retain(|ty| *ty != Type::Integer);
+    }
+    if !untyped {
+        rules.types = Some(types);
+    }
+
+    let mut members = IndexMap::<&str, Vec<&JsonValue>>::new();
+    for obj in &objects {
+        for (name, value) in obj.iter() {
+            members.entry(name).or_default().push(value);
+        }
+    }
+    for (name, values) in members {
+        if values.len() == objects.len() {
+            rules.required.push(name.to_string());
+        }
+        rules
+            .properties
+            .insert(name.to_string(), infer_node(&values));
+    }
+    rules.items = elements.map(|elements| infer_node(&elements));
+    Node::Rules(Box::new(rules))
+}
+
+fn compile_node(schema: &JsonValue, at: &str) -> Result<Node, JError> {
+    let obj = match schema {
+        JsonValue::Bool(b) => return Ok(Node::Bool(*b)),
+        JsonValue::Object(obj) => obj,
+        other => {
+            return Err(JError::new(
+                ErrorKind::Other,
+                format!(
+                    "Schema at {at:?} must be an object or boolean, found {}",
+                    other.type_name()
+                ),
+            ))
+        }
+    };
+
+    let mut rules = Rules::default();
+    for (keyword, v) in obj {
+        let at = format!("{at}/{}", escape_token(keyword));
+        match keyword.as_str() {
+            "type" => {
+                let names = match v {
+                    JsonValue::String(name) => vec![name.as_str()],
+                    JsonValue::Array(names) => names
+                        .iter()
+                        .map(|n| n.as_str().ok_or_else(|| invalid(&at, "a type name")))
+                        .collect::<Result<_, _>>()?,
+                    _ => return Err(invalid(&at, "a type name or an array of them")),
+                };
+                let types = names
+                    .into_iter()
+                    .map(|n| Type::from_name(n).ok_or_else(|| invalid(&at, "a known type")))
+                    .collect::<Result<_, _>>()?;
+                rules.types = Some(types);
+            }
+            "enum" => {
+                let values = v.as_array().ok_or_else(|| invalid(&at, "an array"))?;
+                rules.enumeration = Some(values.clone());
+            }
+            "const" => rules.constant = Some(v.clone()),
+            "required" => {
+                rules.required = v
+                    .as_array()
+                    .ok_or_else(|| invalid(&at, "an array of strings"))?
+                    .iter()
+                    .map(|n| {
+                        n.as_str()
+                            .map(str::to_string)
+                            .ok_or_else(|| invalid(&at, "an array of strings"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "properties" => {
+                let props = v.as_object().ok_or_else(|| invalid(&at, "an object"))?;
+                for (name, sub) in props {
+                    let sub_at = format!("{at}/{}", escape_token(name));
+                    rules
+                        .properties
+                        .insert(name.clone(), compile_node(sub, &sub_at)?);
+                }
+            }
+            "additionalProperties" => rules.additional_properties = Some(compile_node(v, &at)?),
+            "items" => rules.items = Some(compile_node(v, &at)?),
+            "minimum" => rules.minimum = Some(number(v, &at)?),
+            "maximum" => rules.maximum = Some(number(v, &at)?),
+            "exclusiveMinimum" => rules.exclusive_minimum = Some(number(v, &at)?),
+            "exclusiveMaximum" => rules.exclusive_maximum = Some(number(v, &at)?),
+            "minLength" => rules.min_length = Some(count(v, &at)?),
+            "maxLength" => rules.max_length = Some(count(v, &at)?),
+            "minItems" => rules.min_items = Some(count(v, &at)?),
+            "maxItems" => rules.max_items = Some(count(v, &at)?),
+            "pattern" => {
+                let pattern = v.as_str().ok_or_else(|| invalid(&at, "a string"))?;
+                let regex = Regex::new(pattern).map_err(|e| {
+                    JError::new(ErrorKind::Other, format!("Invalid pattern at {at:?}: {e}"))
+                })?;
+                rules.pattern = Some(regex);
+            }
+            _ => {}
+        }
+    }
+    Ok(Node::Rules(Box::new(rules)))
+}
+
+fn invalid(at: &str, expected: &str) -> JError {
+    JError::new(
+        ErrorKind::Other,
+        format!("Schema keyword at {at:?} must be {expected}"),
+    )
+}
+
+fn number(v: &JsonValue, at: &str) -> Result<f64, JError> {
+    v.as_f64().ok_or_else(|| invalid(at, "a number"))
+}
+
+fn count(v: &JsonValue, at: &str) -> Result<usize, JError> {
+    match v.as_f64() {
+        Some(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+        _ => Err(invalid(at, "a non-negative integer")),
+    }
+}
+
+fn validate_node(node: &Node, value: &JsonValue, path: &mut String, out: &mut Vec<Violation>) {
+    let rules = match node {
+        Node::Bool(true) => return,
+        Node::Bool(false) => return report(out, path, "no value is allowed here".to_string()),
+        Node::Rules(rules) => rules,
+    };
+
+    if let Some(types) = &rules.types {
+        if !types.iter().any(|t| t.matches(value)) {
+            let expected = types.iter().map(|t| t.name()).collect::<Vec<_>>();
+            let message = format!(
+                "expected {}, found {}",
+                expected.join(" or "),
+                value.type_name()
+            );
+            return report(out, path, message);
+        }
+    }
+    if let Some(values) = &rules.enumeration {
+        if !values.contains(value) {
+            report(
+                out,
+                path,
+                format!("{value} is not one of the allowed values"),
+            );
+        }
+    }
+    if let Some(constant) = &rules.constant {
+        if constant != value {
+            report(out, path, format!("expected {constant}, found {value}"));
+        }
+    }
+
+    match value {
+        JsonValue::Number(n) => {
+            let n = *n;
+            if let Some(min) = rules.minimum.filter(|min| n < *min) {
+                report(out, path, format!("{n} is less than the minimum {min}"));
+            }
+            if let Some(max) = rules.maximum.filter(|max| n > *max) {
+                report(out, path, format!("{n} is greater than the maximum {max}"));
+            }
+            if let Some(min) = rules.exclusive_minimum.filter(|min| n <= *min) {
+                report(out, path, format!("{n} is not greater than {min}"));
+            }
+            if let Some(max) = rules.exclusive_maximum.filter(|max| n >= *max) {
+                report(out, path, format!("{n} is not less than {max}"));
+            }
+        }
+        JsonValue::String(s) => {
+            let len = s.chars().count();
+            if let Some(min) = rules.min_length.filter(|min| len < *min) {
+                report(
+                    out,
+                    path,
+                    format!("string is shorter than {min} characters"),
+                );
+            }
+            if let Some(max) = rules.max_length.filter(|max| len > *max) {
+                report(out, path, format!("string is longer than {max} characters"));
+            }
+            if let Some(pattern) = rules.pattern.as_ref().filter(|p| !p.is_match(s)) {
+                report(
+                    out,
+                    path,
+                    format!("string does not match {:?}", pattern.as_str()),
+                );
+            }
+        }
+        JsonValue::Array(arr) => {
+            if let Some(min) = rules.min_items.filter(|min| arr.len() < *min) {
+                report(out, path, format!("array has fewer than {min} items"));
+            }
+            if let Some(max) = rules.max_items.filter(|max| arr.len() > *max) {
+                report(out, path, format!("array has more than {max} items"));
+            }
+            if let Some(items) = &rules.items {
+                for (idx, item) in arr.iter().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("/{idx}"));
+                    validate_node(items, item, path, out);
+                    path.truncate(len);
+                }
+            }
+        }
+        JsonValue::Object(obj) => {
+            for name in &rules.required {
+                if !obj.contains_key(name) {
+                    report(out, path, format!("missing required property {name:?}"));
+                }
+            }
+            for (name, item) in obj {
+                let sub = match rules.properties.get(name) {
+                    Some(sub) => sub,
+                    None => match &rules.additional_properties {
+                        Some(sub) => sub,
+                        None => continue,
+                    },
+                };
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(name));
+                validate_node(sub, item, path, out);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn node_is_valid(node: &Node, value: &JsonValue) -> bool {
+    let mut violations = Vec::new();
+    validate_node(node, value, &mut String::new(), &mut violations);
+    violations.is_empty()
+}
+
+fn report(out: &mut Vec<Violation>, path: &str, message: String) {
+    out.push(Violation {
+        path: path.to_string(),
+        message,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::Schema;
+
+    fn schema(source: &str) -> Schema {
+        Schema::compile(&parse(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_validate_valid_document() {
+        let schema = schema(
+            r#"{
+                "type": "object",
+                "required": ["name", "age"],
+                "properties": {
+                    "name": {"type": "string", "minLength": 1},
+                    "age": {"type": "integer", "minimum": 0},
+                    "scores": {"type": "array", "items": {"type": "number", "maximum": 100}}
+                }
+            }"#,
+        );
+        let value = parse(r#"{"name": "John Doe", "age": 30, "scores": [100, 90, 95]}"#).unwrap();
+
+        assert_eq!(schema.validate(&value), Ok(()));
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_validate_collects_all_violations() {
+        let schema = schema(
+            r#"{
+                "type": "object",
+                "required": ["name", "age"],
+                "properties": {
+                    "age": {"type": "integer"},
+                    "scores": {"items": {"maximum": 100}},
+                    "state": {"enum": ["IL", "CA"]},
+                    "zip": {"pattern": "^[0-9]{5}$"}
+                },
+                "additionalProperties": false
+            }"#,
+        );
+        let value = parse(
+            r#"{"age": 30.5, "scores": [100, 101], "state": "NY", "zip": "6270", "extra": 1}"#,
+        )
+        .unwrap();
+
+        let violations = schema
+            .validate(&value)
+            .unwrap_err()
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            vec![
+                r#"/: missing required property "name""#,
+                "/age: expected integer, found number",
+                "/scores/1: 101 is greater than the maximum 100",
+                r#"/state: "NY" is not one of the allowed values"#,
+                r#"/zip: string does not match "^[0-9]{5}$""#,
+                "/extra: no value is allowed here",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_compile_rejects_bad_schema() {
+        assert!(Schema::compile(&parse(r#"{"type": "text"}"#).unwrap()).is_err());
+        assert!(Schema::compile(&parse(r#"{"minItems": -1}"#).unwrap()).is_err());
+        assert!(Schema::compile(&parse(r#"{"pattern": "("}"#).unwrap()).is_err());
+        assert!(Schema::compile(&parse("1").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! JSON text sequences (RFC 7464): each document is preceded by an RS (`0x1E`) byte and
+//! followed by a line feed, as used for log shipping.
+
+use std::io::{BufRead, Write};
+
+use crate::{
+    error::{ErrorKind, JError},
+    parse_bytes,
+    ser::{try_to_string_with, SerializeOptions},
+    JsonValue, Utf8Policy,
+};
+
+/// The record separator starting every sequence element.
+pub const RS: u8 = 0x1e;
+
+/// Reads the documents of a JSON text sequence.
+///
+/// A damaged record (invalid JSON, or a number, `true`, `false` or `null` cut short
+/// before its line feed) is reported as an `Err` item and reading resumes at the next
+/// RS, as the RFC recommends. Empty records are skipped.
+pub struct SeqReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    started: bool,
+    record: usize,
+}
+
+impl<R: BufRead> SeqReader<R> {
+    pub fn new(reader: R) -> Self {
+        SeqReader {
+            reader,
+            buf: Vec::new(),
+            started: false,
+            record: 0,
+        }
+    }
+
+    fn parse_record(&self) -> Result<JsonValue, JError> {
+        let damaged = |e: JError| {
+            JError::new(
+                e.kind(),
+                format!("Damaged record {}: {}", self.record, e.message()),
+            )
+        };
+        let value = parse_bytes(&self.buf, Utf8Policy::Error).map_err(damaged)?;
+        let self_delimited = matches!(
+            value,
+            JsonValue::String(_) | JsonValue::Array(_) | JsonValue::Object(_)
+        );
+        match self_delimited || self.buf.ends_with(b"\n") {
+            true => Ok(value),
+            false => Err(damaged(JError::new(
+                ErrorKind::Eof,
+                "possibly truncated, no line feed after the value".to_string(),
+            ))),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SeqReader<R> {
+    type Item = Result<JsonValue, JError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_until(RS, &mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => {
+                    return Some(Err(JError::new(
+                        ErrorKind::Io,
+                        format!("Failed to read sequence: {e}"),
+                    )))
+                }
+            }
+            if self.buf.last() == Some(&RS) {
+                self.buf.pop();
+            }
+            let leading = !self.started;
+            self.started = true;
+            if self.buf.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if leading {
+                return Some(Err(JError::new(
+                    ErrorKind::Syntax,
+                    "Unexpected data before the first record separator".to_string(),
+                )));
+            }
+            self.record += 1;
+            return Some(self.parse_record());
+        }
+    }
+}
+
+/// Writes documents as a JSON text sequence.
+pub struct SeqWriter<W> {
+    writer: W,
+    options: SerializeOptions,
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W) -> Self {
+        SeqWriter::with_options(writer, SerializeOptions::compact())
+    }
+
+    pub fn with_options(writer: W, options: SerializeOptions) -> Self {
+        SeqWriter { writer, options }
+    }
+
+    // write Append one record: RS, the document, LF
+    pub fn write(&mut self, value: &JsonValue) -> Result<(), JError> {
+        let text = try_to_string_with(value, &self.options)?;
+        let io_err = |e: std::io::Error| {
+            JError::new(ErrorKind::Io, format!("Failed to write sequence: {e}"))
+        };
+        self.writer.write_all(&[RS]).map_err(io_err)?;
+        self.writer.write_all(text.as_bytes()).map_err(io_err)?;
+        self.writer.write_all(b"\n").map_err(io_err)
+    }
+
+    pub fn flush(&mut self) -> Result<(), JError> {
+        self.writer
+            .flush()
+            .map_err(|e| JError::new(ErrorKind::Io, format!("Failed to write sequence: {e}")))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, ser::SerializeOptions, JsonValue};
+
+    use super::{SeqReader, SeqWriter};
+
+    #[test]
+    fn it_write_and_read_sequence() {
+        let values = [
+            parse(r#"{"event": "start", "n": [1, 2]}"#).unwrap(),
+            JsonValue::from(42),
+            JsonValue::from("x"),
+        ];
+        let mut writer = SeqWriter::new(Vec::new());
+        for value in &values {
+            writer.write(value).unwrap();
+        }
+        let bytes = writer.into_inner();
+        assert!(bytes.starts_with(b"\x1e{\"event\":\"start\",\"n\":[1,2]}\n\x1e42\n"));
+
+        let read = SeqReader::new(bytes.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, values);
+
+        let mut pretty = SeqWriter::with_options(Vec::new(), SerializeOptions::pretty());
+        pretty.write(&values[0]).unwrap();
+        let read = SeqReader::new(pretty.into_inner().as_slice()).next();
+        assert_eq!(read.unwrap().unwrap(), values[0]);
+    }
+
+    #[test]
+    fn it_read_sequence_skips_damaged_records() {
+        let input = b"junk\x1e{\"a\": 1}\n\x1e\x1e{\"b\": \n\x1e123\x1e[true]\x1e  \n\x1enull\n";
+        let records = SeqReader::new(&input[..])
+            .map(|r| r.map_err(|e| e.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            records[0],
+            Err("Unexpected data before the first record separator".to_string())
+        );
+        assert_eq!(records[1], Ok(parse(r#"{"a": 1}"#).unwrap()));
+        assert!(records[2]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("Damaged record 2: "));
+        assert_eq!(
+            records[3],
+            Err("Damaged record 3: possibly truncated, no line feed after the value".to_string())
+        );
+        assert_eq!(records[4], Ok(parse("[true]").unwrap()));
+        assert_eq!(records[5], Ok(JsonValue::Null));
+        assert_eq!(records.len(), 6);
+    }
+}
@@ -0,0 +1,1617 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{cmp::Ordering, fmt::Write};
+
+#[cfg(feature = "std")]
+use crate::event::JsonEvent;
+use crate::{
+    error::{ErrorKind, JError},
+    token::{JsonToken, JsonTokenKind},
+    value::unescape,
+    JsonValue,
+};
+
+/// Order in which object members are written.
+///
+/// Only the output is affected; the value's [`Map`](crate::Map) keeps its own order.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum KeyOrder {
+    /// Insertion order of the underlying map.
+    #[default]
+    Insertion,
+    /// Lexicographic order of the keys.
+    Sorted,
+    /// Order given by a user comparator.
+    By(fn(&str, &str) -> Ordering),
+}
+
+/// How numbers with no JSON representation (`NaN` and the infinities) are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    #[default]
+    Null,
+    /// The `NaN`, `Infinity` and `-Infinity` literals, which only lenient parsers accept.
+    Literal,
+    /// Refuse to serialize the value.
+    Error,
+}
+
+/// ANSI SGR parameters (e.g. `"1;34"` for bold blue) used to highlight each kind of token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub key: &'static str,
+    pub string: &'static str,
+    pub number: &'static str,
+    /// `true`, `false` and `null`.
+    pub literal: &'static str,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            key: "1;34",
+            string: "32",
+            number: "36",
+            literal: "35",
+        }
+    }
+}
+
+/// How finite numbers are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Shortest digits that parse back to the same `f64`, always in positional notation
+    /// (`1e-7` is written `0.0000001`).
+    #[default]
+    Shortest,
+    /// Exactly this many decimal places, rounding as needed.
+    Fixed(usize),
+    /// Shortest round-trip digits, in exponent notation (`1.5e-7`, `1e+21`) when the
+    /// decimal exponent is below `min_exp` or at least `max_exp`.
+    Scientific { min_exp: i32, max_exp: i32 },
+}
+
+impl FloatFormat {
+    /// The thresholds of ECMAScript's `Number.prototype.toString` (and `JSON.stringify`).
+    pub const ECMASCRIPT: FloatFormat = FloatFormat::Scientific {
+        min_exp: -6,
+        max_exp: 21,
+    };
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// Spaces per nesting level; `None` writes compact output.
+    pub indent: Option<usize>,
+    pub key_order: KeyOrder,
+    pub non_finite: NonFinite,
+    pub float_format: FloatFormat,
+    /// Highlight tokens with ANSI escape sequences; `None` writes plain JSON.
+    pub colors: Option<Palette>,
+    /// Escape every non-ASCII character as `\uXXXX` (surrogate pairs above the BMP).
+    pub ascii_only: bool,
+    /// Escape `/` as `\/`.
+    pub escape_slash: bool,
+    /// Escape `<`, `>`, `&`, U+2028 and U+2029 so the output can sit inside an HTML
+    /// `<script>` element.
+    pub escape_html: bool,
+    /// With `indent`, write an array or object on one line, as `[1, 2]` or `{"a": 1}`,
+    /// when that line is at most this many characters long.
+    pub inline_width: Option<usize>,
+    /// With `indent`, the width lines should stay within: containers are only written
+    /// on one line if they end before it, and arrays of scalars that do not fit are
+    /// filled with as many elements per line as it allows.
+    pub line_width: Option<usize>,
+    /// With `indent`, pad the keys of each object so that their colons line up.
+    pub align_colons: bool,
+    /// With `indent`, put an empty line between the members of the outermost container.
+    pub blank_lines: bool,
+}
+
+/// Named sets of layout options, see [`SerializeOptions::style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Style {
+    /// No whitespace at all, as [`SerializeOptions::compact`].
+    Compact,
+    /// Two spaces per level and one item per line, as [`SerializeOptions::pretty`].
+    Pretty,
+    /// Two spaces per level, keeping what fits in 80 columns on one line and filling
+    /// long arrays of scalars, like the Prettier formatter.
+    Prettier,
+    /// Four spaces per level, aligned colons, small containers inline within 40
+    /// characters, lines filled up to 100, and blank lines between top-level members.
+    Aligned,
+}
+
+impl Style {
+    pub const ALL: [Style; 4] = [
+        Style::Compact,
+        Style::Pretty,
+        Style::Prettier,
+        Style::Aligned,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Compact => "compact",
+            Style::Pretty => "pretty",
+            Style::Prettier => "prettier",
+            Style::Aligned => "aligned",
+        }
+    }
+
+    // from_name The style of the given `name`, for command lines and configuration files
+    pub fn from_name(name: &str) -> Option<Style> {
+        Style::ALL.into_iter().find(|style| style.name() == name)
+    }
+}
+
+impl SerializeOptions {
+    pub fn compact() -> Self {
+        SerializeOptions::default()
+    }
+
+    pub fn pretty() -> Self {
+        SerializeOptions {
+            indent: Some(2),
+            ..SerializeOptions::default()
+        }
+    }
+
+    // style Options laid out as the named profile, the other settings left at their defaults
+    pub fn style(style: Style) -> Self {
+        match style {
+            Style::Compact => SerializeOptions::compact(),
+            Style::Pretty => SerializeOptions::pretty(),
+            Style::Prettier => SerializeOptions::pretty().line_width(80),
+            Style::Aligned => SerializeOptions::pretty()
+                .indent(4)
+                .inline_width(40)
+                .line_width(100)
+                .align_colons()
+                .blank_lines(),
+        }
+    }
+
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = Some(width);
+        self
+    }
+
+    pub fn sort_keys(self) -> Self {
+        self.key_order(KeyOrder::Sorted)
+    }
+
+    pub fn key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    pub fn non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    pub fn float_format(mut self, float_format: FloatFormat) -> Self {
+        self.float_format = float_format;
+        self
+    }
+
+    pub fn colors(mut self, palette: Palette) -> Self {
+        self.colors = Some(palette);
+        self
+    }
+
+    pub fn ascii_only(mut self) -> Self {
+        self.ascii_only = true;
+        self
+    }
+
+    pub fn escape_slash(mut self) -> Self {
+        self.escape_slash = true;
+        self
+    }
+
+    pub fn escape_html(mut self) -> Self {
+        self.escape_html = true;
+        self
+    }
+
+    pub fn inline_width(mut self, width: usize) -> Self {
+        self.inline_width = Some(width);
+        self
+    }
+
+    pub fn line_width(mut self, width: usize) -> Self {
+        self.line_width = Some(width);
+        self
+    }
+
+    pub fn align_colons(mut self) -> Self {
+        self.align_colons = true;
+        self
+    }
+
+    pub fn blank_lines(mut self) -> Self {
+        self.blank_lines = true;
+        self
+    }
+}
+
+// to_string Serialize the value as compact JSON
+pub fn to_string(value: &JsonValue) -> String {
+    to_string_with(value, &SerializeOptions::compact())
+}
+
+// to_string_pretty Serialize the value as JSON indented by two spaces
+pub fn to_string_pretty(value: &JsonValue) -> String {
+    to_string_with(value, &SerializeOptions::pretty())
+}
+
+/// Serialize the value with the given options.
+///
+/// # Panics
+///
+/// If `options.non_finite` is [`NonFinite::Error`] and the value holds a non-finite
+/// number; use [`try_to_string_with`] to handle that case.
+pub fn to_string_with(value: &JsonValue, options: &SerializeOptions) -> String {
+    try_to_string_with(value, options).unwrap_or_else(|e| panic!("{}", e.message()))
+}
+
+pub fn try_to_string_with(value: &JsonValue, options: &SerializeOptions) -> Result<String, JError> {
+    let mut out = String::new();
+    let mut printer = Printer::new(&mut out, options);
+    match (printer.value(value, 0), printer.rejected) {
+        (Ok(()), _) => Ok(out),
+        (Err(_), Some(n)) => Err(JError::new(
+            ErrorKind::Unsupported,
+            format!("Cannot serialize non-finite number {n}"),
+        )),
+        (Err(_), None) => unreachable!("writing to a String cannot fail"),
+    }
+}
+
+// to_string_colored Serialize the value indented and highlighted for a terminal
+pub fn to_string_colored(value: &JsonValue) -> String {
+    let mut out = String::new();
+    let _ = write_colored(&mut out, value, &SerializeOptions::pretty());
+    out
+}
+
+/// Write the value with ANSI colors, using `options.colors` or the default [`Palette`].
+///
+/// Following <https://no-color.org>, a non-empty `NO_COLOR` environment variable turns
+/// the highlighting off (only checked with the `std` feature).
+pub fn write_colored<W: Write>(
+    w: &mut W,
+    value: &JsonValue,
+    options: &SerializeOptions,
+) -> core::fmt::Result {
+    let colors = match no_color() {
+        true => None,
+        false => Some(options.colors.unwrap_or_default()),
+    };
+    write_value(
+        w,
+        value,
+        &SerializeOptions {
+            colors,
+            ..options.clone()
+        },
+    )
+}
+
+#[cfg(feature = "std")]
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+#[cfg(not(feature = "std"))]
+fn no_color() -> bool {
+    false
+}
+
+pub fn write_value<W: Write>(
+    w: &mut W,
+    value: &JsonValue,
+    options: &SerializeOptions,
+) -> core::fmt::Result {
+    Printer::new(w, options).value(value, 0)
+}
+
+/// Write JSON text straight from tokens, e.g. those of [`tokenize`](crate::tokenize),
+/// without building values.
+///
+/// Only the layout follows `options`: literals are copied as written, so strings keep
+/// their escapes (unless an escape flag asks for re-escaping) and numbers their digits
+/// (unless a [`FloatFormat`] other than `Shortest` is chosen; a literal beyond the `f64`
+/// range such as `1e400` is copied even then). `key_order` and the
+/// width, alignment and blank line options need whole values and are ignored. The tokens are not checked for structure; run
+/// [`validate`](crate::validate) first on untrusted input.
+pub fn write_tokens<'t, 'a: 't, W: Write>(
+    w: &mut W,
+    tokens: impl IntoIterator<Item = &'t JsonToken<'a>>,
+    options: &SerializeOptions,
+) -> Result<(), JError> {
+    let mut printer = Printer::new(w, options);
+    match printer.tokens(tokens) {
+        Ok(()) => Ok(()),
+        Err(TokenError::Token(e)) => Err(e),
+        Err(TokenError::Fmt) => Err(match printer.rejected {
+            Some(n) => JError::new(
+                ErrorKind::Unsupported,
+                format!("Cannot serialize non-finite number {n}"),
+            ),
+            None => JError::new(ErrorKind::Io, "Failed to write JSON: formatter error"),
+        }),
+    }
+}
+
+// tokens_to_string Re-serialize tokens into a new string
+pub fn tokens_to_string<'t, 'a: 't>(
+    tokens: impl IntoIterator<Item = &'t JsonToken<'a>>,
+    options: &SerializeOptions,
+) -> Result<String, JError> {
+    let mut out = String::new();
+    write_tokens(&mut out, tokens, options)?;
+    Ok(out)
+}
+
+enum TokenError {
+    Token(JError),
+    Fmt,
+}
+
+impl From<core::fmt::Error> for TokenError {
+    fn from(_: core::fmt::Error) -> Self {
+        TokenError::Fmt
+    }
+}
+
+struct Printer<'o, W> {
+    w: W,
+    options: &'o SerializeOptions,
+    // Non-finite number refused under `NonFinite::Error`
+    rejected: Option<f64>,
+    // Writing a container on one line, as `[1, 2]`
+    inline: bool,
+    // Characters before the value on its line besides the indentation, i.e. its key
+    lead: usize,
+}
+
+// Budget Writer that fails once more than `left` characters were written to it
+struct Budget {
+    left: usize,
+}
+
+impl Write for Budget {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.left = self
+            .left
+            .checked_sub(s.chars().count())
+            .ok_or(core::fmt::Error)?;
+        Ok(())
+    }
+}
+
+impl<'o, W: Write> Printer<'o, W> {
+    fn new(w: W, options: &'o SerializeOptions) -> Self {
+        Printer {
+            w,
+            options,
+            rejected: None,
+            inline: false,
+            lead: 0,
+        }
+    }
+
+    fn value(&mut self, value: &JsonValue, depth: usize) -> core::fmt::Result {
+        match value {
+            JsonValue::Null => self.literal("null"),
+            JsonValue::Bool(true) => self.literal("true"),
+            JsonValue::Bool(false) => self.literal("false"),
+            JsonValue::Number(n) => self.number(*n),
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(n) => self.paint(|p| p.number, |w| write!(w, "{n}")),
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(n) => self.paint(|p| p.number, |w| write!(w, "{n}")),
+            JsonValue::String(s) => self.string(s),
+            JsonValue::Custom(_, text) => self.literal(text),
+            JsonValue::Array(arr) => {
+                if arr.is_empty() {
+                    return self.w.write_str("[]");
+                }
+                if self.fits_inline(value, depth) {
+                    return self.one_line(value, depth);
+                }
+                if self.fills(arr) {
+                    return self.fill(arr, depth);
+                }
+                self.w.write_char('[')?;
+                for (idx, item) in arr.iter().enumerate() {
+                    if idx > 0 {
+                        self.separator(depth)?;
+                    }
+                    self.newline(depth + 1)?;
+                    self.lead = 0;
+                    self.value(item, depth + 1)?;
+                }
+                self.newline(depth)?;
+                self.w.write_char(']')
+            }
+            JsonValue::Object(obj) => {
+                if obj.is_empty() {
+                    return self.w.write_str("{}");
+                }
+                if self.fits_inline(value, depth) {
+                    return self.one_line(value, depth);
+                }
+                let mut members = obj.iter().collect::<Vec<_>>();
+                match self.options.key_order {
+                    KeyOrder::Insertion => {}
+                    KeyOrder::Sorted => members.sort_by(|a, b| a.0.cmp(b.0)),
+                    KeyOrder::By(cmp) => members.sort_by(|a, b| cmp(a.0, b.0)),
+                }
+
+                // Key widths are needed to align the colons or to know where values start
+                let options = self.options;
+                let measure = options.indent.is_some()
+                    && !self.inline
+                    && (options.align_colons
+                        || options.inline_width.is_some()
+                        || options.line_width.is_some());
+                let widths = members
+                    .iter()
+                    .map(|(key, _)| match measure {
+                        true => self.width_of(|p| p.key_text(key)),
+                        false => 0,
+                    })
+                    .collect::<Vec<_>>();
+                let column = match options.align_colons {
+                    true => widths.iter().copied().max().unwrap_or(0),
+                    false => 0,
+                };
+
+                self.w.write_char('{')?;
+                for (idx, (key, item)) in members.into_iter().enumerate() {
+                    if idx > 0 {
+                        self.separator(depth)?;
+                    }
+                    self.newline(depth + 1)?;
+                    self.key_text(key)?;
+                    let padding = column.saturating_sub(widths[idx]);
+                    write!(self.w, "{:1$}", "", padding)?;
+                    self.colon()?;
+                    self.lead = widths[idx] + padding + 2;
+                    self.value(item, depth + 1)?;
+                }
+                self.newline(depth)?;
+                self.w.write_char('}')
+            }
+        }
+    }
+
+    fn tokens<'t, 'a: 't>(
+        &mut self,
+        tokens: impl IntoIterator<Item = &'t JsonToken<'a>>,
+    ) -> Result<(), TokenError> {
+        let reescape =
+            self.options.ascii_only || self.options.escape_slash || self.options.escape_html;
+        let mut tokens = tokens.into_iter().peekable();
+        let mut depth = 0;
+        while let Some(token) = tokens.next() {
+            let next = tokens.peek().map(|next| &next.kind);
+            match &token.kind {
+                JsonTokenKind::OpenBrace | JsonTokenKind::OpenBracket => {
+                    let close = match token.kind {
+                        JsonTokenKind::OpenBrace => JsonTokenKind::CloseBrace,
+                        _ => JsonTokenKind::CloseBracket,
+                    };
+                    self.w.write_str(token.text())?;
+                    if next == Some(&close) {
+                        if let Some(close) = tokens.next() {
+                            self.w.write_str(close.text())?;
+                        }
+                        continue;
+                    }
+                    depth += 1;
+                    self.newline(depth)?;
+                }
+                JsonTokenKind::CloseBrace | JsonTokenKind::CloseBracket => {
+                    depth = depth.saturating_sub(1);
+                    self.newline(depth)?;
+                    self.w.write_str(token.text())?;
+                }
+                JsonTokenKind::Comma => {
+                    self.w.write_char(',')?;
+                    self.newline(depth)?;
+                }
+                JsonTokenKind::Colon => {
+                    self.w.write_char(':')?;
+                    if self.options.indent.is_some() {
+                        self.w.write_char(' ')?;
+                    }
+                }
+                JsonTokenKind::String => {
+                    let raw = token.text();
+                    let pick = match next {
+                        Some(JsonTokenKind::Colon) => |p: &Palette| p.key,
+                        _ => |p: &Palette| p.string,
+                    };
+                    if reescape {
+                        let body = unescape(&raw[1..raw.len() - 1]).map_err(TokenError::Token)?;
+                        let options = self.options;
+                        self.paint(pick, |w| write_escaped_with(w, &body, options))?;
+                    } else {
+                        self.paint(pick, |w| w.write_str(raw))?;
+                    }
+                }
+                JsonTokenKind::Number(n) => {
+                    // decimal literals keep their digits, even those too large for an f64;
+                    // only `NaN`, `Infinity` and hex literals go through the number policy
+                    let text = token.text();
+                    let verbatim = crate::value::valid_number(text.as_bytes())
+                        && (self.options.float_format == FloatFormat::Shortest || !n.is_finite());
+                    match verbatim {
+                        true => self.paint(|p| p.number, |w| w.write_str(text))?,
+                        false => self.number(*n)?,
+                    }
+                }
+                JsonTokenKind::True => self.literal("true")?,
+                JsonTokenKind::False => self.literal("false")?,
+                JsonTokenKind::Null => self.literal("null")?,
+                JsonTokenKind::Custom(_) => self.literal(token.text())?,
+                JsonTokenKind::Whitespace => {}
+                JsonTokenKind::Error => {
+                    return Err(TokenError::Token(JError::new(
+                        ErrorKind::Syntax,
+                        format!(
+                            "Invalid token {:?} at {}..{}",
+                            token.text(),
+                            token.span.start,
+                            token.span.end
+                        ),
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn number(&mut self, n: f64) -> core::fmt::Result {
+        if n.is_finite() {
+            let format = self.options.float_format;
+            return self.paint(
+                |p| p.number,
+                |w| match format {
+                    FloatFormat::Shortest => write!(w, "{n}"),
+                    FloatFormat::Fixed(places) => write!(w, "{n:.places$}"),
+                    FloatFormat::Scientific { min_exp, max_exp } => {
+                        write_scientific(w, n, min_exp, max_exp)
+                    }
+                },
+            );
+        }
+        match self.options.non_finite {
+            NonFinite::Null => self.literal("null"),
+            NonFinite::Literal if n.is_nan() => self.paint(|p| p.number, |w| w.write_str("NaN")),
+            NonFinite::Literal if n > 0.0 => self.paint(|p| p.number, |w| w.write_str("Infinity")),
+            NonFinite::Literal => self.paint(|p| p.number, |w| w.write_str("-Infinity")),
+            NonFinite::Error => {
+                self.rejected = Some(n);
+                Err(core::fmt::Error)
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) -> core::fmt::Result {
+        let options = self.options;
+        self.paint(|p| p.string, |w| write_escaped_with(w, s, options))
+    }
+
+    // string_part Write part of a string's contents, without the quotes
+    fn string_part(&mut self, s: &str) -> core::fmt::Result {
+        let options = self.options;
+        self.paint(|p| p.string, |w| write_escaped_chars(w, s, options))
+    }
+
+    fn quote(&mut self) -> core::fmt::Result {
+        self.paint(|p| p.string, |w| w.write_char('"'))
+    }
+
+    fn literal(&mut self, literal: &str) -> core::fmt::Result {
+        self.paint(|p| p.literal, |w| w.write_str(literal))
+    }
+
+    // key Write an object key and the colon after it
+    fn key(&mut self, key: &str) -> core::fmt::Result {
+        self.key_text(key)?;
+        self.colon()
+    }
+
+    fn key_text(&mut self, key: &str) -> core::fmt::Result {
+        let options = self.options;
+        self.paint(|p| p.key, |w| write_escaped_with(w, key, options))
+    }
+
+    fn colon(&mut self) -> core::fmt::Result {
+        self.w.write_char(':')?;
+        if self.options.indent.is_some() || self.inline {
+            self.w.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    // separator Write the comma between two items of a container at `depth`
+    fn separator(&mut self, depth: usize) -> core::fmt::Result {
+        self.w.write_char(',')?;
+        if self.inline {
+            return self.w.write_char(' ');
+        }
+        if self.options.blank_lines && depth == 0 && self.options.indent.is_some() {
+            self.w.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    // fits_inline Whether the container may be written on one line at the current position
+    fn fits_inline(&self, value: &JsonValue, depth: usize) -> bool {
+        let Some(indent) = self.options.indent.filter(|_| !self.inline) else {
+            return false;
+        };
+        let column = indent * depth + self.lead;
+        let budget = match (self.options.inline_width, self.options.line_width) {
+            (None, None) => return false,
+            (inline, line) => inline
+                .unwrap_or(usize::MAX)
+                .min(line.map_or(usize::MAX, |line| line.saturating_sub(column))),
+        };
+        let options = SerializeOptions {
+            colors: None,
+            ..self.options.clone()
+        };
+        let mut printer = Printer::new(Budget { left: budget }, &options);
+        printer.inline = true;
+        printer.value(value, depth).is_ok()
+    }
+
+    fn one_line(&mut self, value: &JsonValue, depth: usize) -> core::fmt::Result {
+        self.inline = true;
+        let result = self.value(value, depth);
+        self.inline = false;
+        result
+    }
+
+    // fills Whether the array is one of scalars to be wrapped at `line_width`
+    fn fills(&self, arr: &[JsonValue]) -> bool {
+        self.options.line_width.is_some()
+            && self.options.indent.is_some()
+            && !self.inline
+            && arr
+                .iter()
+                .all(|item| !matches!(item, JsonValue::Array(_) | JsonValue::Object(_)))
+    }
+
+    // fill Write the elements of a scalar array as many per line as `line_width` allows
+    fn fill(&mut self, arr: &[JsonValue], depth: usize) -> core::fmt::Result {
+        let start = self.options.indent.unwrap_or(0) * (depth + 1);
+        let line_width = self.options.line_width.unwrap_or(usize::MAX);
+        let mut column = start;
+        self.w.write_char('[')?;
+        self.newline(depth + 1)?;
+        for (idx, item) in arr.iter().enumerate() {
+            let width = self.width_of(|p| p.value(item, depth + 1));
+            if idx > 0 {
+                self.w.write_char(',')?;
+                column += 1;
+                if column + 1 + width > line_width {
+                    self.newline(depth + 1)?;
+                    column = start;
+                } else {
+                    self.w.write_char(' ')?;
+                    column += 1;
+                }
+            }
+            self.value(item, depth + 1)?;
+            column += width;
+        }
+        self.newline(depth)?;
+        self.w.write_char(']')
+    }
+
+    // width_of Characters `f` writes, leaving out colors
+    fn width_of(&self, f: impl FnOnce(&mut Printer<&mut String>) -> core::fmt::Result) -> usize {
+        let options = SerializeOptions {
+            colors: None,
+            ..self.options.clone()
+        };
+        let mut out = String::new();
+        let mut printer = Printer::new(&mut out, &options);
+        printer.inline = self.inline;
+        let _ = f(&mut printer);
+        out.chars().count()
+    }
+
+    // paint Run `f`, wrapped in the color picked from the palette when colors are on
+    fn paint(
+        &mut self,
+        pick: fn(&Palette) -> &'static str,
+        f: impl FnOnce(&mut W) -> core::fmt::Result,
+    ) -> core::fmt::Result {
+        match self.options.colors.as_ref().map(pick) {
+            Some(code) => {
+                write!(self.w, "\x1b[{code}m")?;
+                f(&mut self.w)?;
+                self.w.write_str("\x1b[0m")
+            }
+            None => f(&mut self.w),
+        }
+    }
+
+    fn newline(&mut self, depth: usize) -> core::fmt::Result {
+        if let Some(width) = self.options.indent.filter(|_| !self.inline) {
+            writeln!(self.w)?;
+            write!(self.w, "{:1$}", "", width * depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// Streams JSON into an [`std::io::Write`] through an internal buffer, so large outputs
+/// never have to be held in memory as a whole.
+///
+/// Whole values and [`JsonEvent`]s can be mixed: events open and close containers and a
+/// value written in between becomes one of their members. Successive top-level values
+/// are separated by a line feed.
+#[cfg(feature = "std")]
+pub struct Serializer<W: std::io::Write> {
+    out: IoWriter<std::io::BufWriter<W>>,
+    options: SerializeOptions,
+    stack: Vec<Frame>,
+    values: usize,
+    // Between the first `StringChunk` of a string and its `StringEnd`
+    in_string: bool,
+}
+
+#[cfg(feature = "std")]
+struct Frame {
+    object: bool,
+    items: usize,
+    // A key was written and its value is still to come
+    after_key: bool,
+}
+
+// IoWriter Adapter keeping the io error a `fmt::Write` cannot return
+#[cfg(feature = "std")]
+struct IoWriter<W> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            core::fmt::Error
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Serializer::with_options(writer, SerializeOptions::compact())
+    }
+
+    pub fn with_options(writer: W, options: SerializeOptions) -> Self {
+        Serializer {
+            out: IoWriter {
+                inner: std::io::BufWriter::new(writer),
+                error: None,
+            },
+            options,
+            stack: Vec::new(),
+            values: 0,
+            in_string: false,
+        }
+    }
+
+    // write_value Write a whole value at the current position
+    pub fn write_value(&mut self, value: &JsonValue) -> Result<(), JError> {
+        self.outside_string()?;
+        self.begin_item()?;
+        let depth = self.stack.len();
+        self.print(|p| p.value(value, depth))
+    }
+
+    // write_event Write one event, checking that the sequence stays well-formed
+    pub fn write_event(&mut self, event: &JsonEvent) -> Result<(), JError> {
+        if !matches!(event, JsonEvent::StringChunk(_) | JsonEvent::StringEnd) {
+            self.outside_string()?;
+        }
+        match event {
+            JsonEvent::StartObject(_) | JsonEvent::StartArray(_) => {
+                let object = matches!(event, JsonEvent::StartObject(_));
+                self.begin_item()?;
+                self.print(|p| p.w.write_char(if object { '{' } else { '[' }))?;
+                self.stack.push(Frame {
+                    object,
+                    items: 0,
+                    after_key: false,
+                });
+                Ok(())
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                let object = matches!(event, JsonEvent::EndObject);
+                let items = match self.stack.last() {
+                    Some(frame) if frame.object == object && !frame.after_key => frame.items,
+                    _ => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!("Unexpected {event:?} event"),
+                        ))
+                    }
+                };
+                self.stack.pop();
+                let depth = self.stack.len();
+                self.print(|p| {
+                    if items > 0 {
+                        p.newline(depth)?;
+                    }
+                    p.w.write_char(if object { '}' } else { ']' })
+                })
+            }
+            JsonEvent::Key(key) => {
+                let frame = match self.stack.last_mut() {
+                    Some(frame) if frame.object && !frame.after_key => frame,
+                    _ => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!("Unexpected key {key:?} event"),
+                        ))
+                    }
+                };
+                frame.after_key = true;
+                frame.items += 1;
+                let first = frame.items == 1;
+                let depth = self.stack.len();
+                self.print(|p| {
+                    if !first {
+                        p.w.write_char(',')?;
+                    }
+                    p.newline(depth)?;
+                    p.key(key)
+                })
+            }
+            JsonEvent::String(s) => {
+                self.begin_item()?;
+                self.print(|p| p.string(s))
+            }
+            JsonEvent::StringChunk(s) => {
+                if !self.in_string {
+                    self.begin_item()?;
+                    self.print(|p| p.quote())?;
+                    self.in_string = true;
+                }
+                self.print(|p| p.string_part(s))
+            }
+            JsonEvent::StringEnd => {
+                if !self.in_string {
+                    return Err(JError::new(ErrorKind::Syntax, "Unexpected end of string"));
+                }
+                self.in_string = false;
+                self.print(|p| p.quote())
+            }
+            JsonEvent::Number(n) => {
+                self.begin_item()?;
+                self.print(|p| p.number(*n))
+            }
+            JsonEvent::RawNumber(literal) => {
+                if !crate::value::valid_number(literal.as_bytes()) {
+                    return Err(JError::new(
+                        ErrorKind::Syntax,
+                        format!("Invalid number literal {literal:?}"),
+                    ));
+                }
+                self.begin_item()?;
+                self.print(|p| p.paint(|p| p.number, |w| w.write_str(literal)))
+            }
+            JsonEvent::Bool(b) => {
+                self.begin_item()?;
+                self.print(|p| p.literal(if *b { "true" } else { "false" }))
+            }
+            JsonEvent::Null => {
+                self.begin_item()?;
+                self.print(|p| p.literal("null"))
+            }
+        }
+    }
+
+    pub fn write_events<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = JsonEvent<'a>>,
+    ) -> Result<(), JError> {
+        events
+            .into_iter()
+            .try_for_each(|event| self.write_event(&event))
+    }
+
+    pub fn flush(&mut self) -> Result<(), JError> {
+        std::io::Write::flush(&mut self.out.inner).map_err(write_error)
+    }
+
+    // into_inner Flush and return the writer, failing if a container is still open
+    pub fn into_inner(mut self) -> Result<W, JError> {
+        if self.in_string {
+            return Err(JError::new(
+                ErrorKind::Eof,
+                "Unclosed string at the end of the output",
+            ));
+        }
+        if let Some(frame) = self.stack.last() {
+            let kind = if frame.object { "object" } else { "array" };
+            return Err(JError::new(
+                ErrorKind::Eof,
+                format!("Unclosed {kind} at the end of the output"),
+            ));
+        }
+        self.flush()?;
+        self.out
+            .inner
+            .into_inner()
+            .map_err(|e| write_error(e.into_error()))
+    }
+
+    fn outside_string(&self) -> Result<(), JError> {
+        match self.in_string {
+            true => Err(JError::new(
+                ErrorKind::Syntax,
+                "Unterminated chunked string",
+            )),
+            false => Ok(()),
+        }
+    }
+
+    // begin_item Write the separator in front of a value and account for it
+    fn begin_item(&mut self) -> Result<(), JError> {
+        let depth = self.stack.len();
+        let separator = match self.stack.last_mut() {
+            None => {
+                self.values += 1;
+                self.values > 1
+            }
+            Some(frame) if frame.object => {
+                if !frame.after_key {
+                    return Err(JError::new(
+                        ErrorKind::Syntax,
+                        "Expected a key event before the value",
+                    ));
+                }
+                frame.after_key = false;
+                return Ok(());
+            }
+            Some(frame) => {
+                frame.items += 1;
+                frame.items > 1
+            }
+        };
+        self.print(|p| match depth {
+            0 if separator => p.w.write_char('\n'),
+            0 => Ok(()),
+            _ => {
+                if separator {
+                    p.w.write_char(',')?;
+                }
+                p.newline(depth)
+            }
+        })
+    }
+
+    fn print(
+        &mut self,
+        f: impl FnOnce(&mut Printer<&mut IoWriter<std::io::BufWriter<W>>>) -> core::fmt::Result,
+    ) -> Result<(), JError> {
+        let mut printer = Printer::new(&mut self.out, &self.options);
+        match (f(&mut printer), printer.rejected) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(n)) => Err(JError::new(
+                ErrorKind::Unsupported,
+                format!("Cannot serialize non-finite number {n}"),
+            )),
+            (Err(_), None) => {
+                Err(write_error(self.out.error.take().unwrap_or_else(|| {
+                    std::io::Error::other("formatter error")
+                })))
+            }
+        }
+    }
+}
+
+/// Push-based counterpart of the event parser: producers call one method per token and
+/// the document streams into an [`std::io::Write`] without a value tree in memory.
+///
+/// Structure is checked as it is written, with the rules of
+/// [`Serializer::write_event`]: keys only directly inside objects, every object value
+/// preceded by its key, closers matching their openers. [`finish`](Self::finish) fails
+/// while a container or chunked string is still open. Methods return the writer so
+/// calls chain with `?`.
+///
+/// ```
+/// use jnom_rs::ser::JsonWriter;
+///
+/// let mut w = JsonWriter::new(Vec::new());
+/// w.begin_object()?.key("ids")?.begin_array()?;
+/// for id in 0..3 {
+///     w.number(id as f64)?;
+/// }
+/// w.end_array()?.key("done")?.bool(true)?.end_object()?;
+/// assert_eq!(w.finish()?, br#"{"ids":[0,1,2],"done":true}"#);
+/// # Ok::<(), jnom_rs::JError>(())
+/// ```
+#[cfg(feature = "std")]
+pub struct JsonWriter<W: std::io::Write> {
+    ser: Serializer<W>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> JsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonWriter::with_options(writer, SerializeOptions::compact())
+    }
+
+    pub fn with_options(writer: W, options: SerializeOptions) -> Self {
+        JsonWriter {
+            ser: Serializer::with_options(writer, options),
+        }
+    }
+
+    pub fn begin_object(&mut self) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::StartObject(None))
+    }
+
+    pub fn end_object(&mut self) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::EndObject)
+    }
+
+    pub fn begin_array(&mut self) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::StartArray(None))
+    }
+
+    pub fn end_array(&mut self) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::EndArray)
+    }
+
+    // key Write the key of the next object member
+    pub fn key(&mut self, key: &str) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::Key(key.into()))
+    }
+
+    pub fn string(&mut self, s: &str) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::String(s.into()))
+    }
+
+    // string_chunk Write part of a string value too long to hold at once, see `end_string`
+    pub fn string_chunk(&mut self, s: &str) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::StringChunk(s.into()))
+    }
+
+    // end_string Close the string opened by `string_chunk`
+    pub fn end_string(&mut self) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::StringEnd)
+    }
+
+    pub fn number(&mut self, n: f64) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::Number(n))
+    }
+
+    pub fn bool(&mut self, b: bool) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::Bool(b))
+    }
+
+    pub fn null(&mut self) -> Result<&mut Self, JError> {
+        self.push(JsonEvent::Null)
+    }
+
+    // value Write a whole value where the next one is expected
+    pub fn value(&mut self, value: &JsonValue) -> Result<&mut Self, JError> {
+        self.ser.write_value(value)?;
+        Ok(self)
+    }
+
+    pub fn flush(&mut self) -> Result<(), JError> {
+        self.ser.flush()
+    }
+
+    // finish Flush and return the writer, failing if the document is incomplete
+    pub fn finish(self) -> Result<W, JError> {
+        self.ser.into_inner()
+    }
+
+    fn push(&mut self, event: JsonEvent) -> Result<&mut Self, JError> {
+        self.ser.write_event(&event)?;
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_error(e: std::io::Error) -> JError {
+    JError::new(ErrorKind::Io, format!("Failed to write JSON: {e}"))
+}
+
+// write_scientific Write a finite number with its shortest round-trip digits, switching to
+// exponent notation outside `min_exp..max_exp`
+pub(crate) fn write_scientific<W: Write>(
+    w: &mut W,
+    n: f64,
+    min_exp: i32,
+    max_exp: i32,
+) -> core::fmt::Result {
+    if n == 0.0 {
+        return w.write_char('0');
+    }
+    if n < 0.0 {
+        w.write_char('-')?;
+    }
+    // `{:e}` yields the shortest round-tripping digits, e.g. `1.2345e3`
+    let sci = format!("{:e}", n.abs());
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let e = exp.parse::<i32>().unwrap_or(0);
+    let point = e + 1;
+
+    if e < min_exp || e >= max_exp {
+        let (first, rest) = digits.split_at(1);
+        w.write_str(first)?;
+        if !rest.is_empty() {
+            write!(w, ".{rest}")?;
+        }
+        write!(w, "e{}{e}", if e < 0 { "" } else { "+" })
+    } else if k <= point {
+        w.write_str(&digits)?;
+        (0..point - k).try_for_each(|_| w.write_char('0'))
+    } else if point > 0 {
+        let (int, frac) = digits.split_at(point as usize);
+        write!(w, "{int}.{frac}")
+    } else {
+        w.write_str("0.")?;
+        (0..-point).try_for_each(|_| w.write_char('0'))?;
+        w.write_str(&digits)
+    }
+}
+
+// write_escaped Write the string as a quoted JSON string literal
+pub(crate) fn write_escaped<W: Write>(w: &mut W, s: &str) -> core::fmt::Result {
+    write_escaped_with(w, s, &SerializeOptions::default())
+}
+
+// write_escaped_with Write the string as a quoted literal, honouring the escape flags
+fn write_escaped_with<W: Write>(
+    w: &mut W,
+    s: &str,
+    options: &SerializeOptions,
+) -> core::fmt::Result {
+    w.write_char('"')?;
+    write_escaped_chars(w, s, options)?;
+    w.write_char('"')
+}
+
+fn write_escaped_chars<W: Write>(
+    w: &mut W,
+    s: &str,
+    options: &SerializeOptions,
+) -> core::fmt::Result {
+    for c in s.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{8}' => w.write_str("\\b")?,
+            '\u{c}' => w.write_str("\\f")?,
+            '/' if options.escape_slash => w.write_str("\\/")?,
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if options.escape_html => {
+                write!(w, "\\u{:04x}", c as u32)?
+            }
+            c if (c as u32) < 0x20 => write!(w, "\\u{:04x}", c as u32)?,
+            c if options.ascii_only && !c.is_ascii() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(w, "\\u{unit:04x}")?;
+                }
+            }
+            c => w.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec};
+
+    use crate::parse;
+
+    use super::{
+        to_string, to_string_colored, to_string_pretty, to_string_with, tokens_to_string,
+        try_to_string_with, write_colored, FloatFormat, KeyOrder, NonFinite, Palette,
+        SerializeOptions,
+    };
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_serialize_compact() {
+        let value =
+            parse(r#"{ "name": "John \"D\"", "scores": [100, 9.5], "ok": true, "x": null }"#)
+                .unwrap();
+
+        assert_eq!(
+            to_string(&value),
+            r#"{"name":"John \"D\"","scores":[100,9.5],"ok":true,"x":null}"#
+        );
+    }
+
+    #[test]
+    fn it_serialize_pretty() {
+        let value = parse(r#"{"a": [1, {}], "b": {"c": []}}"#).unwrap();
+
+        assert_eq!(
+            to_string_pretty(&value),
+            "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": {\n    \"c\": []\n  }\n}"
+        );
+        assert_eq!(format!("{value:#}"), to_string_pretty(&value));
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_serialize_sorted_keys() {
+        let value = parse(r#"{"b": 1, "a": {"z": 1, "y": 2}, "c": 3}"#).unwrap();
+
+        let sorted = to_string_with(&value, &SerializeOptions::compact().sort_keys());
+        assert_eq!(sorted, r#"{"a":{"y":2,"z":1},"b":1,"c":3}"#);
+        // the in-memory order is untouched
+        assert_eq!(to_string(&value), r#"{"b":1,"a":{"z":1,"y":2},"c":3}"#);
+
+        let reversed = SerializeOptions::compact().key_order(KeyOrder::By(|a, b| b.cmp(a)));
+        assert_eq!(
+            to_string_with(&value, &reversed),
+            r#"{"c":3,"b":1,"a":{"z":1,"y":2}}"#
+        );
+    }
+
+    #[test]
+    fn it_serialize_non_finite() {
+        let value = crate::JsonValue::from(vec![f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5]);
+        let with = |policy| SerializeOptions::compact().non_finite(policy);
+
+        assert_eq!(to_string(&value), "[null,null,null,1.5]");
+        assert_eq!(
+            to_string_with(&value, &with(NonFinite::Literal)),
+            "[NaN,Infinity,-Infinity,1.5]"
+        );
+        assert_eq!(
+            try_to_string_with(&value, &with(NonFinite::Error))
+                .unwrap_err()
+                .message(),
+            "Cannot serialize non-finite number NaN"
+        );
+        assert!(try_to_string_with(&parse("[1]").unwrap(), &with(NonFinite::Error)).is_ok());
+    }
+
+    #[test]
+    fn it_serialize_colored() {
+        let value = parse(r#"[{"k": "v"}, 1, null]"#).unwrap();
+        let palette = Palette {
+            key: "k",
+            string: "s",
+            number: "n",
+            literal: "l",
+        };
+        let options = SerializeOptions::compact().colors(palette);
+
+        assert_eq!(
+            to_string_with(&value, &options),
+            "[{\x1b[km\"k\"\x1b[0m:\x1b[sm\"v\"\x1b[0m},\x1b[nm1\x1b[0m,\x1b[lmnull\x1b[0m]"
+        );
+        let mut out = String::new();
+        write_colored(&mut out, &value, &SerializeOptions::compact()).unwrap();
+        assert!(out.starts_with("[{\x1b[1;34m\"k\"\x1b[0m:"));
+        assert!(to_string_colored(&value).contains("\x1b[36m1\x1b[0m,\n"));
+    }
+
+    #[test]
+    fn it_serialize_float_format() {
+        let value = crate::JsonValue::from(vec![1e-7, 0.1, 123.456, 2.0, 1e21, -0.00025]);
+        let with =
+            |format| to_string_with(&value, &SerializeOptions::compact().float_format(format));
+
+        assert_eq!(
+            to_string(&value),
+            "[0.0000001,0.1,123.456,2,1000000000000000000000,-0.00025]"
+        );
+        assert_eq!(
+            with(FloatFormat::Fixed(2)),
+            "[0.00,0.10,123.46,2.00,1000000000000000000000.00,-0.00]"
+        );
+        assert_eq!(
+            with(FloatFormat::ECMASCRIPT),
+            "[1e-7,0.1,123.456,2,1e+21,-0.00025]"
+        );
+        assert_eq!(
+            with(FloatFormat::Scientific {
+                min_exp: -3,
+                max_exp: 2
+            }),
+            "[1e-7,0.1,1.23456e+2,2,1e+21,-2.5e-4]"
+        );
+    }
+
+    #[test]
+    fn it_serialize_tokens() {
+        let source = "{ \"a\" : [ 1.50, -2e3, {} , [ ] ],\n \"b\\u00e9\": \"x/y\", \"c\": null }";
+        let tokens = crate::tokenize(source);
+        let compact = tokens_to_string(&tokens, &SerializeOptions::compact()).unwrap();
+
+        assert_eq!(
+            compact,
+            r#"{"a":[1.50,-2e3,{},[]],"b\u00e9":"x/y","c":null}"#
+        );
+        assert_eq!(parse(&compact).unwrap(), parse(source).unwrap());
+        assert_eq!(
+            tokens_to_string(&tokens, &SerializeOptions::pretty()).unwrap(),
+            "{\n  \"a\": [\n    1.50,\n    -2e3,\n    {},\n    []\n  ],\n  \"b\\u00e9\": \"x/y\",\n  \"c\": null\n}"
+        );
+        let reformatted = SerializeOptions::compact()
+            .escape_slash()
+            .float_format(FloatFormat::ECMASCRIPT);
+        assert_eq!(
+            tokens_to_string(&tokens, &reformatted).unwrap(),
+            r#"{"a":[1.5,-2000,{},[]],"bé":"x\/y","c":null}"#
+        );
+
+        let huge = crate::tokenize("[1e400, -1E+999, 2.50]");
+        for options in [SerializeOptions::compact(), reformatted] {
+            assert_eq!(
+                tokens_to_string(&huge, &options).unwrap(),
+                match options.float_format {
+                    FloatFormat::Shortest => "[1e400,-1E+999,2.50]",
+                    _ => "[1e400,-1E+999,2.5]",
+                }
+            );
+        }
+
+        let recovered = crate::tokenize_recovering("[1, @]");
+        assert_eq!(
+            tokens_to_string(&recovered, &SerializeOptions::compact())
+                .unwrap_err()
+                .message(),
+            "Invalid token \"@\" at 4..5"
+        );
+    }
+
+    #[test]
+    fn it_serialize_escape_options() {
+        let value = crate::JsonValue::from("</script> & é😀\u{2028} a/b");
+
+        assert_eq!(to_string(&value), "\"</script> & é😀\u{2028} a/b\"");
+        assert_eq!(
+            to_string_with(&value, &SerializeOptions::compact().ascii_only()),
+            r#""</script> & \u00e9\ud83d\ude00\u2028 a/b""#
+        );
+        assert_eq!(
+            to_string_with(&value, &SerializeOptions::compact().escape_html()),
+            r#""\u003c/script\u003e \u0026 é😀\u2028 a/b""#
+        );
+        let all = SerializeOptions::compact()
+            .ascii_only()
+            .escape_slash()
+            .escape_html();
+        let escaped = to_string_with(&value, &all);
+        assert_eq!(
+            escaped,
+            r#""\u003c\/script\u003e \u0026 \u00e9\ud83d\ude00\u2028 a\/b""#
+        );
+        assert_eq!(parse(&escaped).unwrap(), value);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_stream_serializer() {
+        use crate::event::JsonEvent;
+
+        use super::Serializer;
+
+        let value = parse(r#"{"a": [1, {}], "b": {"c": []}}"#).unwrap();
+        let mut ser = Serializer::with_options(Vec::new(), SerializeOptions::pretty());
+        ser.write_value(&value).unwrap();
+        ser.write_events(value.events()).unwrap();
+        let out = String::from_utf8(ser.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            out,
+            format!("{}\n{}", to_string_pretty(&value), to_string_pretty(&value))
+        );
+
+        let mut ser = Serializer::new(Vec::new());
+        ser.write_event(&JsonEvent::StartArray(None)).unwrap();
+        for n in 0..3 {
+            ser.write_event(&JsonEvent::Number(n as f64)).unwrap();
+        }
+        ser.write_value(&value).unwrap();
+        ser.write_event(&JsonEvent::EndArray).unwrap();
+        let out = String::from_utf8(ser.into_inner().unwrap()).unwrap();
+        assert_eq!(out, format!("[0,1,2,{}]", to_string(&value)));
+
+        let mut ser = Serializer::new(Vec::new());
+        ser.write_event(&JsonEvent::StartObject(None)).unwrap();
+        assert!(ser.write_event(&JsonEvent::Null).is_err());
+        assert!(ser.write_event(&JsonEvent::EndArray).is_err());
+        assert_eq!(
+            ser.into_inner().unwrap_err().message(),
+            "Unclosed object at the end of the output"
+        );
+
+        let mut ser = Serializer::new(Vec::new());
+        ser.write_events([
+            JsonEvent::StartArray(None),
+            JsonEvent::StringChunk("a\"".into()),
+            JsonEvent::StringChunk("\n".into()),
+            JsonEvent::StringEnd,
+            JsonEvent::Null,
+            JsonEvent::EndArray,
+        ])
+        .unwrap();
+        let out = String::from_utf8(ser.into_inner().unwrap()).unwrap();
+        assert_eq!(out, r#"["a\"\n",null]"#);
+
+        let mut ser = Serializer::new(Vec::new());
+        ser.write_event(&JsonEvent::StartArray(None)).unwrap();
+        ser.write_event(&JsonEvent::RawNumber(
+            "123456789012345678901234567890".into(),
+        ))
+        .unwrap();
+        assert!(ser.write_event(&JsonEvent::RawNumber("1.".into())).is_err());
+        ser.write_event(&JsonEvent::EndArray).unwrap();
+        let out = String::from_utf8(ser.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "[123456789012345678901234567890]");
+
+        let mut ser = Serializer::new(Vec::new());
+        ser.write_event(&JsonEvent::StringChunk("a".into()))
+            .unwrap();
+        assert!(ser.write_event(&JsonEvent::Null).is_err());
+        assert!(ser.into_inner().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_json_writer() {
+        use super::JsonWriter;
+
+        let mut w = JsonWriter::with_options(Vec::new(), SerializeOptions::pretty());
+        w.begin_object()
+            .unwrap()
+            .key("rows")
+            .unwrap()
+            .begin_array()
+            .unwrap();
+        for n in 0..2 {
+            w.begin_object().unwrap();
+            w.key("id").unwrap().number(n as f64).unwrap();
+            w.key("tag").unwrap().string_chunk("a\"").unwrap();
+            w.string_chunk("b").unwrap().end_string().unwrap();
+            w.end_object().unwrap();
+        }
+        w.end_array().unwrap().key("meta").unwrap();
+        w.value(&parse(r#"{"ok": null}"#).unwrap()).unwrap();
+        w.end_object().unwrap();
+        let out = String::from_utf8(w.finish().unwrap()).unwrap();
+        let expected = parse(
+            r#"{"rows": [{"id": 0, "tag": "a\"b"}, {"id": 1, "tag": "a\"b"}], "meta": {"ok": null}}"#,
+        )
+        .unwrap();
+        assert_eq!(out, to_string_pretty(&expected));
+
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_array().unwrap();
+        assert!(w.key("a").is_err());
+        assert!(w.end_object().is_err());
+        w.null().unwrap();
+        assert_eq!(
+            w.finish().unwrap_err().message(),
+            "Unclosed array at the end of the output"
+        );
+
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_object().unwrap();
+        assert!(w.bool(true).is_err());
+        w.key("a").unwrap();
+        assert!(w.end_object().is_err());
+        assert!(w.key("b").is_err());
+    }
+
+    #[test]
+    #[cfg_attr(
+        any(feature = "sorted_map", feature = "hash_map"),
+        ignore = "relies on insertion-ordered objects"
+    )]
+    fn it_style_profiles() {
+        use super::Style;
+
+        let value = parse(
+            r#"{"id": 7, "tags": ["a", "b"], "origin": {"x": 1, "y": 2}, "ids": [100, 200, 300, 400, 500]}"#,
+        )
+        .unwrap();
+        let with = |options: SerializeOptions| to_string_with(&value, &options);
+
+        assert_eq!(
+            with(SerializeOptions::style(Style::Prettier).line_width(25)),
+            r#"{
+  "id": 7,
+  "tags": ["a", "b"],
+  "origin": {
+    "x": 1,
+    "y": 2
+  },
+  "ids": [
+    100, 200, 300, 400,
+    500
+  ]
+}"#
+        );
+        assert_eq!(
+            with(SerializeOptions::pretty().inline_width(10)),
+            r#"{
+  "id": 7,
+  "tags": ["a", "b"],
+  "origin": {
+    "x": 1,
+    "y": 2
+  },
+  "ids": [
+    100,
+    200,
+    300,
+    400,
+    500
+  ]
+}"#
+        );
+        assert_eq!(
+            with(SerializeOptions::style(Style::Aligned)),
+            r#"{
+    "id"    : 7,
+
+    "tags"  : ["a", "b"],
+
+    "origin": {"x": 1, "y": 2},
+
+    "ids"   : [100, 200, 300, 400, 500]
+}"#
+        );
+        assert_eq!(
+            with(SerializeOptions::style(Style::Compact)),
+            to_string(&value)
+        );
+        assert_eq!(
+            with(SerializeOptions::style(Style::Pretty)),
+            to_string_pretty(&value)
+        );
+        assert_eq!(Style::from_name("prettier"), Some(Style::Prettier));
+        assert_eq!(Style::from_name("house"), None);
+
+        // colors do not count towards the width
+        let colored = with(SerializeOptions::style(Style::Prettier).colors(Default::default()));
+        assert!(colored.contains("\"tags\"\u{1b}[0m: ["));
+    }
+}
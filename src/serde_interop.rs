@@ -0,0 +1,83 @@
+use serde_json::{Map, Number, Value};
+
+use crate::JsonValue;
+
+// Member order is carried over as the source map iterates it; `serde_json::Map`
+// only keeps insertion order when serde_json's `preserve_order` feature is on.
+
+impl From<Value> for JsonValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => JsonValue::Null,
+            Value::Bool(b) => JsonValue::Bool(b),
+            Value::Number(n) => JsonValue::Number(n.as_f64().unwrap_or(f64::NAN)),
+            Value::String(s) => JsonValue::String(s),
+            Value::Array(arr) => JsonValue::Array(arr.into_iter().map(JsonValue::from).collect()),
+            Value::Object(obj) => JsonValue::Object(
+                obj.into_iter()
+                    .map(|(k, v)| (k, JsonValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<JsonValue> for Value {
+    // Integral numbers become serde_json integers; non-finite numbers become `null`.
+    fn from(value: JsonValue) -> Self {
+        match value {
+            JsonValue::Null => Value::Null,
+            JsonValue::Bool(b) => Value::Bool(b),
+            JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 9.2e18 => {
+                Value::Number(Number::from(n as i64))
+            }
+            JsonValue::Number(n) => Number::from_f64(n).map_or(Value::Null, Value::Number),
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(n) => rust_decimal::prelude::ToPrimitive::to_f64(&n)
+                .and_then(Number::from_f64)
+                .map_or(Value::Null, Value::Number),
+            #[cfg(feature = "num-bigint")]
+            big @ JsonValue::BigInt(_) => big
+                .as_f64()
+                .and_then(Number::from_f64)
+                .map_or(Value::Null, Value::Number),
+            JsonValue::String(s) | JsonValue::Custom(_, s) => Value::String(s),
+            JsonValue::Array(arr) => Value::Array(arr.into_iter().map(Value::from).collect()),
+            JsonValue::Object(obj) => Value::Object(
+                obj.into_iter()
+                    .map(|(k, v)| (k, Value::from(v)))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{parse, JsonValue};
+
+    #[test]
+    fn it_from_serde_json() {
+        let value = JsonValue::from(json!({"a": [1, 2.5, null], "b": {"c": true, "d": "x"}}));
+
+        assert_eq!(
+            value,
+            parse(r#"{"a": [1, 2.5, null], "b": {"c": true, "d": "x"}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_into_serde_json() {
+        let value = parse(r#"{"a": [1, -2, 2.5], "b": {"c": false}}"#).unwrap();
+
+        let converted = serde_json::Value::from(value);
+        assert_eq!(converted, json!({"a": [1, -2, 2.5], "b": {"c": false}}));
+        assert!(converted["a"][0].is_i64());
+        assert_eq!(
+            serde_json::Value::from(JsonValue::Number(f64::NAN)),
+            serde_json::Value::Null
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use alloc::{string::String, sync::Arc, vec::Vec};
+
+use crate::{
+    pointer::{array_index, parse_pointer},
+    JsonValue, Map,
+};
+
+/// Immutable JSON value whose strings, arrays and objects sit behind [`Arc`]s.
+///
+/// Cloning is cheap whatever the size of the document, and the value is `Send + Sync`,
+/// so one parse can serve read-only queries from many threads. Mutation is copy on
+/// write: [`pointer_mut`](Self::pointer_mut), [`array_mut`](Self::array_mut) and
+/// [`object_mut`](Self::object_mut) copy only the containers along the path that are
+/// still shared with other clones, everything else stays shared.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Arc<str>),
+    Array(Arc<Vec<SharedValue>>),
+    Object(Arc<Map<String, SharedValue>>),
+    Custom(&'static str, Arc<str>),
+    #[cfg(feature = "num-bigint")]
+    BigInt(Arc<num_bigint::BigInt>),
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
+}
+
+// Sharing across threads is the point of the type, so losing it must not compile
+const _: fn() = assert_send_sync::<SharedValue>;
+fn assert_send_sync<T: Send + Sync>() {}
+
+impl SharedValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, SharedValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SharedValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    // as_f64 The number as a float, rounding big integers to the nearest one
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            SharedValue::Number(n) => Some(*n),
+            #[cfg(feature = "num-bigint")]
+            SharedValue::BigInt(n) => num_traits::ToPrimitive::to_f64(n.as_ref()),
+            #[cfg(feature = "rust_decimal")]
+            SharedValue::Decimal(n) => rust_decimal::prelude::ToPrimitive::to_f64(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SharedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[SharedValue]> {
+        match self {
+            SharedValue::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map<String, SharedValue>> {
+        match self {
+            SharedValue::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    // get Look up an object member by key
+    pub fn get(&self, key: &str) -> Option<&SharedValue> {
+        self.as_object()?.get(key)
+    }
+
+    // pointer Look up a value by JSON pointer, as `JsonValue::pointer` does
+    pub fn pointer(&self, pointer: &str) -> Option<&SharedValue> {
+        parse_pointer(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |target, token| match target {
+                SharedValue::Object(obj) => obj.get(token),
+                SharedValue::Array(arr) => arr.get(array_index(token, arr.len())?),
+                _ => None,
+            })
+    }
+
+    /// Name of the variant, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            SharedValue::Null => "null",
+            SharedValue::Bool(_) => "boolean",
+            SharedValue::Number(_) => "number",
+            SharedValue::String(_) => "string",
+            SharedValue::Array(_) => "array",
+            SharedValue::Object(_) => "object",
+            SharedValue::Custom(tag, _) => tag,
+            #[cfg(feature = "num-bigint")]
+            SharedValue::BigInt(_) => "number",
+            #[cfg(feature = "rust_decimal")]
+            SharedValue::Decimal(_) => "number",
+        }
+    }
+
+    // ptr_eq Whether both values are the same allocation, so equal without comparing contents
+    pub fn ptr_eq(&self, other: &SharedValue) -> bool {
+        match (self, other) {
+            (SharedValue::String(a), SharedValue::String(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Array(a), SharedValue::Array(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Object(a), SharedValue::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    // array_mut The elements for writing, copied first if another clone shares them
+    pub fn array_mut(&mut self) -> Option<&mut Vec<SharedValue>> {
+        match self {
+            SharedValue::Array(arr) => Some(Arc::make_mut(arr)),
+            _ => None,
+        }
+    }
+
+    // object_mut The members for writing, copied first if another clone shares them
+    pub fn object_mut(&mut self) -> Option<&mut Map<String, SharedValue>> {
+        match self {
+            SharedValue::Object(obj) => Some(Arc::make_mut(obj)),
+            _ => None,
+        }
+    }
+
+    /// Look up a value by JSON pointer for writing.
+    ///
+    /// Each container on the way that another clone still shares is copied, shallowly:
+    /// the copy points at the same children, so the cost is the size of those
+    /// containers, not of the document. Nothing is copied if the pointer leads nowhere.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut SharedValue> {
+        let tokens = parse_pointer(pointer).ok()?;
+        self.pointer(pointer)?;
+        tokens.iter().try_fold(self, |target, token| match target {
+            SharedValue::Object(obj) => Arc::make_mut(obj).get_mut(token.as_str()),
+            SharedValue::Array(arr) => {
+                let idx = array_index(token, arr.len())?;
+                Arc::make_mut(arr).get_mut(idx)
+            }
+            _ => None,
+        })
+    }
+
+    // insert Set an object member through copy on write, returning the value it replaced
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<SharedValue>,
+    ) -> Option<SharedValue> {
+        self.object_mut()?.insert(key.into(), value.into())
+    }
+
+    // to_value Copy the value into an owned tree
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            SharedValue::Null => JsonValue::Null,
+            SharedValue::Bool(b) => JsonValue::Bool(*b),
+            SharedValue::Number(n) => JsonValue::Number(*n),
+            SharedValue::String(s) => JsonValue::String(String::from(s.as_ref())),
+            SharedValue::Array(arr) => JsonValue::Array(arr.iter().map(Self::to_value).collect()),
+            SharedValue::Object(obj) => {
+                JsonValue::Object(obj.iter().map(|(k, v)| (k.clone(), v.to_value())).collect())
+            }
+            SharedValue::Custom(tag, text) => JsonValue::Custom(tag, String::from(text.as_ref())),
+            #[cfg(feature = "num-bigint")]
+            SharedValue::BigInt(n) => JsonValue::BigInt(n.as_ref().clone()),
+            #[cfg(feature = "rust_decimal")]
+            SharedValue::Decimal(n) => JsonValue::Decimal(*n),
+        }
+    }
+}
+
+impl JsonValue {
+    // into_shared Move the value behind shared pointers, see `SharedValue`
+    pub fn into_shared(self) -> SharedValue {
+        SharedValue::from(self)
+    }
+}
+
+impl From<JsonValue> for SharedValue {
+    fn from(value: JsonValue) -> Self {
+        match value {
+            JsonValue::Null => SharedValue::Null,
+            JsonValue::Bool(b) => SharedValue::Bool(b),
+            JsonValue::Number(n) => SharedValue::Number(n),
+            JsonValue::String(s) => SharedValue::String(Arc::from(s)),
+            JsonValue::Array(arr) => {
+                SharedValue::Array(Arc::new(arr.into_iter().map(SharedValue::from).collect()))
+            }
+            JsonValue::Object(obj) => SharedValue::Object(Arc::new(
+                obj.into_iter()
+                    .map(|(k, v)| (k, SharedValue::from(v)))
+                    .collect(),
+            )),
+            JsonValue::Custom(tag, text) => SharedValue::Custom(tag, Arc::from(text)),
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(n) => SharedValue::BigInt(Arc::new(n)),
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(n) => SharedValue::Decimal(n),
+        }
+    }
+}
+
+impl From<&SharedValue> for JsonValue {
+    fn from(value: &SharedValue) -> Self {
+        value.to_value()
+    }
+}
+
+impl From<&str> for SharedValue {
+    fn from(s: &str) -> Self {
+        SharedValue::String(Arc::from(s))
+    }
+}
+
+impl From<f64> for SharedValue {
+    fn from(n: f64) -> Self {
+        SharedValue::Number(n)
+    }
+}
+
+impl From<bool> for SharedValue {
+    fn from(b: bool) -> Self {
+        SharedValue::Bool(b)
+    }
+}
+
+impl core::fmt::Display for SharedValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.to_value(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use crate::parse;
+
+    use super::SharedValue;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn it_shared_value_across_threads() {
+        use std::thread;
+
+        let doc = parse(r#"{"users": [{"name": "ann"}, {"name": "bo"}], "count": 2}"#)
+            .unwrap()
+            .into_shared();
+        let handles = (0..4)
+            .map(|idx| {
+                let doc = doc.clone();
+                thread::spawn(move || {
+                    let user = doc.pointer(&format!("/users/{}/name", idx % 2)).unwrap();
+                    String::from(user.as_str().unwrap())
+                })
+            })
+            .collect::<Vec<_>>();
+        let names = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["ann", "bo", "ann", "bo"]);
+        assert_eq!(doc.get("count").and_then(SharedValue::as_f64), Some(2.0));
+        assert_eq!(parse(&doc.to_string()).unwrap(), doc.to_value());
+    }
+
+    #[test]
+    fn it_shared_value_copy_on_write() {
+        let original = parse(r#"{"a": {"x": [1, 2]}, "b": {"y": "big"}}"#)
+            .unwrap()
+            .into_shared();
+        let mut edited = original.clone();
+        assert!(edited.ptr_eq(&original));
+
+        *edited.pointer_mut("/a/x/1").unwrap() = SharedValue::from(3.0);
+        edited.insert("c", true);
+        assert!(edited.pointer_mut("/a/missing").is_none());
+
+        assert_eq!(
+            original.to_value(),
+            parse(r#"{"a": {"x": [1, 2]}, "b": {"y": "big"}}"#).unwrap()
+        );
+        assert_eq!(
+            edited.to_value(),
+            parse(r#"{"a": {"x": [1, 3]}, "b": {"y": "big"}, "c": true}"#).unwrap()
+        );
+        // the untouched subtree is still shared, the edited path was copied
+        assert!(edited.get("b").unwrap().ptr_eq(original.get("b").unwrap()));
+        assert!(!edited.get("a").unwrap().ptr_eq(original.get("a").unwrap()));
+
+        // a value no other clone holds is written in place
+        let mut sole = SharedValue::from(parse("[1]").unwrap());
+        let before = match &sole {
+            SharedValue::Array(arr) => Arc::as_ptr(arr),
+            _ => unreachable!(),
+        };
+        sole.array_mut().unwrap().push(SharedValue::Null);
+        assert!(matches!(&sole, SharedValue::Array(arr) if Arc::as_ptr(arr) == before));
+        assert_eq!(sole.as_array().map(<[_]>::len), Some(2));
+    }
+}
@@ -0,0 +1,198 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{event::JsonEvent, JsonValue, Map};
+
+/// Shape of a document: how deep it nests, what it is made of and which keys it uses.
+///
+/// Depth counts containers, so a scalar document has depth 0 and `[[1]]` depth 2.
+/// String bytes are those of the decoded string values, keys excluded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub max_depth: usize,
+    pub nulls: usize,
+    pub bools: usize,
+    pub numbers: usize,
+    pub strings: usize,
+    pub arrays: usize,
+    pub objects: usize,
+    pub string_bytes: usize,
+    pub largest_array: usize,
+    pub largest_object: usize,
+    pub key_counts: Map<String, usize>,
+}
+
+impl Stats {
+    // values Total number of values, containers included
+    pub fn values(&self) -> usize {
+        self.nulls + self.bools + self.numbers + self.strings + self.arrays + self.objects
+    }
+
+    // most_common_keys The `n` most used keys, most frequent first and ties by key
+    pub fn most_common_keys(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut keys = self
+            .key_counts
+            .iter()
+            .map(|(key, count)| (key.as_str(), *count))
+            .collect::<Vec<_>>();
+        keys.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        keys.truncate(n);
+        keys
+    }
+
+    // from_events Gather statistics over an event sequence
+    pub fn from_events<'a>(events: impl IntoIterator<Item = JsonEvent<'a>>) -> Stats {
+        let mut collector = StatsCollector::new();
+        for event in events {
+            collector.push(&event);
+        }
+        collector.finish()
+    }
+}
+
+impl JsonValue {
+    // stats Gather statistics over the value
+    pub fn stats(&self) -> Stats {
+        Stats::from_events(self.events())
+    }
+}
+
+/// Accumulates [`Stats`] from events pushed one at a time, keeping only the stack of
+/// open containers, so documents of any size can be measured from a stream.
+#[derive(Debug, Default)]
+pub struct StatsCollector {
+    stats: Stats,
+    // Whether each open container is an object, and its members or elements so far
+    open: Vec<(bool, usize)>,
+    in_string: bool,
+}
+
+impl StatsCollector {
+    pub fn new() -> Self {
+        StatsCollector::default()
+    }
+
+    pub fn push(&mut self, event: &JsonEvent) {
+        let stats = &mut self.stats;
+        match event {
+            JsonEvent::Key(key) => {
+                if let Some((_, members)) = self.open.last_mut() {
+                    *members += 1;
+                }
+                match stats.key_counts.get_mut(key.as_ref()) {
+                    Some(count) => *count += 1,
+                    None => {
+                        stats.key_counts.insert(String::from(key.as_ref()), 1);
+                    }
+                }
+                return;
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                let (object, items) = self.open.pop().unwrap_or_default();
+                let largest = match object {
+                    true => &mut stats.largest_object,
+                    false => &mut stats.largest_array,
+                };
+                *largest = (*largest).max(items);
+                return;
+            }
+            JsonEvent::StringChunk(s) if self.in_string => {
+                stats.string_bytes += s.len();
+                return;
+            }
+            JsonEvent::StringEnd => {
+                self.in_string = false;
+                return;
+            }
+            _ => {}
+        }
+
+        // A value starts, one more element of the enclosing array if there is one
+        if let Some((false, elements)) = self.open.last_mut() {
+            *elements += 1;
+        }
+        match event {
+            JsonEvent::StartObject(_) => {
+                stats.objects += 1;
+                self.open.push((true, 0));
+            }
+            JsonEvent::StartArray(_) => {
+                stats.arrays += 1;
+                self.open.push((false, 0));
+            }
+            JsonEvent::String(s) | JsonEvent::StringChunk(s) => {
+                self.in_string = matches!(event, JsonEvent::StringChunk(_));
+                stats.strings += 1;
+                stats.string_bytes += s.len();
+            }
+            JsonEvent::Number(_) | JsonEvent::RawNumber(_) => stats.numbers += 1,
+            JsonEvent::Bool(_) => stats.bools += 1,
+            JsonEvent::Null => stats.nulls += 1,
+            _ => {}
+        }
+        stats.max_depth = stats.max_depth.max(self.open.len());
+    }
+
+    pub fn finish(self) -> Stats {
+        self.stats
+    }
+}
+
+/// Gather statistics over a document read from `reader`, without building it in memory.
+///
+/// Long strings are measured chunk by chunk, so memory use stays bounded by the nesting
+/// depth and the number of distinct keys.
+#[cfg(feature = "std")]
+pub fn stats_from_reader<R: std::io::Read>(reader: R) -> Result<Stats, crate::JError> {
+    let mut collector = StatsCollector::new();
+    for event in crate::reader::EventReader::new(reader).chunk_strings(64 * 1024) {
+        collector.push(&event?);
+    }
+    Ok(collector.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::parse;
+
+    use super::Stats;
+
+    #[test]
+    fn it_stats() {
+        let source = r#"{"users": [{"id": 1, "name": "ann", "tags": []}, {"id": 2, "name": "bo", "admin": true}],
+                         "next": null, "total": 2}"#;
+        let stats = parse(source).unwrap().stats();
+
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(
+            (stats.nulls, stats.bools, stats.numbers, stats.strings),
+            (1, 1, 3, 2)
+        );
+        assert_eq!((stats.arrays, stats.objects), (2, 3));
+        assert_eq!(stats.values(), 12);
+        assert_eq!(stats.string_bytes, 5);
+        assert_eq!((stats.largest_array, stats.largest_object), (2, 3));
+        assert_eq!(
+            stats.most_common_keys(3),
+            vec![("id", 2), ("name", 2), ("admin", 1)]
+        );
+        #[cfg(feature = "std")]
+        {
+            use super::stats_from_reader;
+
+            assert_eq!(stats_from_reader(source.as_bytes()).unwrap(), stats);
+
+            let long = format!("[\"{}\", \"é\"]", "x".repeat(100_000));
+            let streamed = stats_from_reader(long.as_bytes()).unwrap();
+            assert_eq!((streamed.strings, streamed.string_bytes), (2, 100_002));
+            assert_eq!((streamed.largest_array, streamed.max_depth), (2, 1));
+
+            assert!(stats_from_reader(&b"[1, "[..]).is_err());
+        }
+
+        assert_eq!(parse("7").unwrap().stats().max_depth, 0);
+        assert_eq!(parse("[[], [[]]]").unwrap().stats().max_depth, 3);
+        assert_eq!(Stats::default().values(), 0);
+    }
+}
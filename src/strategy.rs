@@ -0,0 +1,114 @@
+//! [proptest] strategies for JSON values, to property-test code that consumes JSON.
+//!
+//! `any::<JsonValue>()` works as for any other type; [`json_value`] and
+//! `any_with::<JsonValue>(params)` take [`ValueParams`] to size the documents.
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    prop_oneof,
+    strategy::{BoxedStrategy, Just, Strategy},
+    string::string_regex,
+};
+
+use crate::{JsonValue, Map};
+
+/// Size of the documents a [`json_value`] strategy produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueParams {
+    /// Levels of nested arrays and objects.
+    pub depth: u32,
+    /// Values per document proptest aims for, containers included.
+    pub size: u32,
+    /// Elements or members per array or object.
+    pub items: u32,
+    /// Characters per string and object key.
+    pub string_len: u32,
+}
+
+impl Default for ValueParams {
+    fn default() -> Self {
+        ValueParams {
+            depth: 4,
+            size: 64,
+            items: 8,
+            string_len: 16,
+        }
+    }
+}
+
+/// Strategy for JSON values of the given size.
+///
+/// Values are nulls, booleans, finite numbers (integers as often as not), strings of
+/// any Unicode text, and arrays and objects of those. Custom literals and exact numbers
+/// are never produced. Shrinking goes towards smaller containers and simpler scalars.
+pub fn json_value(params: ValueParams) -> BoxedStrategy<JsonValue> {
+    let string = string_regex(&format!("\\PC{{0,{}}}", params.string_len))
+        .expect("a valid character class")
+        .boxed();
+    let leaf = prop_oneof![
+        Just(JsonValue::Null),
+        any::<bool>().prop_map(JsonValue::Bool),
+        (-1_000_000_i64..1_000_000).prop_map(|n| JsonValue::Number(n as f64)),
+        any::<f64>()
+            .prop_filter("finite number", |n| n.is_finite())
+            .prop_map(JsonValue::Number),
+        string.clone().prop_map(JsonValue::String),
+    ];
+    let items = params.items as usize;
+    leaf.prop_recursive(params.depth, params.size, params.items, move |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..=items).prop_map(JsonValue::Array),
+            vec((string.clone(), inner), 0..=items)
+                .prop_map(|members| JsonValue::Object(members.into_iter().collect::<Map<_, _>>())),
+        ]
+    })
+    .boxed()
+}
+
+impl Arbitrary for JsonValue {
+    type Parameters = ValueParams;
+    type Strategy = BoxedStrategy<JsonValue>;
+
+    fn arbitrary_with(params: ValueParams) -> Self::Strategy {
+        json_value(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        parse,
+        ser::{to_string, to_string_pretty},
+        JsonValue,
+    };
+
+    use super::{json_value, ValueParams};
+
+    fn depth(value: &JsonValue) -> u32 {
+        match value {
+            JsonValue::Array(arr) => 1 + arr.iter().map(depth).max().unwrap_or(0),
+            JsonValue::Object(obj) => 1 + obj.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn it_round_trips(value in any::<JsonValue>()) {
+            let json = to_string(&value);
+            prop_assert_eq!(to_string(&parse(&json).unwrap()), json.clone());
+            prop_assert_eq!(to_string(&parse(&to_string_pretty(&value)).unwrap()), json);
+        }
+
+        #[test]
+        fn it_respects_params(value in json_value(ValueParams { depth: 2, items: 3, ..ValueParams::default() })) {
+            prop_assert!(depth(&value) <= 2);
+            if let JsonValue::Array(arr) = &value {
+                prop_assert!(arr.len() <= 3);
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use alloc::{borrow::Cow, format, vec::Vec};
+
+use logos::Lexer;
+
+use crate::{
+    error::{ErrorKind, JError},
+    token::{lex_error, lexer, JsonTokenKind as Token},
+    value::unescape,
+    JsonValue,
+};
+
+/// Kind of a tape record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapeKind {
+    Null,
+    True,
+    False,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// One record of the tape.
+///
+/// Scalars cover `len` bytes of the source starting at `offset` (string records
+/// exclude the quotes). Containers store their child count in `len`: elements for
+/// arrays, members for objects. Object members are laid out as a string record for
+/// the key followed by the value's records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Record {
+    kind: TapeKind,
+    offset: usize,
+    len: usize,
+    /// Tape index just past this value, so whole subtrees can be skipped.
+    next: usize,
+}
+
+/// Flat document representation built in a single pass over the source.
+///
+/// Nothing is materialized up front: numbers are parsed and strings decoded only
+/// when read through a [`Cursor`].
+#[derive(Debug, Clone)]
+pub struct Tape<'a> {
+    source: &'a str,
+    records: Vec<Record>,
+}
+
+impl<'a> Tape<'a> {
+    // parse Build the tape of a complete JSON document
+    pub fn parse(source: &'a str) -> Result<Tape<'a>, JError> {
+        let mut builder = TapeBuilder {
+            lexer: lexer(source),
+            records: Vec::new(),
+        };
+        let first = builder.next_token()?;
+        builder.value(first)?;
+        match builder.lexer.next() {
+            None => Ok(Tape {
+                source,
+                records: builder.records,
+            }),
+            Some(_) => Err(JError::new(
+                ErrorKind::Syntax,
+                format!(
+                    "Unexpected trailing input at {}",
+                    builder.lexer.span().start
+                ),
+            )),
+        }
+    }
+
+    pub fn root(&self) -> Cursor<'_, 'a> {
+        Cursor {
+            tape: self,
+            idx: Some(0),
+        }
+    }
+
+    // len Number of records on the tape
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+struct TapeBuilder<'a> {
+    lexer: Lexer<'a, Token>,
+    records: Vec<Record>,
+}
+
+impl TapeBuilder<'_> {
+    fn next_token(&mut self) -> Result<Token, JError> {
+        match self.lexer.next() {
+            Some(Ok(token)) => Ok(token),
+            Some(Err(_)) => Err(lex_error(&self.lexer)),
+            None => Err(JError::new(ErrorKind::Eof, "Unexpected end of input")),
+        }
+    }
+
+    fn push(&mut self, kind: TapeKind, offset: usize, len: usize) -> usize {
+        let idx = self.records.len();
+        self.records.push(Record {
+            kind,
+            offset,
+            len,
+            next: idx + 1,
+        });
+        idx
+    }
+
+    fn value(&mut self, token: Token) -> Result<(), JError> {
+        let span = self.lexer.span();
+        match token {
+            Token::Null => self.push(TapeKind::Null, span.start, span.len()),
+            Token::True => self.push(TapeKind::True, span.start, span.len()),
+            Token::False => self.push(TapeKind::False, span.start, span.len()),
+            Token::Number(_) => self.push(TapeKind::Number, span.start, span.len()),
+            Token::String => self.push(TapeKind::String, span.start + 1, span.len() - 2),
+            Token::OpenBracket => return self.container(TapeKind::Array, Token::CloseBracket),
+            Token::OpenBrace => return self.container(TapeKind::Object, Token::CloseBrace),
+            other => {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("JsonToken Kind {other} does not match at {}", span.start),
+                ))
+            }
+        };
+        Ok(())
+    }
+
+    fn container(&mut self, kind: TapeKind, close: Token) -> Result<(), JError> {
+        let idx = self.push(kind, self.lexer.span().start, 0);
+        let mut count = 0;
+        let mut token = self.next_token()?;
+        if token != close {
+            loop {
+                if kind == TapeKind::Object {
+                    let span = self.lexer.span();
+                    match token {
+                        Token::String => {
+                            self.push(TapeKind::String, span.start + 1, span.len() - 2)
+                        }
+                        other => {
+                            return Err(JError::new(
+                                ErrorKind::Syntax,
+                                format!("Expected an object key, found {other} at {}", span.start),
+                            ))
+                        }
+                    };
+                    self.expect(Token::Colon)?;
+                    token = self.next_token()?;
+                }
+                self.value(token)?;
+                count += 1;
+
+                match self.next_token()? {
+                    Token::Comma => token = self.next_token()?,
+                    t if t == close => break,
+                    other => {
+                        return Err(JError::new(
+                            ErrorKind::Syntax,
+                            format!(
+                                "Expected ',' or '{close}', found {other} at {}",
+                                self.lexer.span().start
+                            ),
+                        ))
+                    }
+                }
+            }
+        }
+        let next = self.records.len();
+        let record = &mut self.records[idx];
+        record.len = count;
+        record.next = next;
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), JError> {
+        match self.next_token()? {
+            t if t == expected => Ok(()),
+            other => Err(JError::new(
+                ErrorKind::Syntax,
+                format!(
+                    "Expected '{expected}', found {other} at {}",
+                    self.lexer.span().start
+                ),
+            )),
+        }
+    }
+}
+
+/// Lazy position in a [`Tape`].
+///
+/// Navigating to a missing member or element yields a cursor that points nowhere;
+/// every accessor on it returns `None`, so lookups chain without intermediate checks:
+/// `tape.root().get("scores").at(1).as_i64()`.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'t, 'a> {
+    tape: &'t Tape<'a>,
+    idx: Option<usize>,
+}
+
+impl<'t, 'a> Cursor<'t, 'a> {
+    fn record(&self) -> Option<&'t Record> {
+        self.idx.map(|idx| &self.tape.records[idx])
+    }
+
+    fn text(&self) -> Option<&'a str> {
+        let record = self.record()?;
+        Some(&self.tape.source[record.offset..record.offset + record.len])
+    }
+
+    fn at_index(&self, idx: Option<usize>) -> Cursor<'t, 'a> {
+        Cursor {
+            tape: self.tape,
+            idx,
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.idx.is_some()
+    }
+
+    pub fn kind(&self) -> Option<TapeKind> {
+        self.record().map(|r| r.kind)
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.kind() == Some(TapeKind::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind()? {
+            TapeKind::True => Some(true),
+            TapeKind::False => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.number_text()?.parse().ok()
+    }
+
+    // as_i64 Read an integer literal exactly, without going through f64
+    pub fn as_i64(&self) -> Option<i64> {
+        self.number_text()?.parse().ok()
+    }
+
+    fn number_text(&self) -> Option<&'a str> {
+        match self.kind()? {
+            TapeKind::Number => self.text(),
+            _ => None,
+        }
+    }
+
+    // as_str Read a string, borrowing from the source unless it contains escapes
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        match self.kind()? {
+            TapeKind::String => decode(self.text()?),
+            _ => None,
+        }
+    }
+
+    // len Number of elements of an array or members of an object
+    pub fn len(&self) -> Option<usize> {
+        match self.record()? {
+            r if matches!(r.kind, TapeKind::Array | TapeKind::Object) => Some(r.len),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    // get Move to the value of an object member
+    pub fn get(&self, key: &str) -> Cursor<'t, 'a> {
+        let found = self
+            .members()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.idx);
+        self.at_index(found)
+    }
+
+    // at Move to an array element
+    pub fn at(&self, index: usize) -> Cursor<'t, 'a> {
+        let found = self.elements().nth(index).and_then(|v| v.idx);
+        self.at_index(found)
+    }
+
+    // elements Iterate over the elements of an array (empty for anything else)
+    pub fn elements(&self) -> impl Iterator<Item = Cursor<'t, 'a>> + 't {
+        let (start, count) = match self.record() {
+            Some(r) if r.kind == TapeKind::Array => (self.idx.unwrap_or(0) + 1, r.len),
+            _ => (0, 0),
+        };
+        let tape = self.tape;
+        let mut idx = start;
+        (0..count).map(move |_| {
+            let cursor = Cursor {
+                tape,
+                idx: Some(idx),
+            };
+            idx = tape.records[idx].next;
+            cursor
+        })
+    }
+
+    // members Iterate over the members of an object (empty for anything else)
+    pub fn members(&self) -> impl Iterator<Item = (Cow<'a, str>, Cursor<'t, 'a>)> + 't {
+        let (start, count) = match self.record() {
+            Some(r) if r.kind == TapeKind::Object => (self.idx.unwrap_or(0) + 1, r.len),
+            _ => (0, 0),
+        };
+        let tape = self.tape;
+        let mut idx = start;
+        (0..count).map(move |_| {
+            let key = Cursor {
+                tape,
+                idx: Some(idx),
+            };
+            let value = Cursor {
+                tape,
+                idx: Some(idx + 1),
+            };
+            idx = tape.records[idx + 1].next;
+            (key.as_str().unwrap_or_default(), value)
+        })
+    }
+
+    // to_value Materialize the subtree under the cursor
+    pub fn to_value(&self) -> Option<JsonValue> {
+        Some(match self.kind()? {
+            TapeKind::Null => JsonValue::Null,
+            TapeKind::True => JsonValue::Bool(true),
+            TapeKind::False => JsonValue::Bool(false),
+            TapeKind::Number => JsonValue::Number(self.as_f64()?),
+            TapeKind::String => JsonValue::String(self.as_str()?.into_owned()),
+            TapeKind::Array => JsonValue::Array(
+                self.elements()
+                    .map(|c| c.to_value())
+                    .collect::<Option<_>>()?,
+            ),
+            TapeKind::Object => JsonValue::Object(
+                self.members()
+                    .map(|(k, v)| Some((k.into_owned(), v.to_value()?)))
+                    .collect::<Option<_>>()?,
+            ),
+        })
+    }
+}
+
+fn decode(raw: &str) -> Option<Cow<'_, str>> {
+    if raw.contains('\\') {
+        unescape(raw).ok().map(Cow::<str>::Owned)
+    } else {
+        Some(Cow::Borrowed(raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::{Tape, TapeKind};
+
+    #[test]
+    fn it_tape_cursor() {
+        let source = r#"{
+            "name": "John \"D\"",
+            "scores": [100, 90, 95],
+            "address": {"city": "Springfield", "zip": null},
+            "id": 9007199254740993
+        }"#;
+        let tape = Tape::parse(source).unwrap();
+        let root = tape.root();
+
+        assert_eq!(root.get("scores").at(1).as_i64(), Some(90));
+        assert_eq!(root.get("name").as_str().unwrap(), "John \"D\"");
+        assert_eq!(
+            root.get("address").get("city").as_str().unwrap(),
+            "Springfield"
+        );
+        assert!(root.get("address").get("zip").is_null());
+        assert_eq!(root.get("id").as_i64(), Some(9007199254740993));
+        assert_eq!(root.get("scores").len(), Some(3));
+        assert_eq!(root.kind(), Some(TapeKind::Object));
+
+        assert!(!root.get("missing").at(3).exists());
+        assert_eq!(root.get("scores").at(7).as_f64(), None);
+        assert_eq!(root.to_value().unwrap(), parse(source).unwrap());
+    }
+
+    #[test]
+    fn it_tape_skips_subtrees() {
+        let tape = Tape::parse(r#"[[1, [2, {"a": [3]}]], {}, "last"]"#).unwrap();
+
+        assert_eq!(tape.root().at(2).as_str().unwrap(), "last");
+        assert_eq!(tape.root().at(1).len(), Some(0));
+        assert_eq!(tape.len(), 11);
+    }
+
+    #[test]
+    fn it_tape_rejects_invalid_input() {
+        assert!(Tape::parse("[1, 2").is_err());
+        assert!(Tape::parse("[1 2]").is_err());
+        assert!(Tape::parse(r#"{"a" 1}"#).is_err());
+        assert!(Tape::parse(r#"{"a": 1,}"#).is_err());
+        assert!(Tape::parse("1 2").is_err());
+        assert!(Tape::parse("@").is_err());
+    }
+}
@@ -1,11 +1,18 @@
-use std::ops::Range;
+use alloc::{format, rc::Rc, string::ToString, vec::Vec};
+use core::ops::Range;
 
 use logos::{Lexer, Logos};
 
+use crate::{
+    encoding::UTF8_BOM,
+    error::{ErrorCode, ErrorKind, JError},
+    ParseOptions,
+};
+
+#[derive(Clone)]
 pub struct JsonToken<'a> {
     pub source: &'a str,
     pub kind: JsonTokenKind,
-    pub at: &'a str,
     pub span: Range<usize>,
 }
 
@@ -15,8 +22,8 @@ impl<'a> JsonToken<'a> {
     }
 }
 
-impl std::fmt::Debug for JsonToken<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for JsonToken<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{:?}[{:?}] @ {}..{}",
@@ -28,7 +35,8 @@ impl std::fmt::Debug for JsonToken<'_> {
     }
 }
 
-#[derive(Logos, Debug, PartialEq)]
+#[derive(Logos, Debug, Clone, PartialEq)]
+#[logos(extras = LexerState)]
 pub enum JsonTokenKind {
     #[token("{")]
     OpenBrace,
@@ -52,18 +60,30 @@ pub enum JsonTokenKind {
     #[token("null")]
     Null,
 
-    #[regex(r"-?\d+(\.\d+)?([eE][+-]?\d+)?", |lex| lex.slice().parse::<f64>().unwrap_or_default())]
+    #[regex(r"-?\d+(\.\d+)?([eE][+-]?\d+)?", number)]
+    #[regex(r"-?0[xX][0-9a-fA-F]+", hex)]
+    #[token("NaN", |lex| non_finite(lex, f64::NAN))]
+    #[token("Infinity", |lex| non_finite(lex, f64::INFINITY))]
+    #[token("-Infinity", |lex| non_finite(lex, f64::NEG_INFINITY))]
     Number(f64),
 
-    #[regex(r#""([^"\\]|\\.)*""#, |lex| lex.slice().to_string())]
-    String(String),
+    #[cfg_attr(not(feature = "simd"), regex(r#""([^"\\]|\\.)*""#, lex_string))]
+    #[cfg_attr(feature = "simd", token("\"", crate::scan::lex_string))]
+    String,
 
-    #[regex(r"\s+", logos::skip)]
+    #[cfg_attr(not(feature = "simd"), regex(r"\s+", logos::skip))]
+    #[cfg_attr(feature = "simd", regex(r"\s", crate::scan::skip_whitespace))]
     Whitespace,
+
+    // A literal matched by one of the `ParseOptions::custom_literals`, named by its tag
+    Custom(&'static str),
+
+    // Invalid input, only produced by `tokenize_recovering`
+    Error,
 }
 
-impl std::fmt::Display for JsonTokenKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JsonTokenKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             JsonTokenKind::OpenBrace => write!(f, "{{"),
             JsonTokenKind::CloseBrace => write!(f, "}}"),
@@ -75,12 +95,227 @@ impl std::fmt::Display for JsonTokenKind {
             JsonTokenKind::False => write!(f, "false"),
             JsonTokenKind::Null => write!(f, "null"),
             JsonTokenKind::Number(n) => write!(f, "{}", n),
-            JsonTokenKind::String(s) => write!(f, "{}", s),
+            JsonTokenKind::String => write!(f, "<string>"),
+            JsonTokenKind::Custom(tag) => write!(f, "<{tag}>"),
             JsonTokenKind::Whitespace => write!(f, " "),
+            JsonTokenKind::Error => write!(f, "<invalid>"),
         }
     }
 }
 
+/// An extra literal the lexer recognizes, for JSON supersets with values such as dates,
+/// UUIDs or `@directives`.
+///
+/// Register literals with [`ParseOptions::custom_literals`]; a match becomes a
+/// [`JsonTokenKind::Custom`] token and a `Custom` value carrying `tag` and the text as
+/// written. Literals are tried where the lexer meets text it rejects, and where a number
+/// starts, so a literal such as `2024-01-31` wins over the number `2024`. The longest
+/// match wins, ties going to the literal registered first.
+///
+/// Serializing a `Custom` value writes the literal back as written, so the output is
+/// in the same JSON superset rather than JSON.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomLiteral {
+    /// Name of the literal, reported with every match.
+    pub tag: &'static str,
+    /// Length in bytes of the literal at the start of the input, if one starts there.
+    pub matcher: fn(&str) -> Option<usize>,
+}
+
+impl CustomLiteral {
+    pub const fn new(tag: &'static str, matcher: fn(&str) -> Option<usize>) -> Self {
+        CustomLiteral { tag, matcher }
+    }
+}
+
+impl PartialEq for CustomLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.tag == other.tag && core::ptr::fn_addr_eq(self.matcher, other.matcher)
+    }
+}
+
+impl Eq for CustomLiteral {}
+
+// longest_custom The tag and length of the longest custom literal at the start of `rest`
+fn longest_custom(literals: &[CustomLiteral], rest: &str) -> Option<(&'static str, usize)> {
+    literals
+        .iter()
+        .filter_map(|literal| {
+            let len =
+                (literal.matcher)(rest).filter(|&len| len > 0 && rest.is_char_boundary(len))?;
+            Some((literal.tag, len))
+        })
+        .fold(None, |best, (tag, len)| match best {
+            Some((_, best_len)) if best_len >= len => best,
+            _ => Some((tag, len)),
+        })
+}
+
+/// Lexer configuration, plus where the last rejected string went wrong.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LexerState {
+    pub(crate) allow_control_chars: bool,
+    pub(crate) allow_non_finite: bool,
+    pub(crate) allow_hex: bool,
+    pub(crate) custom: &'static [CustomLiteral],
+    pub(crate) control_at: Option<usize>,
+}
+
+// number Value of a decimal number, unless a longer custom literal starts with it
+fn number(lex: &mut Lexer<JsonTokenKind>) -> Option<f64> {
+    if custom_overrides(lex) {
+        return None;
+    }
+    Some(lex.slice().parse::<f64>().unwrap_or_default())
+}
+
+// custom_overrides Whether a custom literal longer than the current token starts with it
+fn custom_overrides(lex: &Lexer<JsonTokenKind>) -> bool {
+    let span = lex.span();
+    !lex.extras.custom.is_empty()
+        && longest_custom(lex.extras.custom, &lex.source()[span.start..])
+            .is_some_and(|(_, len)| len > span.len())
+}
+
+// next_token Advance the lexer, turning rejected text that starts a custom literal into a token
+pub(crate) fn next_token(lexer: &mut Lexer<JsonTokenKind>) -> Option<Result<JsonTokenKind, ()>> {
+    let token = lexer.next();
+    if !matches!(token, Some(Err(_))) || lexer.extras.custom.is_empty() {
+        return token;
+    }
+    let span = lexer.span();
+    match longest_custom(lexer.extras.custom, &lexer.source()[span.start..]) {
+        Some((tag, len)) if len >= span.len() => {
+            lexer.bump(len - span.len());
+            Some(Ok(JsonTokenKind::Custom(tag)))
+        }
+        _ => token,
+    }
+}
+
+// hex Value of a hexadecimal integer literal; the token text keeps the literal as written.
+// Literals above 2^53 are refused because an f64 cannot hold them exactly
+fn hex(lex: &mut Lexer<JsonTokenKind>) -> Option<f64> {
+    if !lex.extras.allow_hex || custom_overrides(lex) {
+        return None;
+    }
+    let text = lex.slice();
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, &rest[2..]),
+        None => (1.0, &text[2..]),
+    };
+    u64::from_str_radix(digits, 16)
+        .ok()
+        .filter(|&n| n <= 1 << 53)
+        .map(|n| sign * n as f64)
+}
+
+fn non_finite(lex: &mut Lexer<JsonTokenKind>, n: f64) -> Option<f64> {
+    lex.extras.allow_non_finite.then_some(n)
+}
+
+#[cfg(not(feature = "simd"))]
+fn lex_string(lex: &mut Lexer<JsonTokenKind>) -> Option<()> {
+    check_control_chars(lex)
+}
+
+// check_control_chars Reject a just-lexed string holding a raw control character, unless allowed
+pub(crate) fn check_control_chars(lex: &mut Lexer<JsonTokenKind>) -> Option<()> {
+    if lex.extras.allow_control_chars {
+        return Some(());
+    }
+    match lex.slice().bytes().position(|b| b < 0x20) {
+        Some(idx) => {
+            lex.extras.control_at = Some(lex.span().start + idx);
+            None
+        }
+        None => Some(()),
+    }
+}
+
+// lex_error Describe the error the lexer just returned
+pub(crate) fn lex_error(lex: &Lexer<JsonTokenKind>) -> JError {
+    let span = lex.span();
+    match lex.extras.control_at.filter(|at| span.contains(at)) {
+        Some(at) => JError::new(
+            ErrorKind::Lex,
+            format!(
+                "Unescaped control character {:?} in string at {}..{}",
+                char::from(lex.source().as_bytes()[at]),
+                at,
+                at + 1
+            ),
+        )
+        .with_code(ErrorCode::ControlCharacter)
+        .with_span(at..at + 1),
+        None if is_hex(lex.slice()) => JError::new(
+            ErrorKind::Lex,
+            format!(
+                "Hexadecimal number {} at {}..{} is {}",
+                lex.slice(),
+                span.start,
+                span.end,
+                match lex.extras.allow_hex {
+                    true => "out of range",
+                    false => "not allowed",
+                }
+            ),
+        )
+        .with_code(ErrorCode::InvalidNumber)
+        .with_span(span),
+        None if matches!(lex.slice(), "NaN" | "Infinity" | "-Infinity") => JError::new(
+            ErrorKind::Lex,
+            format!(
+                "Non-finite number {} at {}..{} is not allowed",
+                lex.slice(),
+                span.start,
+                span.end
+            ),
+        )
+        .with_code(ErrorCode::InvalidNumber)
+        .with_span(span),
+        None => JError::new(
+            ErrorKind::Lex,
+            format!(
+                "Invalid token {:?} at {}..{}",
+                lex.slice(),
+                span.start,
+                span.end
+            ),
+        )
+        // A string the lexer gave up on never found its closing quote
+        .with_code(match lex.slice().starts_with('"') {
+            true => ErrorCode::UnterminatedString,
+            false => ErrorCode::InvalidToken,
+        })
+        .with_span(span),
+    }
+}
+
+pub(crate) fn is_hex(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    digits.len() > 2 && (digits.starts_with("0x") || digits.starts_with("0X"))
+}
+
+// lexer_with Start lexing with the string policy of `options`
+pub(crate) fn lexer_with<'a>(source: &'a str, options: &ParseOptions) -> Lexer<'a, JsonTokenKind> {
+    let mut lexer = lexer(source);
+    lexer.extras.allow_control_chars = options.allow_control_chars;
+    lexer.extras.allow_non_finite = options.allow_non_finite;
+    lexer.extras.allow_hex = options.allow_hex;
+    lexer.extras.custom = options.custom_literals;
+    lexer
+}
+
+// lexer Start lexing `source`, skipping a leading byte order mark so spans stay relative to it
+pub(crate) fn lexer(source: &str) -> Lexer<'_, JsonTokenKind> {
+    let mut lexer = JsonTokenKind::lexer(source);
+    if source.starts_with(UTF8_BOM) {
+        lexer.bump(UTF8_BOM.len());
+    }
+    lexer
+}
+
 pub struct JsonLexer<'a> {
     source: &'a str,
     lexer: Lexer<'a, JsonTokenKind>,
@@ -90,7 +325,7 @@ impl<'a> JsonLexer<'a> {
     pub fn new(source: &'a str) -> Self {
         JsonLexer {
             source,
-            lexer: JsonTokenKind::lexer(source),
+            lexer: lexer(source),
         }
     }
 }
@@ -103,7 +338,6 @@ impl<'a> Iterator for JsonLexer<'a> {
             Some(Ok(token)) => Some(JsonToken {
                 source: self.source,
                 kind: token,
-                at: self.lexer.slice(),
                 span: self.lexer.span(),
             }),
             _ => None,
@@ -112,6 +346,429 @@ impl<'a> Iterator for JsonLexer<'a> {
 }
 
 // tokenize Tokenize the input string
-pub fn tokenize(source: &str) -> Vec<JsonToken> {
+pub fn tokenize(source: &str) -> Vec<JsonToken<'_>> {
     JsonLexer::new(source).collect::<Vec<_>>()
 }
+
+// try_tokenize Tokenize the input string, failing at the first invalid character
+pub fn try_tokenize(source: &str) -> Result<Vec<JsonToken<'_>>, JError> {
+    let mut lexer = lexer(source);
+    let mut tokens = Vec::new();
+    while let Some(kind) = lexer.next() {
+        match kind {
+            Ok(kind) => tokens.push(JsonToken {
+                source,
+                kind,
+                span: lexer.span(),
+            }),
+            Err(_) => return Err(lex_error(&lexer)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Tokenize the input string, recording invalid input instead of stopping at it.
+///
+/// Each invalid stretch becomes one [`JsonTokenKind::Error`] token running up to the next
+/// structural character (`{}[]:,`) or whitespace, where lexing resumes. Lint-style
+/// callers can report every error token; the parsers never match one.
+pub fn tokenize_recovering(source: &str) -> Vec<JsonToken<'_>> {
+    let mut lexer = lexer(source);
+    let mut tokens: Vec<JsonToken<'_>> = Vec::new();
+    while let Some(kind) = lexer.next() {
+        let span = match kind {
+            Ok(kind) => {
+                tokens.push(JsonToken {
+                    source,
+                    kind,
+                    span: lexer.span(),
+                });
+                continue;
+            }
+            Err(_) => lexer.span(),
+        };
+        let rest = &source[span.end..];
+        let skip = rest
+            .find(|c: char| "{}[]:,".contains(c) || c.is_whitespace())
+            .unwrap_or(rest.len());
+        lexer.bump(skip);
+        let end = span.end + skip;
+
+        match tokens.last_mut() {
+            Some(last) if last.kind == JsonTokenKind::Error && last.span.end == span.start => {
+                last.span.end = end;
+            }
+            _ => tokens.push(JsonToken {
+                source,
+                kind: JsonTokenKind::Error,
+                span: span.start..end,
+            }),
+        }
+    }
+    tokens
+}
+
+/// The token covering byte `offset` of `source`, if any.
+///
+/// A token covers the offsets from its first byte up to, not including, its end, so an
+/// offset in whitespace or past the end finds nothing. Invalid input is found as a
+/// [`JsonTokenKind::Error`] token, as [`tokenize_recovering`] produces it.
+pub fn token_at_offset(source: &str, offset: usize) -> Option<JsonToken<'_>> {
+    let mut tokens = tokenize_recovering(source);
+    let idx = tokens.partition_point(|token| token.span.end <= offset);
+    match tokens.get(idx) {
+        Some(token) if token.span.start <= offset => Some(tokens.swap_remove(idx)),
+        _ => None,
+    }
+}
+
+/// Payload-free token kind, as stored in a [`TokenBuffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TokenKind {
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Colon,
+    Comma,
+    True,
+    False,
+    Null,
+    Number,
+    String,
+    Custom,
+    /// Input the lexer rejected, as kept by [`tokenize_recovering`]; never stored in a
+    /// [`TokenBuffer`].
+    Error,
+}
+
+impl From<&JsonTokenKind> for TokenKind {
+    fn from(kind: &JsonTokenKind) -> Self {
+        match kind {
+            JsonTokenKind::OpenBrace => TokenKind::OpenBrace,
+            JsonTokenKind::CloseBrace => TokenKind::CloseBrace,
+            JsonTokenKind::OpenBracket => TokenKind::OpenBracket,
+            JsonTokenKind::CloseBracket => TokenKind::CloseBracket,
+            JsonTokenKind::Colon => TokenKind::Colon,
+            JsonTokenKind::Comma => TokenKind::Comma,
+            JsonTokenKind::True => TokenKind::True,
+            JsonTokenKind::False => TokenKind::False,
+            JsonTokenKind::Null => TokenKind::Null,
+            JsonTokenKind::Number(_) => TokenKind::Number,
+            JsonTokenKind::String => TokenKind::String,
+            JsonTokenKind::Custom(_) => TokenKind::Custom,
+            // The lexer skips whitespace, so only a hand-made token can carry it
+            JsonTokenKind::Whitespace | JsonTokenKind::Error => TokenKind::Error,
+        }
+    }
+}
+
+impl core::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            TokenKind::OpenBrace => "{",
+            TokenKind::CloseBrace => "}",
+            TokenKind::OpenBracket => "[",
+            TokenKind::CloseBracket => "]",
+            TokenKind::Colon => ":",
+            TokenKind::Comma => ",",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Null => "null",
+            TokenKind::Number => "Number",
+            TokenKind::String => "String",
+            TokenKind::Custom => "Custom",
+            TokenKind::Error => "Error",
+        })
+    }
+}
+
+/// Tokens of a source stored as parallel arrays of kind, start offset and length.
+///
+/// A token takes 9 bytes instead of a full [`JsonToken`]; its text, number value and
+/// string contents are read back from the source on demand. Offsets are `u32`, so
+/// sources are limited to 4 GiB.
+#[derive(Debug, Clone)]
+pub struct TokenBuffer<'a> {
+    source: &'a str,
+    kinds: Vec<TokenKind>,
+    starts: Vec<u32>,
+    lens: Vec<u32>,
+}
+
+impl<'a> TokenBuffer<'a> {
+    // new Tokenize the input string, failing at the first invalid character
+    pub fn new(source: &'a str) -> Result<Self, JError> {
+        check_buffer_len(source)?;
+        let mut lexer = lexer(source);
+        let mut buffer = TokenBuffer {
+            source,
+            kinds: Vec::new(),
+            starts: Vec::new(),
+            lens: Vec::new(),
+        };
+        while let Some(kind) = lexer.next() {
+            let span = lexer.span();
+            match kind {
+                Ok(kind) => {
+                    buffer.kinds.push(TokenKind::from(&kind));
+                    buffer.starts.push(span.start as u32);
+                    buffer.lens.push(span.len() as u32);
+                }
+                Err(_) => return Err(lex_error(&lexer)),
+            }
+        }
+        Ok(buffer)
+    }
+
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    pub fn len(&self) -> usize {
+        self.kinds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+
+    pub fn kind(&self, idx: usize) -> Option<TokenKind> {
+        self.kinds.get(idx).copied()
+    }
+
+    pub fn span(&self, idx: usize) -> Option<Range<usize>> {
+        let start = *self.starts.get(idx)? as usize;
+        Some(start..start + self.lens[idx] as usize)
+    }
+
+    // text Source text of a token; string tokens include their quotes
+    pub fn text(&self, idx: usize) -> Option<&'a str> {
+        self.span(idx).map(|span| &self.source[span])
+    }
+
+    pub fn kinds(&self) -> &[TokenKind] {
+        &self.kinds
+    }
+
+    /// Update the tokens for an edit that replaced `range` of the buffered source with
+    /// `text`, giving `source`, the source after the edit.
+    ///
+    /// Only the tokens around the edit are lexed again: lexing restarts at the first
+    /// token the edit touches and stops as soon as it reaches a token the edit left
+    /// alone, whose successors are then kept with their offsets shifted. An edit that
+    /// opens or closes a string can still change every token up to the end. The buffer's
+    /// vectors are reused, so an edit costs one pass over the offsets after it rather than
+    /// a pass of the lexer over the whole source.
+    pub fn relex<'b>(
+        self,
+        source: &'b str,
+        range: Range<usize>,
+        text: &str,
+    ) -> Result<TokenBuffer<'b>, JError> {
+        let consistent = range.start <= range.end
+            && range.end <= self.source.len()
+            && source.len() + range.len() == self.source.len() + text.len()
+            && source.get(range.start..range.start + text.len()) == Some(text);
+        if !consistent {
+            return Err(JError::new(
+                ErrorKind::Other,
+                format!(
+                    "Replacing {}..{} with {} bytes does not give the new source",
+                    range.start,
+                    range.end,
+                    text.len()
+                ),
+            ));
+        }
+        check_buffer_len(source)?;
+
+        // Tokens ending before the edit are untouched; lexing restarts at the next one
+        let mut first = self
+            .starts
+            .partition_point(|start| (*start as usize) < range.start);
+        if first > 0
+            && self
+                .span(first - 1)
+                .is_some_and(|span| span.end >= range.start)
+        {
+            first -= 1;
+        }
+        let restart = match self.starts.get(first) {
+            Some(start) => (*start as usize).min(range.start),
+            None => range.start,
+        };
+        let mut lexer = match restart < UTF8_BOM.len() {
+            true => lexer(source),
+            false => {
+                let mut lexer = JsonTokenKind::lexer(source);
+                lexer.bump(restart);
+                lexer
+            }
+        };
+
+        let delta = text.len() as i64 - range.len() as i64;
+        let edited_end = range.start + text.len();
+        let (mut kinds, mut starts, mut lens) = (Vec::new(), Vec::new(), Vec::new());
+        let mut resume = self.len();
+        while let Some(kind) = lexer.next() {
+            let span = lexer.span();
+            // Past the edit, a token where an old one started means the rest is unchanged
+            if span.start >= edited_end {
+                let old_start = (span.start as i64 - delta) as usize;
+                let idx = self
+                    .starts
+                    .partition_point(|start| (*start as usize) < old_start);
+                if self.starts.get(idx) == Some(&(old_start as u32)) {
+                    resume = idx;
+                    break;
+                }
+            }
+            match kind {
+                Ok(kind) => {
+                    kinds.push(TokenKind::from(&kind));
+                    starts.push(span.start as u32);
+                    lens.push(span.len() as u32);
+                }
+                // Report the error as lexing the whole source would
+                Err(_) => return TokenBuffer::new(source),
+            }
+        }
+
+        let shifted = first + starts.len();
+        let mut buffer = TokenBuffer {
+            source,
+            kinds: self.kinds,
+            starts: self.starts,
+            lens: self.lens,
+        };
+        buffer.kinds.splice(first..resume, kinds);
+        buffer.starts.splice(first..resume, starts);
+        buffer.lens.splice(first..resume, lens);
+        for start in &mut buffer.starts[shifted..] {
+            *start = (*start as i64 + delta) as u32;
+        }
+        Ok(buffer)
+    }
+
+    // token_at Index of the token covering byte `offset`, see [`token_at_offset`]
+    pub fn token_at(&self, offset: usize) -> Option<usize> {
+        let idx = self
+            .starts
+            .partition_point(|start| *start as usize <= offset);
+        let idx = idx.checked_sub(1)?;
+        let span = self.span(idx)?;
+        (offset < span.end).then_some(idx)
+    }
+}
+
+fn check_buffer_len(source: &str) -> Result<(), JError> {
+    match u32::try_from(source.len()) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(JError::new(
+            ErrorKind::Unsupported,
+            "Source exceeds the 4 GiB token buffer limit".to_string(),
+        )),
+    }
+}
+
+/// Token source the parser combinators read from.
+///
+/// Implemented for token slices and for [`TokenStream`], which lexes on demand.
+pub trait TokenInput<'a>: Clone + core::fmt::Debug + nom::InputLength {
+    fn peek(&self) -> Option<&JsonToken<'a>>;
+
+    // advance The input after the current token
+    fn advance(&self) -> Self;
+
+    // lex_error The error that stopped lexing at the current position, if any
+    fn lex_error(&self) -> Option<JError> {
+        None
+    }
+}
+
+impl<'a> TokenInput<'a> for &'a [JsonToken<'a>] {
+    fn peek(&self) -> Option<&JsonToken<'a>> {
+        self.first()
+    }
+
+    fn advance(&self) -> Self {
+        self.get(1..).unwrap_or_default()
+    }
+}
+
+/// Cursor over the tokens of a source, lexing one token ahead at a time.
+///
+/// Cloning only copies the lexer state and shares the current token, so
+/// backtracking combinators stay cheap and no token vector is ever built.
+#[derive(Clone)]
+pub struct TokenStream<'a> {
+    lexer: Lexer<'a, JsonTokenKind>,
+    head: Option<Rc<JsonToken<'a>>>,
+    invalid: Option<Range<usize>>,
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(source: &'a str) -> Self {
+        TokenStream::with_options(source, &ParseOptions::default())
+    }
+
+    pub fn with_options(source: &'a str, options: &ParseOptions) -> Self {
+        TokenStream {
+            lexer: lexer_with(source, options),
+            head: None,
+            invalid: None,
+        }
+        .advance()
+    }
+}
+
+impl<'a> TokenInput<'a> for TokenStream<'a> {
+    fn peek(&self) -> Option<&JsonToken<'a>> {
+        self.head.as_deref()
+    }
+
+    fn advance(&self) -> Self {
+        let mut lexer = self.lexer.clone();
+        let (head, invalid) = match next_token(&mut lexer) {
+            Some(Ok(kind)) => {
+                let token = JsonToken {
+                    source: lexer.source(),
+                    kind,
+                    span: lexer.span(),
+                };
+                (Some(Rc::new(token)), None)
+            }
+            Some(Err(_)) => (None, Some(lexer.span())),
+            None => (None, None),
+        };
+        TokenStream {
+            lexer,
+            head,
+            invalid,
+        }
+    }
+
+    fn lex_error(&self) -> Option<JError> {
+        self.invalid.as_ref()?;
+        Some(lex_error(&self.lexer))
+    }
+}
+
+impl nom::InputLength for TokenStream<'_> {
+    fn input_len(&self) -> usize {
+        self.head
+            .as_ref()
+            .map_or(0, |token| token.source.len() - token.span.start)
+    }
+}
+
+impl core::fmt::Debug for TokenStream<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.head {
+            Some(token) => write!(f, "{token:?}"),
+            None => write!(f, "<end>"),
+        }
+    }
+}
@@ -0,0 +1,564 @@
+//! Typed mapping between Rust values and [`JsonValue`] without serde.
+//!
+//! Implement [`FromJson`] and [`ToJson`] by hand with the help of [`Fields`], which reads
+//! object members and gathers every problem instead of stopping at the first:
+//!
+//! ```
+//! use jnom_rs::{typed::{self, Fields, FromJson, ToJson}, JError, JsonValue};
+//!
+//! struct User {
+//!     name: String,
+//!     age: Option<u32>,
+//!     tags: Vec<String>,
+//! }
+//!
+//! impl FromJson for User {
+//!     fn from_json(value: &JsonValue) -> Result<Self, JError> {
+//!         let mut fields = Fields::new(value)?;
+//!         let name = fields.required("name");
+//!         let age = fields.optional("age");
+//!         let tags = fields.optional("tags").unwrap_or_default();
+//!         fields.finish()?;
+//!         Ok(User { name: name.unwrap(), age, tags })
+//!     }
+//! }
+//!
+//! impl ToJson for User {
+//!     fn to_json(&self) -> JsonValue {
+//!         JsonValue::from_iter([
+//!             ("name", self.name.to_json()),
+//!             ("age", self.age.to_json()),
+//!             ("tags", self.tags.to_json()),
+//!         ])
+//!     }
+//! }
+//!
+//! let user: User = typed::from_str(r#"{"name": "Ann", "tags": ["admin"]}"#).unwrap();
+//! let expected = jnom_rs::parse(r#"{"name": "Ann", "age": null, "tags": ["admin"]}"#).unwrap();
+//! assert_eq!(user.to_json(), expected);
+//!
+//! let err = typed::from_str::<User>(r#"{"age": -1}"#).err().unwrap();
+//! assert_eq!(err.message(), "missing field \"name\"; expected u32, found number -1 at key \"age\"");
+//! ```
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    error::{ErrorKind, JError},
+    parse, JsonValue, Map,
+};
+
+/// `#[derive(FromJson, ToJson)]`; see the `jnom-derive` crate for the supported attributes.
+#[cfg(feature = "derive")]
+pub use jnom_derive::{FromJson, ToJson};
+
+/// Conversion from a borrowed [`JsonValue`].
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, JError>;
+}
+
+/// Conversion into a [`JsonValue`].
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+// from_str Parse the source and convert the document to `T`
+pub fn from_str<T: FromJson>(source: &str) -> Result<T, JError> {
+    T::from_json(&parse(source)?)
+}
+
+/// Reader for the members of an object, collecting errors for [`finish`](Fields::finish).
+///
+/// Each accessor returns `None` when the member cannot be produced and records why;
+/// once `finish` has succeeded, every [`required`](Fields::required) call returned `Some`.
+pub struct Fields<'a> {
+    obj: &'a Map<String, JsonValue>,
+    seen: Vec<&'a str>,
+    errors: Vec<String>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(value: &'a JsonValue) -> Result<Self, JError> {
+        let obj = value.as_object().ok_or_else(|| {
+            JError::new(
+                ErrorKind::Type,
+                format!("expected object, found {}", value.type_name()),
+            )
+        })?;
+        Ok(Fields {
+            obj,
+            seen: Vec::new(),
+            errors: Vec::new(),
+        })
+    }
+
+    // required Member that must be present
+    pub fn required<T: FromJson>(&mut self, key: &'a str) -> Option<T> {
+        match self.obj.get(key) {
+            Some(value) => self.convert(key, value),
+            None => {
+                self.seen.push(key);
+                self.errors.push(format!("missing field {key:?}"));
+                None
+            }
+        }
+    }
+
+    // optional Member that may be absent or `null`
+    pub fn optional<T: FromJson>(&mut self, key: &'a str) -> Option<T> {
+        match self.obj.get(key) {
+            Some(value) if !value.is_null() => self.convert(key, value),
+            _ => {
+                self.seen.push(key);
+                None
+            }
+        }
+    }
+
+    // deny_unknown Record an error for every member no accessor has asked for so far
+    pub fn deny_unknown(&mut self) {
+        for key in self.obj.keys() {
+            if !self.seen.contains(&key.as_str()) {
+                self.errors.push(format!("unknown field {key:?}"));
+            }
+        }
+    }
+
+    // error Record a custom error, e.g. from cross-field validation
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into());
+    }
+
+    // finish Fail with all recorded errors joined by "; "
+    pub fn finish(self) -> Result<(), JError> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(JError::new(ErrorKind::Type, self.errors.join("; "))),
+        }
+    }
+
+    fn convert<T: FromJson>(&mut self, key: &'a str, value: &JsonValue) -> Option<T> {
+        self.seen.push(key);
+        T::from_json(value)
+            .map_err(|e| self.errors.push(format!("{} at key {key:?}", e.message())))
+            .ok()
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, JError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+// Scalars reuse the checked `TryFrom` conversions and the `From` impls of `JsonValue`
+macro_rules! scalar_json {
+    ($($ty:ty),*) => {
+        $(
+            impl FromJson for $ty {
+                fn from_json(value: &JsonValue) -> Result<Self, JError> {
+                    <$ty>::try_from(value)
+                }
+            }
+
+            impl ToJson for $ty {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::from(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+scalar_json!(String, bool, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(self)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, ToJson::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JError> {
+        let arr = value.as_array().ok_or_else(|| {
+            JError::new(
+                ErrorKind::Type,
+                format!("expected array, found {}", value.type_name()),
+            )
+        })?;
+        arr.iter()
+            .enumerate()
+            .map(|(idx, item)| {
+                T::from_json(item)
+                    .map_err(|e| JError::new(e.kind(), format!("{} at index {idx}", e.message())))
+            })
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+// members Convert every member of an object, failing at the first bad one
+fn members<T: FromJson>(
+    value: &JsonValue,
+) -> Result<impl Iterator<Item = Result<(String, T), JError>> + '_, JError> {
+    let obj = value.as_object().ok_or_else(|| {
+        JError::new(
+            ErrorKind::Type,
+            format!("expected object, found {}", value.type_name()),
+        )
+    })?;
+    Ok(obj.iter().map(|(key, item)| {
+        let item = T::from_json(item)
+            .map_err(|e| JError::new(e.kind(), format!("{} at key {key:?}", e.message())))?;
+        Ok((key.to_string(), item))
+    }))
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JError> {
+        members(value)?.collect()
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: FromJson, S: core::hash::BuildHasher + Default> FromJson
+    for std::collections::HashMap<String, T, S>
+{
+    fn from_json(value: &JsonValue) -> Result<Self, JError> {
+        members(value)?.collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ToJson, S> ToJson for std::collections::HashMap<String, T, S> {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+    }
+}
+
+// Support code for the derive macros, not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    use alloc::{
+        format,
+        string::{String, ToString},
+    };
+
+    use super::FromJson;
+    use crate::{
+        error::{ErrorKind, JError},
+        JsonValue, Map,
+    };
+
+    pub fn expected(what: &str, found: &JsonValue) -> JError {
+        JError::new(
+            ErrorKind::Type,
+            format!("expected {what}, found {}", found.type_name()),
+        )
+    }
+
+    pub fn unknown_variant(tag: &str, ty: &str) -> JError {
+        JError::new(ErrorKind::Type, format!("unknown variant {tag:?} of {ty}"))
+    }
+
+    pub fn at_key(e: JError, key: &str) -> JError {
+        JError::new(e.kind(), format!("{} at key {key:?}", e.message()))
+    }
+
+    pub fn unit(value: &JsonValue) -> Result<(), JError> {
+        match value {
+            JsonValue::Null => Ok(()),
+            other => Err(expected("null", other)),
+        }
+    }
+
+    // tuple Elements of an array that must have exactly `len` of them
+    pub fn tuple(value: &JsonValue, len: usize) -> Result<&[JsonValue], JError> {
+        match value.as_array() {
+            Some(items) if items.len() == len => Ok(items),
+            Some(items) => Err(JError::new(
+                ErrorKind::Type,
+                format!("expected array of {len} elements, found {}", items.len()),
+            )),
+            None => Err(expected("array", value)),
+        }
+    }
+
+    pub fn element<T: FromJson>(items: &[JsonValue], idx: usize) -> Result<T, JError> {
+        T::from_json(&items[idx])
+            .map_err(|e| JError::new(e.kind(), format!("{} at index {idx}", e.message())))
+    }
+
+    pub fn object() -> Map<String, JsonValue> {
+        Map::default()
+    }
+
+    // extend Merge the members of a flattened field; other values add nothing
+    pub fn extend(obj: &mut Map<String, JsonValue>, value: JsonValue) {
+        if let JsonValue::Object(members) = value {
+            obj.extend(members);
+        }
+    }
+
+    pub fn array<const N: usize>(items: [JsonValue; N]) -> JsonValue {
+        JsonValue::Array(items.into())
+    }
+
+    pub fn string(s: &str) -> JsonValue {
+        JsonValue::String(s.to_string())
+    }
+
+    // tagged Single-member object wrapping an enum variant's payload
+    pub fn tagged(tag: &str, payload: JsonValue) -> JsonValue {
+        let mut obj = object();
+        obj.insert(tag.to_string(), payload);
+        JsonValue::Object(obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        collections::BTreeMap,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use crate::{parse, JError, JsonValue};
+
+    use super::{from_str, Fields, FromJson, ToJson};
+
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+        label: Option<String>,
+    }
+
+    impl FromJson for Point {
+        fn from_json(value: &JsonValue) -> Result<Self, JError> {
+            let mut fields = Fields::new(value)?;
+            let x = fields.required("x");
+            let y = fields.required("y");
+            let label = fields.optional("label");
+            fields.deny_unknown();
+            fields.finish()?;
+            Ok(Point {
+                x: x.unwrap(),
+                y: y.unwrap(),
+                label,
+            })
+        }
+    }
+
+    #[test]
+    fn it_from_json_fields() {
+        let points: Vec<Point> =
+            from_str(r#"[{"x": 1, "y": 2}, {"x": -1, "y": 0, "label": "o"}]"#).unwrap();
+        assert_eq!(
+            points[1],
+            Point {
+                x: -1,
+                y: 0,
+                label: Some("o".to_string())
+            }
+        );
+
+        let err = from_str::<Point>(r#"{"x": 1.5, "label": 3, "z": 0}"#).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "expected i32, found number 1.5 at key \"x\"; missing field \"y\"; \
+             expected string, found number at key \"label\"; unknown field \"z\""
+        );
+        let err = from_str::<Vec<Point>>(r#"[{"x": 1, "y": 2}, []]"#).unwrap_err();
+        assert_eq!(err.message(), "expected object, found array at index 1");
+    }
+
+    #[test]
+    fn it_to_json_std_types() {
+        let mut map = BTreeMap::new();
+        map.insert("b".to_string(), vec![Some(1u8), None]);
+        map.insert("a".to_string(), Vec::new());
+
+        let value = map.to_json();
+        assert_eq!(value, parse(r#"{"a": [], "b": [1, null]}"#).unwrap());
+        assert_eq!(
+            BTreeMap::<String, Vec<Option<u8>>>::from_json(&value).unwrap(),
+            map
+        );
+        assert_eq!("x".to_json(), JsonValue::from("x"));
+        #[cfg(feature = "std")]
+        {
+            let flags = parse(r#"{"k": true}"#).unwrap();
+            assert!(std::collections::HashMap::<String, bool>::from_json(&flags).unwrap()["k"]);
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive {
+        use alloc::{boxed::Box, collections::BTreeMap};
+
+        use crate::{
+            parse,
+            typed::{from_str, FromJson, ToJson},
+        };
+
+        #[derive(Debug, PartialEq, FromJson, ToJson)]
+        #[json(rename_all = "camelCase")]
+        struct Order {
+            order_id: u64,
+            #[json(rename = "who")]
+            customer: String,
+            note: Option<String>,
+            #[json(default)]
+            lines: Vec<Line>,
+            #[json(default = "default_currency")]
+            currency: String,
+            #[json(flatten)]
+            extra: BTreeMap<String, crate::JsonValue>,
+            #[json(skip)]
+            cached_total: Option<f64>,
+        }
+
+        fn default_currency() -> String {
+            "EUR".to_string()
+        }
+
+        #[derive(Debug, PartialEq, FromJson, ToJson)]
+        struct Line(String, u32);
+
+        #[derive(Debug, PartialEq, FromJson, ToJson)]
+        #[json(rename_all = "snake_case", deny_unknown_fields)]
+        enum Shape {
+            Empty,
+            Circle(f64),
+            Rect(f64, f64),
+            Labelled {
+                #[json(rename = "text")]
+                label: String,
+                inner: Box<Shape>,
+            },
+        }
+
+        #[test]
+        fn it_derive_struct() {
+            let order: Order =
+                from_str(r#"{"orderId": 7, "who": "Ann", "lines": [["tea", 2]], "gift": true}"#)
+                    .unwrap();
+            assert_eq!(order.order_id, 7);
+            assert_eq!(order.note, None);
+            assert_eq!(order.lines, vec![Line("tea".to_string(), 2)]);
+            assert_eq!(order.currency, "EUR");
+            assert_eq!(order.cached_total, None);
+            // flatten sees the whole object, including the members read by name
+            assert_eq!(order.extra["gift"], crate::JsonValue::Bool(true));
+
+            let json = order.to_json();
+            assert_eq!(json.get("who"), Some(&"Ann".into()));
+            assert_eq!(json.get("lines"), Some(&parse(r#"[["tea", 2]]"#).unwrap()));
+            assert!(!json.contains_key("cached_total"));
+            assert!(!json.contains_key("cachedTotal"));
+
+            let err = from_str::<Order>(r#"{"lines": [["x"]]}"#).unwrap_err();
+            assert_eq!(
+                err.message(),
+                "missing field \"orderId\"; missing field \"who\"; \
+                 expected array of 2 elements, found 1 at index 0 at key \"lines\""
+            );
+        }
+
+        #[test]
+        fn it_derive_enum() {
+            let shapes = vec![
+                Shape::Empty,
+                Shape::Circle(1.5),
+                Shape::Rect(2.0, 3.0),
+                Shape::Labelled {
+                    label: "a".to_string(),
+                    inner: Box::new(Shape::Empty),
+                },
+            ];
+            let json = shapes.to_json();
+            assert_eq!(
+                json,
+                parse(
+                    r#"["empty", {"circle": 1.5}, {"rect": [2, 3]},
+                        {"labelled": {"text": "a", "inner": "empty"}}]"#
+                )
+                .unwrap()
+            );
+            assert_eq!(Vec::<Shape>::from_json(&json).unwrap(), shapes);
+
+            let err = from_str::<Shape>(r#"{"labelled": {"text": "a", "inner": "hex", "x": 1}}"#)
+                .unwrap_err();
+            assert_eq!(
+                err.message(),
+                "unknown variant \"hex\" of Shape at key \"inner\"; unknown field \"x\" \
+                 at key \"labelled\""
+            );
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use alloc::{format, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    error::{ErrorKind, JError},
+    token::{lex_error, lexer_with, next_token, JsonTokenKind},
+    value::unescape_with,
+    ParseOptions,
+};
+
+// validate Check that the source is one well-formed JSON document without building it
+pub fn validate(source: &str) -> Result<(), Vec<JError>> {
+    validate_with(source, &ParseOptions::default())
+}
+
+/// Check well-formedness under `options`, accepting exactly what [`parse_with`](crate::parse_with)
+/// accepts.
+///
+/// Only the token stream and a stack of open containers are inspected; no values are
+/// built. Every invalid token or escape sequence is reported, but after the first
+/// structural error (a token where it cannot appear) the structure is no longer checked.
+pub fn validate_with(source: &str, options: &ParseOptions) -> Result<(), Vec<JError>> {
+    let mut lexer = lexer_with(source, options);
+    let mut checker = Checker {
+        open: Vec::new(),
+        expect: Expect::Value,
+    };
+    let mut errors = Vec::new();
+    let mut broken = false;
+    while let Some(token) = next_token(&mut lexer) {
+        let span = lexer.span();
+        let kind = match token {
+            Ok(kind) => kind,
+            Err(_) => {
+                errors.push(lex_error(&lexer));
+                broken = true;
+                continue;
+            }
+        };
+        if kind == JsonTokenKind::String {
+            let raw = lexer.slice();
+            if let Err(e) = unescape_with(&raw[1..raw.len() - 1], |_| {}) {
+                let message = format!("{} at {}..{}", e.message(), span.start, span.end);
+                errors.push(
+                    JError::new(e.kind(), message)
+                        .with_code(e.code())
+                        .with_span(span.clone()),
+                );
+            }
+        }
+        if !broken {
+            if let Err(e) = checker.step(&kind, lexer.slice(), span) {
+                errors.push(e);
+                broken = true;
+            }
+        }
+    }
+    if !broken && checker.expect != Expect::End {
+        errors.push(JError::new(ErrorKind::Eof, "Unexpected end of input"));
+    }
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors
+            .into_iter()
+            .map(|e| e.locate_document(source))
+            .collect()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    // Right after `[`
+    ValueOrClose,
+    // Right after `{`
+    KeyOrClose,
+    // After a comma in an object
+    Key,
+    Colon,
+    CommaOrClose,
+    End,
+}
+
+struct Checker {
+    // Open containers, `true` for objects
+    open: Vec<bool>,
+    expect: Expect,
+}
+
+impl Checker {
+    fn step(&mut self, kind: &JsonTokenKind, text: &str, span: Range<usize>) -> Result<(), JError> {
+        use JsonTokenKind as T;
+
+        let in_object = self.open.last().copied();
+        self.expect = match (self.expect, kind) {
+            (Expect::Value | Expect::ValueOrClose, T::OpenBrace | T::OpenBracket) => {
+                let object = *kind == T::OpenBrace;
+                self.open.push(object);
+                match object {
+                    true => Expect::KeyOrClose,
+                    false => Expect::ValueOrClose,
+                }
+            }
+            (
+                Expect::Value | Expect::ValueOrClose,
+                T::String | T::Number(_) | T::True | T::False | T::Null | T::Custom(_),
+            ) => self.after_value(),
+            (Expect::Key | Expect::KeyOrClose, T::String) => Expect::Colon,
+            (Expect::Colon, T::Colon) => Expect::Value,
+            (Expect::CommaOrClose, T::Comma) => match in_object {
+                Some(true) => Expect::Key,
+                _ => Expect::Value,
+            },
+            (Expect::ValueOrClose | Expect::CommaOrClose, T::CloseBracket)
+                if in_object == Some(false) =>
+            {
+                self.open.pop();
+                self.after_value()
+            }
+            (Expect::KeyOrClose | Expect::CommaOrClose, T::CloseBrace)
+                if in_object == Some(true) =>
+            {
+                self.open.pop();
+                self.after_value()
+            }
+            (expect, _) => {
+                let wanted = match expect {
+                    Expect::Value => "a value",
+                    Expect::ValueOrClose => "a value or ']'",
+                    Expect::KeyOrClose => "a string key or '}'",
+                    Expect::Key => "a string key",
+                    Expect::Colon => "':'",
+                    Expect::CommaOrClose if in_object == Some(true) => "',' or '}'",
+                    Expect::CommaOrClose => "',' or ']'",
+                    Expect::End => "the end of input",
+                };
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!(
+                        "Expected {wanted}, found {text:?} at {}..{}",
+                        span.start, span.end
+                    ),
+                )
+                .with_span(span));
+            }
+        };
+        Ok(())
+    }
+
+    fn after_value(&self) -> Expect {
+        match self.open.is_empty() {
+            true => Expect::End,
+            false => Expect::CommaOrClose,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use crate::{lines::LineCol, parse, ErrorCode, ParseOptions};
+
+    use super::{validate, validate_with};
+
+    #[test]
+    fn it_validate() {
+        let messages = |source| {
+            validate(source)
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>()
+        };
+
+        for source in [
+            r#"{"a": [1, 2.5, {"b": null}], "c": "\u00e9\n", "d": {}}"#,
+            "[]",
+            " 42 ",
+            "\u{feff}\"x\"",
+        ] {
+            assert_eq!(validate(source).map_err(|e| e[0].to_string()), Ok(()));
+            assert!(parse(source).is_ok());
+        }
+        for source in [
+            "",
+            "[1,]",
+            "{\"a\" 1}",
+            "[1] 2",
+            "{\"a\": [}",
+            "[\"\\q\"]",
+            "{1: 2}",
+        ] {
+            assert!(validate(source).is_err(), "{source:?}");
+            assert!(parse(source).is_err(), "{source:?}");
+        }
+
+        assert_eq!(messages("[1, 2"), ["Unexpected end of input"]);
+        assert_eq!(
+            messages(r#"{"a": 1, "b" 2}"#),
+            ["Expected ':', found \"2\" at 13..14"]
+        );
+        assert_eq!(
+            messages("[1, @, \"\\x\", #]"),
+            [
+                "Invalid token \"@\" at 4..5",
+                "Invalid escape \\x at 7..11",
+                "Invalid token \"#\" at 13..14"
+            ]
+        );
+        assert_eq!(
+            messages("[NaN]"),
+            ["Non-finite number NaN at 1..4 is not allowed"]
+        );
+        assert!(validate_with("[NaN]", &ParseOptions::new().allow_non_finite()).is_ok());
+
+        // every error carries its code, span and position, the missing comma included
+        let errors = validate("[1 2, \"\\q\"").unwrap_err();
+        assert_eq!(
+            errors[0].to_json(),
+            parse(r#"{"code":"E0001","message":"Expected ',' or ']', found \"2\" at 3..4","context":[],"span":{"start":3,"end":4},"line":0,"col":3,"path":"/0"}"#).unwrap()
+        );
+        assert_eq!(errors[1].code(), ErrorCode::InvalidEscape);
+        assert_eq!(errors[1].span(), Some(6..10));
+        let eof = &validate("{\"a\":\n[").unwrap_err()[0];
+        assert_eq!(eof.code(), ErrorCode::UnexpectedEof);
+        assert_eq!(eof.line_col(), Some(LineCol { line: 1, col: 1 }));
+        assert_eq!(eof.path(), Some("/a/0"));
+    }
+}
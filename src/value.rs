@@ -0,0 +1,534 @@
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    error::{ErrorKind, JError},
+    JsonExpr, NumberMode,
+};
+
+/// Map backing JSON objects.
+///
+/// By default objects keep insertion order (`IndexMap`, hashed with `RandomState`
+/// when `std` is available and with FNV otherwise). The `sorted_map` feature switches
+/// to a `BTreeMap` that iterates in key order, and `hash_map` to an unordered FNV
+/// `HashMap` for the fastest inserts and lookups; `sorted_map` wins if both are on.
+#[cfg(feature = "sorted_map")]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+#[cfg(all(feature = "hash_map", not(feature = "sorted_map")))]
+pub type Map<K, V> =
+    std::collections::HashMap<K, V, core::hash::BuildHasherDefault<fnv::FnvHasher>>;
+#[cfg(all(
+    feature = "std",
+    not(any(feature = "sorted_map", feature = "hash_map"))
+))]
+pub type Map<K, V> = indexmap::IndexMap<K, V>;
+#[cfg(all(
+    not(feature = "std"),
+    not(any(feature = "sorted_map", feature = "hash_map"))
+))]
+pub type Map<K, V> = indexmap::IndexMap<K, V, core::hash::BuildHasherDefault<fnv::FnvHasher>>;
+
+// map_with_capacity Empty object map with room for `len` members where the backend supports it
+pub(crate) fn map_with_capacity<K, V>(len: usize) -> Map<K, V> {
+    #[cfg(feature = "sorted_map")]
+    let map = {
+        let _ = len;
+        Map::new()
+    };
+    #[cfg(not(feature = "sorted_map"))]
+    let map = Map::with_capacity_and_hasher(len, Default::default());
+    map
+}
+
+/// Owned JSON value, detached from the source text and token stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Map<String, JsonValue>),
+    /// A [`CustomLiteral`](crate::CustomLiteral) match: its tag and the literal as written.
+    ///
+    /// Serializers write the literal back verbatim, so a document holding one is not JSON;
+    /// read it back with the same custom literals.
+    Custom(&'static str, String),
+    /// An integer literal beyond the `i64` and `u64` range, kept exact.
+    #[cfg(feature = "num-bigint")]
+    BigInt(num_bigint::BigInt),
+    /// A fractional number parsed under [`NumberMode::Decimal`], kept exact.
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
+}
+
+impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    // as_f64 The number as a float, rounding big integers to the nearest one
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Some(*n),
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(n) => num_traits::ToPrimitive::to_f64(n),
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(n) => rust_decimal::prelude::ToPrimitive::to_f64(n),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    pub fn as_decimal(&self) -> Option<rust_decimal::Decimal> {
+        match self {
+            JsonValue::Decimal(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "num-bigint")]
+    pub fn as_bigint(&self) -> Option<&num_bigint::BigInt> {
+        match self {
+            JsonValue::BigInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map<String, JsonValue>> {
+        match self {
+            JsonValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Name of the variant, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+            JsonValue::Custom(tag, _) => tag,
+            #[cfg(feature = "num-bigint")]
+            JsonValue::BigInt(_) => "number",
+            #[cfg(feature = "rust_decimal")]
+            JsonValue::Decimal(_) => "number",
+        }
+    }
+
+    #[cfg_attr(not(feature = "rust_decimal"), allow(clippy::only_used_in_recursion))]
+    pub(crate) fn from_expr(expr: JsonExpr, mode: NumberMode) -> Result<JsonValue, JError> {
+        Ok(match expr {
+            JsonExpr::Object(obj) => JsonValue::Object(
+                obj.into_iter()
+                    .map(|(k, v)| Ok((unescape(k)?, JsonValue::from_expr(v, mode)?)))
+                    .collect::<Result<_, JError>>()?,
+            ),
+            JsonExpr::Array(arr) => JsonValue::Array(
+                arr.into_iter()
+                    .map(|v| JsonValue::from_expr(v, mode))
+                    .collect::<Result<_, _>>()?,
+            ),
+            JsonExpr::String(s) => JsonValue::String(unescape(s)?),
+            JsonExpr::Number(n) => JsonValue::Number(n),
+            JsonExpr::Boolean(b) => JsonValue::Bool(b),
+            JsonExpr::Null => JsonValue::Null,
+            JsonExpr::Custom(tag, text) => JsonValue::Custom(tag, text.to_string()),
+            #[cfg(feature = "num-bigint")]
+            JsonExpr::BigInt(digits) => JsonValue::BigInt(parse_bigint(digits)),
+            #[cfg(feature = "rust_decimal")]
+            JsonExpr::Fraction(n, literal) => match mode {
+                NumberMode::Decimal => JsonValue::Decimal(parse_decimal(literal)?),
+                _ => JsonValue::Number(n),
+            },
+        })
+    }
+}
+
+/// JSON value whose strings and keys borrow from the source when they contain no
+/// escapes; escaped ones are decoded into owned buffers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedValue<'a>>),
+    Object(Map<Cow<'a, str>, BorrowedValue<'a>>),
+    Custom(&'static str, &'a str),
+    #[cfg(feature = "num-bigint")]
+    BigInt(num_bigint::BigInt),
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
+}
+
+impl<'a> BorrowedValue<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    // get Look up an object member by key
+    pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+        match self {
+            BorrowedValue::Object(obj) => obj.get(key),
+            _ => None,
+        }
+    }
+
+    // into_owned Detach the value from the source
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            BorrowedValue::Null => JsonValue::Null,
+            BorrowedValue::Bool(b) => JsonValue::Bool(b),
+            BorrowedValue::Number(n) => JsonValue::Number(n),
+            BorrowedValue::String(s) => JsonValue::String(s.into_owned()),
+            BorrowedValue::Array(arr) => {
+                JsonValue::Array(arr.into_iter().map(BorrowedValue::into_owned).collect())
+            }
+            BorrowedValue::Object(obj) => JsonValue::Object(
+                obj.into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+            ),
+            BorrowedValue::Custom(tag, text) => JsonValue::Custom(tag, text.to_string()),
+            #[cfg(feature = "num-bigint")]
+            BorrowedValue::BigInt(n) => JsonValue::BigInt(n),
+            #[cfg(feature = "rust_decimal")]
+            BorrowedValue::Decimal(n) => JsonValue::Decimal(n),
+        }
+    }
+
+    #[cfg_attr(not(feature = "rust_decimal"), allow(clippy::only_used_in_recursion))]
+    pub(crate) fn from_expr(
+        expr: JsonExpr<'a>,
+        mode: NumberMode,
+    ) -> Result<BorrowedValue<'a>, JError> {
+        Ok(match expr {
+            JsonExpr::Object(obj) => BorrowedValue::Object(
+                obj.into_iter()
+                    .map(|(k, v)| Ok((unescape_cow(k)?, BorrowedValue::from_expr(v, mode)?)))
+                    .collect::<Result<_, JError>>()?,
+            ),
+            JsonExpr::Array(arr) => BorrowedValue::Array(
+                arr.into_iter()
+                    .map(|v| BorrowedValue::from_expr(v, mode))
+                    .collect::<Result<_, _>>()?,
+            ),
+            JsonExpr::String(s) => BorrowedValue::String(unescape_cow(s)?),
+            JsonExpr::Number(n) => BorrowedValue::Number(n),
+            JsonExpr::Boolean(b) => BorrowedValue::Bool(b),
+            JsonExpr::Null => BorrowedValue::Null,
+            JsonExpr::Custom(tag, text) => BorrowedValue::Custom(tag, text),
+            #[cfg(feature = "num-bigint")]
+            JsonExpr::BigInt(digits) => BorrowedValue::BigInt(parse_bigint(digits)),
+            #[cfg(feature = "rust_decimal")]
+            JsonExpr::Fraction(n, literal) => match mode {
+                NumberMode::Decimal => BorrowedValue::Decimal(parse_decimal(literal)?),
+                _ => BorrowedValue::Number(n),
+            },
+        })
+    }
+}
+
+impl core::fmt::Display for JsonValue {
+    /// `{}` writes compact JSON, `{:#}` writes pretty JSON.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let options = if f.alternate() {
+            crate::ser::SerializeOptions::pretty()
+        } else {
+            crate::ser::SerializeOptions::compact()
+        };
+        crate::ser::write_value(f, self, &options)
+    }
+}
+
+// valid_number Same grammar as the tokenizer: `-?\d+(\.\d+)?([eE][+-]?\d+)?`
+pub(crate) fn valid_number(bytes: &[u8]) -> bool {
+    fn digits(bytes: &[u8]) -> (usize, &[u8]) {
+        let n = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        (n, &bytes[n..])
+    }
+
+    let rest = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    let (n, mut rest) = digits(rest);
+    if n == 0 {
+        return false;
+    }
+    if let Some(frac) = rest.strip_prefix(b".") {
+        let (n, after) = digits(frac);
+        if n == 0 {
+            return false;
+        }
+        rest = after;
+    }
+    if let Some(exp) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exp = exp
+            .strip_prefix(b"+")
+            .or_else(|| exp.strip_prefix(b"-"))
+            .unwrap_or(exp);
+        let (n, after) = digits(exp);
+        if n == 0 {
+            return false;
+        }
+        rest = after;
+    }
+    rest.is_empty()
+}
+
+// raw_number Rebuild a number from its literal text, as an exact type where `mode` asks for one
+//
+// A fraction a decimal cannot hold exactly falls back to the nearest f64.
+#[cfg_attr(not(feature = "rust_decimal"), allow(unused_variables))]
+pub(crate) fn raw_number(literal: &str, mode: NumberMode) -> Result<JsonValue, JError> {
+    if !valid_number(literal.as_bytes()) {
+        return Err(JError::new(
+            ErrorKind::Syntax,
+            format!("Invalid number literal {literal:?}"),
+        ));
+    }
+    #[cfg(feature = "num-bigint")]
+    if is_big_integer(literal) {
+        return Ok(JsonValue::BigInt(parse_bigint(literal)));
+    }
+    #[cfg(feature = "rust_decimal")]
+    if mode == NumberMode::Decimal && literal.contains(['.', 'e', 'E']) {
+        if let Ok(n) = parse_decimal(literal) {
+            return Ok(JsonValue::Decimal(n));
+        }
+    }
+    let n = literal.parse().expect("a valid literal parses as f64");
+    Ok(JsonValue::Number(n))
+}
+
+// is_big_integer Whether a number literal is an integer outside the i64 and u64 range
+#[cfg(feature = "num-bigint")]
+pub(crate) fn is_big_integer(literal: &str) -> bool {
+    let digits = literal.strip_prefix('-').unwrap_or(literal);
+    digits.len() >= 19
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && literal.parse::<i64>().is_err()
+        && literal.parse::<u64>().is_err()
+}
+
+#[cfg(feature = "num-bigint")]
+fn parse_bigint(digits: &str) -> num_bigint::BigInt {
+    digits
+        .parse()
+        .expect("the lexer only passes integer literals")
+}
+
+// parse_decimal Read a fractional literal exactly, failing rather than rounding
+#[cfg(feature = "rust_decimal")]
+fn parse_decimal(literal: &str) -> Result<rust_decimal::Decimal, JError> {
+    let parsed = match literal.contains(['e', 'E']) {
+        true => rust_decimal::Decimal::from_scientific(literal),
+        false => rust_decimal::Decimal::from_str_exact(literal),
+    };
+    parsed.map_err(|_| {
+        JError::new(
+            ErrorKind::Unsupported,
+            format!("Number {literal} does not fit a decimal exactly"),
+        )
+    })
+}
+
+// unescape Decode the escape sequences of a string literal body (quotes already trimmed)
+pub(crate) fn unescape(raw: &str) -> Result<String, JError> {
+    if !raw.contains('\\') {
+        return Ok(raw.to_string());
+    }
+
+    let mut out = String::with_capacity(raw.len());
+    unescape_with(raw, |c| out.push(c))?;
+    Ok(out)
+}
+
+// unescape_cow Like `unescape`, borrowing the body when it has no escapes
+pub(crate) fn unescape_cow(raw: &str) -> Result<Cow<'_, str>, JError> {
+    match raw.contains('\\') {
+        true => unescape(raw).map(Cow::Owned),
+        false => Ok(Cow::Borrowed(raw)),
+    }
+}
+
+// unescape_with Decode the escape sequences of a string literal body, feeding every char to `out`
+pub(crate) fn unescape_with(raw: &str, mut out: impl FnMut(char)) -> Result<(), JError> {
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => out('"'),
+            Some('\\') => out('\\'),
+            Some('/') => out('/'),
+            Some('b') => out('\u{8}'),
+            Some('f') => out('\u{c}'),
+            Some('n') => out('\n'),
+            Some('r') => out('\r'),
+            Some('t') => out('\t'),
+            Some('u') => {
+                let high = read_hex4(&mut chars)?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    if chars.next() != Some('\\') || chars.next() != Some('u') {
+                        return Err(JError::new(
+                            ErrorKind::Escape,
+                            format!("Unpaired surrogate \\u{high:04x}"),
+                        ));
+                    }
+                    let low = read_hex4(&mut chars)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(JError::new(
+                            ErrorKind::Escape,
+                            format!("Invalid low surrogate \\u{low:04x}"),
+                        ));
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                match char::from_u32(code) {
+                    Some(c) => out(c),
+                    None => {
+                        return Err(JError::new(
+                            ErrorKind::Escape,
+                            format!("Invalid code point \\u{code:04x}"),
+                        ))
+                    }
+                }
+            }
+            Some(other) => {
+                return Err(JError::new(
+                    ErrorKind::Escape,
+                    format!("Invalid escape \\{other}"),
+                ))
+            }
+            None => return Err(JError::new(ErrorKind::Escape, "Unterminated escape")),
+        }
+    }
+    Ok(())
+}
+
+fn read_hex4(chars: &mut core::str::Chars) -> Result<u32, JError> {
+    let hex: String = chars.take(4).collect();
+    match u32::from_str_radix(&hex, 16) {
+        Ok(code) if hex.len() == 4 => Ok(code),
+        _ => Err(JError::new(
+            ErrorKind::Escape,
+            format!("Invalid unicode escape \\u{hex}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, string::ToString, vec, vec::Vec};
+
+    use crate::{parse, parse_borrowed};
+
+    use super::{unescape, BorrowedValue, JsonValue};
+
+    #[test]
+    fn it_unescape() {
+        assert_eq!(unescape(r#"a\"b\\c\n"#).unwrap(), "a\"b\\c\n");
+        assert_eq!(unescape(r"\u676d\u5dde").unwrap(), "杭州");
+        assert_eq!(unescape(r"\ud83d\ude00").unwrap(), "😀");
+        assert!(unescape(r"\x").is_err());
+        assert!(unescape(r"\ud83d").is_err());
+    }
+
+    #[test]
+    fn it_parse_value() {
+        let value = parse(r#"{"name": "John\tDoe", "tags": [null, true, 1.5]}"#).unwrap();
+        let obj = value.as_object().unwrap();
+
+        assert_eq!(obj["name"], JsonValue::String("John\tDoe".to_string()));
+        assert_eq!(
+            obj["tags"],
+            JsonValue::Array(vec![
+                JsonValue::Null,
+                JsonValue::Bool(true),
+                JsonValue::Number(1.5),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_parse_value_rejects_trailing_input() {
+        assert!(parse("[1, 2] 3").is_err());
+        assert!(parse("[1, 2] @").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn it_parse_borrowed() {
+        let source = r#"{"name": "John", "quote\u0021": "a\"b", "tags": ["x"]}"#;
+        let value = parse_borrowed(source).unwrap();
+
+        assert!(matches!(
+            value.get("name"),
+            Some(BorrowedValue::String(Cow::Borrowed("John")))
+        ));
+        assert!(
+            matches!(value.get("quote!"), Some(BorrowedValue::String(Cow::Owned(s))) if s == "a\"b")
+        );
+        match &value {
+            BorrowedValue::Object(obj) => {
+                assert!(obj
+                    .keys()
+                    .all(|k| matches!(k, Cow::Borrowed(_)) == (k != "quote!")))
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(value.into_owned(), parse(source).unwrap());
+    }
+
+    #[test]
+    fn it_map_backend_order() {
+        let value = parse(r#"{"b": 1, "c": 2, "a": 3}"#).unwrap();
+        let keys = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "sorted_map")]
+        assert_eq!(keys, ["a", "b", "c"]);
+        #[cfg(not(any(feature = "sorted_map", feature = "hash_map")))]
+        assert_eq!(keys, ["b", "c", "a"]);
+        assert_eq!(keys.len(), 3);
+    }
+}
@@ -0,0 +1,147 @@
+use yaml_rust2::{yaml::Hash, Yaml, YamlEmitter, YamlLoader};
+
+use crate::{
+    error::{ErrorKind, JError},
+    JsonValue,
+};
+
+// from_yaml Load the first document of a YAML stream as a value
+//
+// Mapping keys must be scalars; numeric and boolean keys are converted to
+// their string form. An empty stream loads as `null`.
+pub fn from_yaml(source: &str) -> Result<JsonValue, JError> {
+    let docs = YamlLoader::load_from_str(source)
+        .map_err(|e| JError::new(ErrorKind::Syntax, format!("Invalid YAML: {e}")))?;
+    match docs.into_iter().next() {
+        Some(doc) => convert(doc),
+        None => Ok(JsonValue::Null),
+    }
+}
+
+fn convert(yaml: Yaml) -> Result<JsonValue, JError> {
+    Ok(match yaml {
+        Yaml::Null => JsonValue::Null,
+        Yaml::Boolean(b) => JsonValue::Bool(b),
+        Yaml::Integer(i) => JsonValue::Number(i as f64),
+        Yaml::Real(ref raw) => match yaml.as_f64() {
+            Some(n) => JsonValue::Number(n),
+            None => {
+                return Err(JError::new(
+                    ErrorKind::Syntax,
+                    format!("Invalid YAML number {raw:?}"),
+                ))
+            }
+        },
+        Yaml::String(s) => JsonValue::String(s),
+        Yaml::Array(arr) => {
+            JsonValue::Array(arr.into_iter().map(convert).collect::<Result<_, _>>()?)
+        }
+        Yaml::Hash(hash) => JsonValue::Object(
+            hash.into_iter()
+                .map(|(k, v)| Ok((key(k)?, convert(v)?)))
+                .collect::<Result<_, JError>>()?,
+        ),
+        Yaml::Alias(_) => {
+            return Err(JError::new(
+                ErrorKind::Unsupported,
+                "YAML aliases are not supported",
+            ))
+        }
+        Yaml::BadValue => return Err(JError::new(ErrorKind::Syntax, "Invalid YAML value")),
+    })
+}
+
+fn key(yaml: Yaml) -> Result<String, JError> {
+    match yaml {
+        Yaml::String(s) | Yaml::Real(s) => Ok(s),
+        Yaml::Integer(i) => Ok(i.to_string()),
+        Yaml::Boolean(b) => Ok(b.to_string()),
+        Yaml::Null => Ok("null".to_string()),
+        _ => Err(JError::new(
+            ErrorKind::Syntax,
+            "YAML mapping keys must be scalars",
+        )),
+    }
+}
+
+// to_yaml Write the value as a YAML document
+pub fn to_yaml(value: &JsonValue) -> String {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(&to_yaml_node(value))
+        .expect("writing to a String cannot fail");
+    out.push('\n');
+    out
+}
+
+fn to_yaml_node(value: &JsonValue) -> Yaml {
+    match value {
+        JsonValue::Null => Yaml::Null,
+        JsonValue::Bool(b) => Yaml::Boolean(*b),
+        JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 9.2e18 => Yaml::Integer(*n as i64),
+        JsonValue::Number(n) if n.is_nan() => Yaml::Real(".nan".to_string()),
+        JsonValue::Number(n) if n.is_infinite() => {
+            Yaml::Real(if *n > 0.0 { ".inf" } else { "-.inf" }.to_string())
+        }
+        JsonValue::Number(n) => Yaml::Real(n.to_string()),
+        #[cfg(feature = "num-bigint")]
+        JsonValue::BigInt(n) => Yaml::Real(n.to_string()),
+        #[cfg(feature = "rust_decimal")]
+        JsonValue::Decimal(n) => Yaml::Real(n.to_string()),
+        JsonValue::String(s) | JsonValue::Custom(_, s) => Yaml::String(s.clone()),
+        JsonValue::Array(arr) => Yaml::Array(arr.iter().map(to_yaml_node).collect()),
+        JsonValue::Object(obj) => Yaml::Hash(
+            obj.iter()
+                .map(|(k, v)| (Yaml::String(k.clone()), to_yaml_node(v)))
+                .collect::<Hash>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::{from_yaml, to_yaml};
+
+    #[test]
+    fn it_from_yaml() {
+        let value = from_yaml(
+            "name: John Doe\n\
+             age: 30\n\
+             ratio: 0.5\n\
+             scores: [100, 90]\n\
+             address:\n  city: Springfield\n  zip: ~\n\
+             1: one\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            parse(
+                r#"{
+                    "name": "John Doe",
+                    "age": 30,
+                    "ratio": 0.5,
+                    "scores": [100, 90],
+                    "address": {"city": "Springfield", "zip": null},
+                    "1": "one"
+                }"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_yaml_round_trip() {
+        let value = parse(r#"{"b": [1, 2.5, "x: y"], "a": {"ok": true}, "n": null}"#).unwrap();
+
+        assert_eq!(from_yaml(&to_yaml(&value)).unwrap(), value);
+    }
+
+    #[test]
+    fn it_from_yaml_rejects_complex_keys() {
+        assert!(from_yaml("? [1, 2]\n: x\n").is_err());
+        assert!(from_yaml("a: [1\n").is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use jnom_rs::validate;
+
+// Counts the allocations made by the current thread, so tests running alongside do not interfere
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(|n| n.get());
+    f();
+    ALLOCATIONS.with(|n| n.get()) - before
+}
+
+#[test]
+fn it_validate_allocations_do_not_grow_with_values() {
+    let one = r#"[{"name": "a\nb", "n": 1}]"#;
+    let many = format!("[{}]", [r#"{"name": "a\nb", "n": 1}"#; 1000].join(", "));
+    let cost = |source: &str| allocations(|| assert!(validate(source).is_ok()));
+
+    assert_eq!(cost(&many), cost(one));
+}